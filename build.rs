@@ -7,11 +7,20 @@
 //! Cargo re-run the build script whenever `memory.x` is changed,
 //! updating `memory.x` ensures a rebuild of the application with the
 //! new memory settings.
+//!
+//! It also packs the images in `assets/` for `vfdgraphics`, see
+//! `build/assetgen.rs`, and converts the fonts in `assets/fonts/` for
+//! `fonts`, see `build/fontgen.rs`.
 
 use std::env;
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+#[path = "build/assetgen.rs"]
+mod assetgen;
+#[path = "build/fontgen.rs"]
+mod fontgen;
 
 fn main() {
     // Put `memory.x` in our output directory and ensure it's
@@ -29,6 +38,11 @@ fn main() {
     // `memory.x` is changed.
     println!("cargo:rerun-if-changed=memory.x");
 
+    assetgen::generate(Path::new("assets"), out);
+    println!("cargo:rerun-if-changed=build/assetgen.rs");
+    fontgen::generate(Path::new("assets/fonts"), out);
+    println!("cargo:rerun-if-changed=build/fontgen.rs");
+
     println!("cargo:rustc-link-arg-bins=--nmagic");
     println!("cargo:rustc-link-arg-bins=-Tlink.x");
     println!("cargo:rustc-link-arg-bins=-Tdefmt.x");
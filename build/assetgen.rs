@@ -0,0 +1,166 @@
+//! Converts the BMPs in `assets/` into packed 1bpp images at build time.
+//!
+//! Shared by the firmware build script and the host-tests one so both see the
+//! same image data. Each `NAME.bmp` becomes a `pub const NAME: PackedImage`
+//! in `$OUT_DIR/assets.rs`, along with an `ALL` list of every image.
+//!
+//! Pixels are stored column by column, top to bottom, most significant bit
+//! first, which is how `Display256x50` lays out the display RAM.
+//! Colour conversion goes through `tinybmp` so the result is exactly what
+//! drawing the BMP would have produced.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use tinybmp::{Bmp, Bpp};
+
+/// Visible area of the VFD, every image has to fit inside it
+const SCREEN_WIDTH: i32 = 256;
+const SCREEN_HEIGHT: i32 = 50;
+
+/// Read `manifest.txt` into file name -> offset
+fn read_manifest(assets: &Path) -> BTreeMap<String, (i32, i32)> {
+    let path = assets.join("manifest.txt");
+    let Ok(text) = fs::read_to_string(&path) else {
+        return BTreeMap::new();
+    };
+
+    let mut offsets = BTreeMap::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let parsed = match fields[..] {
+            [file, x, y] => x.parse().ok().zip(y.parse().ok()).map(|o| (file, o)),
+            _ => None,
+        };
+        let Some((file, offset)) = parsed else {
+            panic!("{}:{}: expected `file x y`, got `{line}`", path.display(), n + 1);
+        };
+        offsets.insert(file.to_string(), offset);
+    }
+    offsets
+}
+
+/// `ClimateControlBackground.bmp` -> `CLIMATECONTROLBACKGROUND`
+fn const_name(stem: &str) -> String {
+    let name: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+fn pack(file: &str, bytes: &[u8], (x, y): (i32, i32)) -> (u32, u32, Vec<u8>) {
+    let bmp = Bmp::<BinaryColor>::from_slice(bytes)
+        .unwrap_or_else(|e| panic!("assets/{file}: not a BMP we can read: {e:?}"));
+    let header = bmp.as_raw().header();
+    let Size { width, height } = header.image_size;
+
+    match header.bpp {
+        Bpp::Bits1 | Bpp::Bits8 | Bpp::Bits16 | Bpp::Bits24 => {}
+        bpp => panic!("assets/{file}: {bpp:?} images aren't supported, use 1, 8, 16 or 24 bits"),
+    }
+    if width == 0 || height == 0 {
+        panic!("assets/{file}: image is empty");
+    }
+    if x < 0
+        || y < 0
+        || x + width as i32 > SCREEN_WIDTH
+        || y + height as i32 > SCREEN_HEIGHT
+    {
+        panic!(
+            "assets/{file}: {width}x{height} at ({x}, {y}) doesn't fit on the \
+             {SCREEN_WIDTH}x{SCREEN_HEIGHT} screen"
+        );
+    }
+
+    let column_bytes = height.div_ceil(8) as usize;
+    let mut data = vec![0u8; column_bytes * width as usize];
+    for Pixel(p, color) in bmp.pixels() {
+        if color.is_on() {
+            let (px, py) = (p.x as usize, p.y as usize);
+            data[px * column_bytes + py / 8] |= 0x80 >> (py % 8);
+        }
+    }
+    (width, height, data)
+}
+
+/// Pack every BMP in `assets` and write the constants to `out`
+pub fn generate(assets: &Path, out: &Path) {
+    println!("cargo:rerun-if-changed={}", assets.display());
+
+    let offsets = read_manifest(assets);
+
+    let mut files: Vec<String> = fs::read_dir(assets)
+        .unwrap_or_else(|e| panic!("can't read {}: {e}", assets.display()))
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.to_ascii_lowercase().ends_with(".bmp"))
+        .collect();
+    files.sort();
+
+    for listed in offsets.keys() {
+        if !files.contains(listed) {
+            panic!("assets/manifest.txt lists {listed}, which isn't in assets/");
+        }
+    }
+
+    let mut code = String::from("// Generated from assets/ by build/assetgen.rs, do not edit.\n\n");
+    let mut names = Vec::new();
+    for file in &files {
+        let bytes = fs::read(assets.join(file)).unwrap();
+        let offset = offsets.get(file).copied().unwrap_or((0, 0));
+        let (width, height, data) = pack(file, &bytes, offset);
+
+        let stem = &file[..file.len() - 4];
+        let name = const_name(stem);
+        if names.iter().any(|(n, _)| *n == name) {
+            panic!("assets/{file}: another image already uses the name {name}");
+        }
+
+        let _ = writeln!(
+            code,
+            "/// `{file}`, {width}x{height} at ({}, {})",
+            offset.0, offset.1
+        );
+        let _ = write!(
+            code,
+            "pub const {name}: PackedImage = PackedImage::new({width}, {height}, \
+             Point::new({}, {}), &[",
+            offset.0, offset.1
+        );
+        for line in data.chunks(16) {
+            code.push_str("\n   ");
+            for byte in line {
+                let _ = write!(code, " 0x{byte:02x},");
+            }
+        }
+        code.push_str("\n]);\n\n");
+        names.push((name, stem.to_string()));
+    }
+
+    code.push_str("/// Every packed image with the file name it came from\n");
+    code.push_str("pub const ALL: &[(&str, &PackedImage)] = &[\n");
+    for (name, stem) in &names {
+        let _ = writeln!(code, "    (\"{stem}\", &{name}),");
+    }
+    code.push_str("];\n");
+
+    fs::write(out.join("assets.rs"), code).unwrap();
+}
@@ -0,0 +1,219 @@
+//! Converts the BDF fonts in `assets/fonts/` into bitmap fonts at build time.
+//!
+//! Shared by the firmware build script and the host-tests one, like
+//! `assetgen`. Each `name.bdf` becomes a `pub const NAME: BitmapFont` in
+//! `$OUT_DIR/fonts.rs`, along with an `ALL` list of every font.
+//!
+//! Only what drawing needs is kept: the ascent and descent, and for every
+//! glyph its advance, bounding box and bitmap. Glyph rows are stored top to
+//! bottom, most significant bit first and padded to whole bytes, the same as
+//! the BDF `BITMAP` lines. Glyphs are sorted by character so `fonts` can look
+//! them up with a binary search.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+struct Glyph {
+    ch: char,
+    advance: i32,
+    /// Bounding box, offset from the origin on the baseline with y going up
+    width: u32,
+    height: u32,
+    x: i32,
+    y: i32,
+    rows: Vec<u8>,
+}
+
+struct Font {
+    ascent: i32,
+    descent: i32,
+    glyphs: Vec<Glyph>,
+}
+
+/// The `N` numbers after a keyword
+fn numbers<const N: usize>(fields: &[&str]) -> Option<[i32; N]> {
+    let mut out = [0; N];
+    if fields.len() != N {
+        return None;
+    }
+    for (out, field) in out.iter_mut().zip(fields) {
+        *out = field.parse().ok()?;
+    }
+    Some(out)
+}
+
+fn parse(path: &Path, text: &str) -> Font {
+    let mut ascent = None;
+    let mut descent = None;
+    let mut glyphs = Vec::new();
+    // the glyph being read, and whether its bitmap rows are next
+    let mut glyph: Option<Glyph> = None;
+    let mut bitmap = false;
+
+    for (n, line) in text.lines().enumerate() {
+        let fail =
+            |what: &str| -> ! { panic!("{}:{}: {what}, got `{line}`", path.display(), n + 1) };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let Some((&keyword, args)) = fields.split_first() else {
+            continue;
+        };
+
+        if bitmap && keyword != "ENDCHAR" {
+            let glyph = glyph.as_mut().unwrap();
+            let bytes = (glyph.width as usize).div_ceil(8);
+            if keyword.len() != bytes * 2 {
+                fail(&format!("expected {bytes} bytes of bitmap"));
+            }
+            for i in 0..bytes {
+                let byte = u8::from_str_radix(&keyword[i * 2..i * 2 + 2], 16)
+                    .unwrap_or_else(|_| fail("expected hex bitmap"));
+                glyph.rows.push(byte);
+            }
+            continue;
+        }
+
+        match keyword {
+            "FONT_ASCENT" => {
+                ascent = Some(numbers::<1>(args).unwrap_or_else(|| fail("expected a number"))[0])
+            }
+            "FONT_DESCENT" => {
+                descent = Some(numbers::<1>(args).unwrap_or_else(|| fail("expected a number"))[0])
+            }
+            "STARTCHAR" => {
+                if glyph.is_some() {
+                    fail("STARTCHAR before ENDCHAR");
+                }
+                glyph = Some(Glyph {
+                    ch: '\0',
+                    advance: 0,
+                    width: 0,
+                    height: 0,
+                    x: 0,
+                    y: 0,
+                    rows: Vec::new(),
+                });
+            }
+            "ENCODING" | "DWIDTH" | "BBX" | "BITMAP" | "ENDCHAR" => {
+                let Some(g) = glyph.as_mut() else {
+                    fail("outside of a STARTCHAR");
+                };
+                match keyword {
+                    "ENCODING" => {
+                        let [code] = numbers(args).unwrap_or_else(|| fail("expected a number"));
+                        g.ch = u32::try_from(code)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .unwrap_or_else(|| fail("not a character"));
+                    }
+                    "DWIDTH" => {
+                        let [x, _] = numbers(args).unwrap_or_else(|| fail("expected `DWIDTH x y`"));
+                        g.advance = x;
+                    }
+                    "BBX" => {
+                        let [w, h, x, y] =
+                            numbers(args).unwrap_or_else(|| fail("expected `BBX w h x y`"));
+                        if w < 0 || h < 0 {
+                            fail("negative size");
+                        }
+                        (g.width, g.height, g.x, g.y) = (w as u32, h as u32, x, y);
+                    }
+                    "BITMAP" => bitmap = true,
+                    _ => {
+                        let g = glyph.take().unwrap();
+                        let bytes = (g.width as usize).div_ceil(8) * g.height as usize;
+                        if g.rows.len() != bytes {
+                            fail(&format!("expected {} bitmap rows", g.height));
+                        }
+                        if g.ch == '\0' {
+                            fail("glyph without an ENCODING");
+                        }
+                        bitmap = false;
+                        glyphs.push(g);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (Some(ascent), Some(descent)) = (ascent, descent) else {
+        panic!("{}: needs FONT_ASCENT and FONT_DESCENT", path.display());
+    };
+    glyphs.sort_by_key(|g| g.ch);
+    for pair in glyphs.windows(2) {
+        if pair[0].ch == pair[1].ch {
+            panic!("{}: {:?} is in there twice", path.display(), pair[0].ch);
+        }
+    }
+    Font {
+        ascent,
+        descent,
+        glyphs,
+    }
+}
+
+/// Convert every BDF in `fonts` and write the constants to `out`
+pub fn generate(fonts: &Path, out: &Path) {
+    println!("cargo:rerun-if-changed={}", fonts.display());
+
+    let mut files: Vec<String> = fs::read_dir(fonts)
+        .unwrap_or_else(|e| panic!("can't read {}: {e}", fonts.display()))
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.to_ascii_lowercase().ends_with(".bdf"))
+        .collect();
+    files.sort();
+
+    let mut code =
+        String::from("// Generated from assets/fonts/ by build/fontgen.rs, do not edit.\n\n");
+    let mut names = Vec::new();
+    for file in &files {
+        let path = fonts.join(file);
+        let text = fs::read_to_string(&path).unwrap();
+        let font = parse(&path, &text);
+
+        let stem = &file[..file.len() - 4];
+        let name = stem
+            .to_ascii_uppercase()
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+
+        let _ = writeln!(
+            code,
+            "/// `{file}`, {} glyphs, {} above the baseline and {} below",
+            font.glyphs.len(),
+            font.ascent,
+            font.descent
+        );
+        let _ = writeln!(
+            code,
+            "pub const {name}: BitmapFont = BitmapFont::new({}, {}, &[",
+            font.ascent, font.descent
+        );
+        for g in &font.glyphs {
+            // to e-g coordinates, the top left corner relative to the origin,
+            // which is the bottom row of the capitals rather than the line
+            // under them
+            let top = -(g.y + g.height as i32 - 1);
+            let _ = write!(
+                code,
+                "    Glyph::new({:?}, {}, Point::new({}, {top}), Size::new({}, {}), &[",
+                g.ch, g.advance, g.x, g.width, g.height
+            );
+            for byte in &g.rows {
+                let _ = write!(code, "0x{byte:02x}, ");
+            }
+            code.push_str("]),\n");
+        }
+        code.push_str("]);\n\n");
+        names.push((name, stem.to_string()));
+    }
+
+    code.push_str("/// Every font with the file name it came from\n");
+    code.push_str("pub const ALL: &[(&str, &BitmapFont)] = &[\n");
+    for (name, stem) in &names {
+        let _ = writeln!(code, "    (\"{stem}\", &{name}),");
+    }
+    code.push_str("];\n");
+
+    fs::write(out.join("fonts.rs"), code).unwrap();
+}
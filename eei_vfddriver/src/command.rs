@@ -1,4 +1,4 @@
-//! SPI Commands for the EEI GB1287BI VFD
+//! SPI commands understood by the EEI VFD controllers
 use crate::traits;
 
 #[allow(dead_code)]
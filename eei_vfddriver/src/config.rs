@@ -0,0 +1,268 @@
+//! Power up parameters, shared by every [`Model`]
+//!
+//! The settings without a documented meaning are newtypes over their
+//! argument bytes, each with the `VENDOR` value [`InitConfig`] starts from.
+
+use crate::command::Command;
+use crate::driver::{brightness_args, MAX_BRIGHTNESS};
+use crate::error::Error;
+use crate::model::Model;
+use crate::scroll::Offset;
+
+/// How the controller scans the grids, the two bytes of the VFD mode
+/// setting.
+///
+/// The bits aren't described in any public documentation, so other values
+/// are passed through unchecked for panels that need them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScanDirection(pub [u8; 2]);
+
+impl ScanDirection {
+    /// `[0x02, 0x00]`, from the vendor's sample code. Display RAM column 0
+    /// is the left of the panel and row 0 the top, the way round every frame
+    /// in this crate is laid out.
+    pub const VENDOR: ScanDirection = ScanDirection([0x02, 0x00]);
+}
+
+/// The oscillator setting byte, the clock the controller scans the panel
+/// with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Oscillator(pub u8);
+
+impl Oscillator {
+    /// `0x08`, from the vendor's sample code
+    pub const VENDOR: Oscillator = Oscillator(0x08);
+}
+
+/// The four bytes of the internal speed setting, timing of the grid and
+/// anode drive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InternalSpeed(pub [u8; 4]);
+
+impl InternalSpeed {
+    /// `[0x20, 0x3F, 0x00, 0x01]`, from the vendor's sample code
+    pub const VENDOR: InternalSpeed = InternalSpeed([0x20, 0x3F, 0x00, 0x01]);
+}
+
+/// The five display area setting bytes after the column and row counts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AreaOptions(pub [u8; 5]);
+
+impl AreaOptions {
+    /// `[0x00, 0x20, 0x00, 0x00, 0x80]`, from the vendor's sample code
+    pub const VENDOR: AreaOptions = AreaOptions([0x00, 0x20, 0x00, 0x00, 0x80]);
+}
+
+/// The frame sync setting byte, when a newly written frame takes over from
+/// the one on the panel. See [`crate::grayscale`] for where it matters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameSync(pub u8);
+
+impl FrameSync {
+    /// `0x00`, from the vendor's sample code
+    pub const VENDOR: FrameSync = FrameSync(0x00);
+}
+
+/// The display mode setting byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisplayMode(pub u8);
+
+impl DisplayMode {
+    /// `0x00`, from the vendor's sample code, the display RAM shown as it is
+    pub const VENDOR: DisplayMode = DisplayMode(0x00);
+}
+
+/// Settings sent to the controller when it is initialised.
+///
+/// Every model has its own defaults in [`Model::DEFAULT_CONFIG`]: its lit
+/// area, and the `VENDOR` value of each setting, taken from the vendor's
+/// sample code.
+///
+/// ```
+/// use eei_vfd::gp1287bi;
+///
+/// let config = gp1287bi::DEFAULT_CONFIG.display_area(256, 48).brightness(200);
+/// assert_eq!(config.size(), (256, 48));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InitConfig {
+    columns: u32,
+    rows: u32,
+    area_options: AreaOptions,
+    oscillator: Oscillator,
+    scan_direction: ScanDirection,
+    internal_speed: InternalSpeed,
+    brightness: u32,
+    position1_offset: Offset,
+    position2_offset: Offset,
+    frame_sync: FrameSync,
+    display_mode: DisplayMode,
+}
+
+impl InitConfig {
+    /// The vendor defaults with a lit area of `columns` x `rows`
+    pub const fn new(columns: u32, rows: u32) -> Self {
+        InitConfig {
+            columns,
+            rows,
+            area_options: AreaOptions::VENDOR,
+            oscillator: Oscillator::VENDOR,
+            scan_direction: ScanDirection::VENDOR,
+            internal_speed: InternalSpeed::VENDOR,
+            brightness: 0x30,
+            position1_offset: Offset::new(0, 0),
+            position2_offset: Offset::new(0, 0),
+            frame_sync: FrameSync::VENDOR,
+            display_mode: DisplayMode::VENDOR,
+        }
+    }
+
+    /// Lit area of the panel in pixels, `columns` along the grids and `rows`
+    /// down them.
+    ///
+    /// Has to fit in the model's display RAM.
+    pub const fn display_area(mut self, columns: u32, rows: u32) -> Self {
+        self.columns = columns;
+        self.rows = rows;
+        self
+    }
+
+    /// The rest of the display area setting. Default [`AreaOptions::VENDOR`].
+    pub const fn area_options(mut self, options: AreaOptions) -> Self {
+        self.area_options = options;
+        self
+    }
+
+    /// Default [`Oscillator::VENDOR`].
+    pub const fn oscillator(mut self, oscillator: Oscillator) -> Self {
+        self.oscillator = oscillator;
+        self
+    }
+
+    /// Default [`ScanDirection::VENDOR`].
+    pub const fn scan_direction(mut self, direction: ScanDirection) -> Self {
+        self.scan_direction = direction;
+        self
+    }
+
+    /// Default [`InternalSpeed::VENDOR`].
+    pub const fn internal_speed(mut self, speed: InternalSpeed) -> Self {
+        self.internal_speed = speed;
+        self
+    }
+
+    /// Brightness right after power up, 0 to 1023. Default `0x30`, dim
+    /// enough not to flash at power up.
+    pub const fn brightness(mut self, level: u32) -> Self {
+        self.brightness = level;
+        self
+    }
+
+    /// Where display positions 1 and 2 start showing the display RAM, see
+    /// [`crate::scroll`]. Both have to be inside the model's display RAM.
+    /// Default the first column and row for both.
+    pub const fn position_offsets(mut self, position1: Offset, position2: Offset) -> Self {
+        self.position1_offset = position1;
+        self.position2_offset = position2;
+        self
+    }
+
+    /// Default [`FrameSync::VENDOR`].
+    pub const fn frame_sync(mut self, frame_sync: FrameSync) -> Self {
+        self.frame_sync = frame_sync;
+        self
+    }
+
+    /// Default [`DisplayMode::VENDOR`].
+    pub const fn display_mode(mut self, mode: DisplayMode) -> Self {
+        self.display_mode = mode;
+        self
+    }
+
+    /// Lit area as (columns, rows)
+    pub const fn size(&self) -> (u32, u32) {
+        (self.columns, self.rows)
+    }
+
+    /// Check the settings and work out the argument bytes
+    pub(crate) fn args<M: Model, SpiE, PinE>(&self) -> Result<InitArgs, Error<SpiE, PinE>> {
+        if !(1..=M::GRAM_COLUMNS).contains(&self.columns)
+            || !(1..=M::GRAM_ROWS).contains(&self.rows)
+        {
+            return Err(Error::WindowOutOfBounds);
+        }
+        if self.brightness > MAX_BRIGHTNESS {
+            return Err(Error::BrightnessOutOfRange(self.brightness));
+        }
+
+        let [a, b, c, d, e] = self.area_options.0;
+        Ok(InitArgs {
+            oscillation: [self.oscillator.0],
+            vfd_mode: self.scan_direction.0,
+            display_area: [
+                (self.columns - 1) as u8,
+                (self.rows - 1) as u8,
+                a,
+                b,
+                c,
+                d,
+                e,
+            ],
+            internal_speed: self.internal_speed.0,
+            brightness: brightness_args(self.brightness),
+            position1_offset: self.position1_offset.args::<M, _, _>()?,
+            position2_offset: self.position2_offset.args::<M, _, _>()?,
+            frame_sync: [self.frame_sync.0],
+            display_mode: [self.display_mode.0],
+        })
+    }
+}
+
+/// One step of the power up sequence
+#[derive(Copy, Clone)]
+pub(crate) enum InitStep<'a> {
+    /// Hold the reset pin low for this many ms
+    Reset(u32),
+    Command(Command, &'a [u8]),
+    DelayMs(u32),
+}
+
+/// Argument bytes of every init command, from a checked [`InitConfig`]
+pub(crate) struct InitArgs {
+    oscillation: [u8; 1],
+    vfd_mode: [u8; 2],
+    display_area: [u8; 7],
+    internal_speed: [u8; 4],
+    brightness: [u8; 2],
+    position1_offset: [u8; 2],
+    position2_offset: [u8; 2],
+    frame_sync: [u8; 1],
+    display_mode: [u8; 1],
+}
+
+impl InitArgs {
+    /// Power up sequence, shared by the blocking and async drivers so both
+    /// send exactly the same bytes. Based on the spec (not public accessible).
+    pub(crate) fn steps(&self) -> [InitStep<'_>; 15] {
+        [
+            InitStep::Reset(1),
+            // software reset
+            InitStep::Command(Command::Reset, &[]),
+            InitStep::DelayMs(1),
+            InitStep::Command(Command::OscillationSetting, &self.oscillation),
+            InitStep::Command(Command::VFDModeSetting, &self.vfd_mode),
+            InitStep::Command(Command::DisplayAreaSetting, &self.display_area),
+            InitStep::Command(Command::InternalSpeedSetting, &self.internal_speed),
+            InitStep::Command(Command::BrightnessSetting, &self.brightness),
+            // clear gram
+            InitStep::Command(Command::ClearGRAM, &[]),
+            InitStep::DelayMs(15),
+            InitStep::Command(Command::DisplayPosition1Offset, &self.position1_offset),
+            InitStep::Command(Command::DisplayPosition2Offset, &self.position2_offset),
+            // unknown
+            InitStep::Command(Command::UnknownInit, &[0x00]),
+            InitStep::Command(Command::FrameSyncSetting, &self.frame_sync),
+            InitStep::Command(Command::DisplayModeSetting, &self.display_mode),
+        ]
+    }
+}
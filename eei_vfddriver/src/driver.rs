@@ -0,0 +1,357 @@
+//! The driver shared by every [`Model`]
+//!
+//! [`Vfd`] talks to a module over blocking SPI, [`VfdAsync`] over async SPI.
+//! Both send exactly the same bytes.
+
+use core::marker::PhantomData;
+
+use embedded_hal::{delay::DelayNs, digital::*, spi::SpiDevice};
+
+use crate::color::BinaryColor;
+use crate::command::Command;
+use crate::config::{FrameSync, InitConfig, InitStep};
+use crate::error::Error;
+#[cfg(feature = "graphics")]
+use crate::grayscale::GrayFrame;
+use crate::interface::DisplayInterface;
+use crate::model::Model;
+use crate::scroll::{DisplayPosition, Offset, PageFlip};
+use crate::traits::{EEIDisplay, EEIInit};
+
+#[cfg(feature = "async")]
+mod asynch;
+
+#[cfg(feature = "async")]
+pub use self::asynch::VfdAsync;
+
+/// Highest brightness level
+pub const MAX_BRIGHTNESS: u32 = 1023;
+
+/// Arguments of [`Command::BrightnessSetting`], a 10 bit level high byte first
+pub(crate) const fn brightness_args(val: u32) -> [u8; 2] {
+    [((val >> 8) as u8) & 0b11, val as u8]
+}
+
+/// [`brightness_args`], refusing anything that doesn't fit in 10 bits
+pub(crate) fn checked_brightness_args<SpiE, PinE>(val: u32) -> Result<[u8; 2], Error<SpiE, PinE>> {
+    if val > MAX_BRIGHTNESS {
+        return Err(Error::BrightnessOutOfRange(val));
+    }
+    Ok(brightness_args(val))
+}
+
+/// Arguments of [`Command::WriteGRAM`] for a full frame
+pub(crate) const fn write_gram_args<M: Model>() -> [u8; 3] {
+    [0x00, 0x00, (M::GRAM_ROWS - 1) as u8]
+}
+
+pub(crate) fn check_frame<M: Model, SpiE, PinE>(buffer: &[u8]) -> Result<(), Error<SpiE, PinE>> {
+    if buffer.len() != M::FRAME_BYTES {
+        return Err(Error::BufferSize {
+            expected: M::FRAME_BYTES,
+            actual: buffer.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Arguments of [`Command::WriteGRAM`] for part of a frame: start column,
+/// start row and number of rows - 1, as for the full frame.
+///
+/// `x` and `width` count GRAM columns, `y` and `height` count rows and have
+/// to be multiples of 8, since every byte covers 8 rows.
+pub(crate) fn window_args<M: Model, SpiE, PinE>(
+    buffer: &[u8],
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> Result<[u8; 3], Error<SpiE, PinE>> {
+    let fits = width > 0
+        && height > 0
+        && x.checked_add(width)
+            .is_some_and(|end| end <= M::GRAM_COLUMNS)
+        && y.checked_add(height).is_some_and(|end| end <= M::GRAM_ROWS);
+    if !fits || !y.is_multiple_of(8) || !height.is_multiple_of(8) {
+        return Err(Error::WindowOutOfBounds);
+    }
+
+    let expected = (width * height / 8) as usize;
+    if buffer.len() != expected {
+        return Err(Error::BufferSize {
+            expected,
+            actual: buffer.len(),
+        });
+    }
+    Ok([x as u8, y as u8, (height - 1) as u8])
+}
+
+/// Time the controller needs to clear its GRAM
+pub(crate) const CLEAR_DELAY_MS: u32 = 10;
+
+/// Driver for any [`Model`], over blocking SPI
+///
+/// Usually named through a model's alias such as
+/// [`VFD256x50`](crate::gp1287bi::VFD256x50).
+pub struct Vfd<M, SPI, RST, DELAY> {
+    interface: DisplayInterface<SPI, RST, DELAY>,
+    config: InitConfig,
+    model: PhantomData<M>,
+}
+
+impl<M, SPI, RST, DELAY> EEIInit<SPI, RST, DELAY> for Vfd<M, SPI, RST, DELAY>
+where
+    M: Model,
+    SPI: SpiDevice,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn init(&mut self) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let args = self.config.args::<M, _, _>()?;
+        for step in args.steps() {
+            match step {
+                InitStep::Reset(ms) => self.interface.reset(ms)?,
+                InitStep::Command(command, args) => self.cmd_with_args(command, args)?,
+                InitStep::DelayMs(ms) => self.interface.delay.delay_ms(ms),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<M, SPI, RST, DELAY> EEIDisplay<SPI, RST, DELAY> for Vfd<M, SPI, RST, DELAY>
+where
+    M: Model,
+    SPI: SpiDevice,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = BinaryColor;
+    fn new(spi: SPI, rst: RST, delay: DELAY) -> Result<Self, Error<SPI::Error, RST::Error>> {
+        Self::with_config(spi, rst, delay, M::DEFAULT_CONFIG)
+    }
+
+    fn set_brightness(&mut self, val: u32) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let args = checked_brightness_args(val)?;
+        self.cmd_with_args(Command::BrightnessSetting, &args)
+    }
+
+    fn sleep(&mut self) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.command(Command::Sleep)
+    }
+
+    fn wake_up(&mut self) -> Result<(), Error<SPI::Error, RST::Error>> {
+        // deep sleep is only left through a hardware reset, which loses
+        // every setting
+        self.init()
+    }
+
+    fn width(&self) -> u32 {
+        self.config.size().0
+    }
+
+    fn height(&self) -> u32 {
+        self.config.size().1
+    }
+
+    fn update_frame(&mut self, buffer: &[u8]) -> Result<(), Error<SPI::Error, RST::Error>> {
+        check_frame::<M, _, _>(buffer)?;
+        self.cmd_with_data(Command::WriteGRAM, &write_gram_args::<M>(), buffer)
+    }
+
+    fn update_partial_frame(
+        &mut self,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let args = window_args::<M, _, _>(buffer, x, y, width, height)?;
+        self.cmd_with_data(Command::WriteGRAM, &args, buffer)
+    }
+
+    fn clear_frame(&mut self) -> Result<(), Error<SPI::Error, RST::Error>> {
+        // Clear the black
+        self.command(Command::ClearGRAM)?;
+        self.interface.delay.delay_ms(CLEAR_DELAY_MS);
+        Ok(())
+    }
+}
+
+impl<M, SPI, RST, DELAY> Vfd<M, SPI, RST, DELAY>
+where
+    M: Model,
+    SPI: SpiDevice,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Like [`EEIDisplay::new`], but initialises the display with `config`
+    /// instead of the defaults.
+    pub fn with_config(
+        spi: SPI,
+        rst: RST,
+        delay: DELAY,
+        config: InitConfig,
+    ) -> Result<Self, Error<SPI::Error, RST::Error>> {
+        let interface = DisplayInterface::new(spi, rst, delay);
+
+        let mut vfd = Vfd {
+            interface,
+            config,
+            model: PhantomData,
+        };
+
+        vfd.init()?;
+
+        Ok(vfd)
+    }
+
+    /// The settings the display was initialised with
+    pub fn config(&self) -> &InitConfig {
+        &self.config
+    }
+
+    /// Point display position `position` at `offset` in the display RAM.
+    /// See [`crate::scroll`].
+    pub fn set_display_offset(
+        &mut self,
+        position: DisplayPosition,
+        offset: Offset,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let args = offset.args::<M, _, _>()?;
+        self.cmd_with_args(position.command(), &args)
+    }
+
+    /// Send the next frame to the page off the panel, `buffer` covering
+    /// [`PageFlip::page_rows`] rows of every column
+    pub fn update_back_page(
+        &mut self,
+        pages: &PageFlip,
+        buffer: &[u8],
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.update_partial_frame(
+            buffer,
+            0,
+            pages.back_row(),
+            M::GRAM_COLUMNS,
+            pages.page_rows(),
+        )
+    }
+
+    /// Show the page last sent with [`Vfd::update_back_page`]
+    pub fn flip_page(&mut self, pages: &mut PageFlip) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let offset = pages.flip();
+        self.set_display_offset(DisplayPosition::First, offset)
+    }
+
+    /// Change the frame sync setting, see
+    /// [`InitConfig::frame_sync`](crate::prelude::InitConfig::frame_sync)
+    pub fn set_frame_sync(
+        &mut self,
+        frame_sync: FrameSync,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.cmd_with_args(Command::FrameSyncSetting, &[frame_sync.0])
+    }
+
+    /// Send the next sub-frame of a grayscale frame, call this once per
+    /// panel refresh. See [`crate::grayscale`].
+    #[cfg(feature = "graphics")]
+    pub fn update_gray_frame<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize>(
+        &mut self,
+        frame: &mut GrayFrame<WIDTH, HEIGHT, BYTES>,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let rows = GrayFrame::<WIDTH, HEIGHT, BYTES>::ROWS;
+        self.update_partial_frame(frame.next_subframe(), 0, 0, WIDTH, rows)
+    }
+
+    fn command(&mut self, command: Command) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.cmd_with_args(command, &[])
+    }
+
+    fn cmd_with_args(
+        &mut self,
+        command: Command,
+        args: &[u8],
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.interface.cmd_with_arg(command, args)
+    }
+
+    fn cmd_with_data(
+        &mut self,
+        command: Command,
+        args: &[u8],
+        data: &[u8],
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.interface.cmd_with_data(command, args, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::mock::{expect_commands, reset_pin, vendor_init, Short};
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::digital::Mock as PinMock;
+    use embedded_hal_mock::eh1::spi::Mock as SpiMock;
+
+    #[test]
+    fn command_bytes_are_bit_reversed() {
+        assert_eq!(
+            crate::interface::command_byte(Command::WriteGRAM),
+            0b0000_1111
+        );
+        assert_eq!(crate::interface::command_byte(Command::Reset), 0b0101_0101);
+    }
+
+    #[test]
+    fn brightness_is_ten_bits() {
+        assert_eq!(brightness_args(0), [0, 0]);
+        assert_eq!(brightness_args(0x30), [0, 0x30]);
+        assert_eq!(brightness_args(1023), [0b11, 0xFF]);
+        // anything above 10 bits is dropped
+        assert_eq!(brightness_args(0x7FF), [0b11, 0xFF]);
+    }
+
+    #[test]
+    fn limits_follow_the_model() {
+        // display area setting for the whole 256x48 panel
+        let area = [0xFF, 0x2F, 0x00, 0x20, 0x00, 0x00, 0x80];
+        let mut spi = SpiMock::new(&expect_commands(&vendor_init(&area)));
+        let mut rst = reset_pin();
+
+        let mut vfd = Vfd::<Short, _, _, _>::new(spi.clone(), rst.clone(), NoopDelay).unwrap();
+        assert_eq!((vfd.width(), vfd.height()), (256, 48));
+        // a gp1287bi frame is too big
+        assert_eq!(
+            vfd.update_frame(&[0; crate::gp1287bi::FRAME_BYTES]),
+            Err(Error::BufferSize {
+                expected: Short::FRAME_BYTES,
+                actual: crate::gp1287bi::FRAME_BYTES
+            })
+        );
+        assert_eq!(
+            vfd.update_partial_frame(&[0; 8], 0, 48, 8, 8),
+            Err(Error::WindowOutOfBounds)
+        );
+
+        spi.done();
+        rst.done();
+
+        // and so is a gp1287bi display area
+        let mut spi = SpiMock::new(&[]);
+        let mut rst = PinMock::new(&[]);
+        let result = Vfd::<Short, _, _, _>::with_config(
+            spi.clone(),
+            rst.clone(),
+            NoopDelay,
+            Short::DEFAULT_CONFIG.display_area(256, 50),
+        );
+        assert!(matches!(result, Err(Error::WindowOutOfBounds)));
+
+        spi.done();
+        rst.done();
+    }
+}
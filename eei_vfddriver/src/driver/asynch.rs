@@ -0,0 +1,218 @@
+//! Async driver for any [`Model`], see [`VfdAsync`]
+
+use core::marker::PhantomData;
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{delay::DelayNs, spi::SpiDevice};
+
+use super::{check_frame, checked_brightness_args, window_args, write_gram_args, CLEAR_DELAY_MS};
+use crate::color::BinaryColor;
+use crate::command::Command;
+use crate::config::{FrameSync, InitConfig, InitStep};
+use crate::error::Error;
+#[cfg(feature = "graphics")]
+use crate::grayscale::GrayFrame;
+use crate::interface::AsyncDisplayInterface;
+use crate::model::Model;
+use crate::scroll::{DisplayPosition, Offset, PageFlip};
+use crate::traits::{EEIDisplayAsync, EEIInitAsync};
+
+/// Driver for any [`Model`], over async SPI
+///
+/// Same commands as [`Vfd`](super::Vfd), but every transfer is awaited so a
+/// DMA backed SPI can send the frame in the background.
+pub struct VfdAsync<M, SPI, RST, DELAY> {
+    interface: AsyncDisplayInterface<SPI, RST, DELAY>,
+    config: InitConfig,
+    model: PhantomData<M>,
+}
+
+impl<M, SPI, RST, DELAY> EEIInitAsync<SPI, RST, DELAY> for VfdAsync<M, SPI, RST, DELAY>
+where
+    M: Model,
+    SPI: SpiDevice,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let args = self.config.args::<M, _, _>()?;
+        for step in args.steps() {
+            match step {
+                InitStep::Reset(ms) => self.interface.reset(ms).await?,
+                InitStep::Command(command, args) => self.cmd_with_args(command, args).await?,
+                InitStep::DelayMs(ms) => self.interface.delay.delay_ms(ms).await,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<M, SPI, RST, DELAY> EEIDisplayAsync<SPI, RST, DELAY> for VfdAsync<M, SPI, RST, DELAY>
+where
+    M: Model,
+    SPI: SpiDevice,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = BinaryColor;
+
+    async fn new(spi: SPI, rst: RST, delay: DELAY) -> Result<Self, Error<SPI::Error, RST::Error>> {
+        Self::with_config(spi, rst, delay, M::DEFAULT_CONFIG).await
+    }
+
+    async fn set_brightness(&mut self, val: u32) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let args = checked_brightness_args(val)?;
+        self.cmd_with_args(Command::BrightnessSetting, &args).await
+    }
+
+    async fn sleep(&mut self) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.command(Command::Sleep).await
+    }
+
+    async fn wake_up(&mut self) -> Result<(), Error<SPI::Error, RST::Error>> {
+        // deep sleep is only left through a hardware reset, which loses
+        // every setting
+        self.init().await
+    }
+
+    fn width(&self) -> u32 {
+        self.config.size().0
+    }
+
+    fn height(&self) -> u32 {
+        self.config.size().1
+    }
+
+    async fn update_frame(&mut self, buffer: &[u8]) -> Result<(), Error<SPI::Error, RST::Error>> {
+        check_frame::<M, _, _>(buffer)?;
+        self.interface
+            .cmd_with_data(Command::WriteGRAM, &write_gram_args::<M>(), buffer)
+            .await
+    }
+
+    async fn update_partial_frame(
+        &mut self,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let args = window_args::<M, _, _>(buffer, x, y, width, height)?;
+        self.interface
+            .cmd_with_data(Command::WriteGRAM, &args, buffer)
+            .await
+    }
+
+    async fn clear_frame(&mut self) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.command(Command::ClearGRAM).await?;
+        self.interface.delay.delay_ms(CLEAR_DELAY_MS).await;
+        Ok(())
+    }
+}
+
+impl<M, SPI, RST, DELAY> VfdAsync<M, SPI, RST, DELAY>
+where
+    M: Model,
+    SPI: SpiDevice,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Like [`EEIDisplayAsync::new`], but initialises the display with
+    /// `config` instead of the defaults.
+    pub async fn with_config(
+        spi: SPI,
+        rst: RST,
+        delay: DELAY,
+        config: InitConfig,
+    ) -> Result<Self, Error<SPI::Error, RST::Error>> {
+        let interface = AsyncDisplayInterface::new(spi, rst, delay);
+
+        let mut vfd = VfdAsync {
+            interface,
+            config,
+            model: PhantomData,
+        };
+
+        vfd.init().await?;
+
+        Ok(vfd)
+    }
+
+    /// The settings the display was initialised with
+    pub fn config(&self) -> &InitConfig {
+        &self.config
+    }
+
+    /// Point display position `position` at `offset` in the display RAM.
+    /// See [`crate::scroll`].
+    pub async fn set_display_offset(
+        &mut self,
+        position: DisplayPosition,
+        offset: Offset,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let args = offset.args::<M, _, _>()?;
+        self.cmd_with_args(position.command(), &args).await
+    }
+
+    /// Send the next frame to the page off the panel, `buffer` covering
+    /// [`PageFlip::page_rows`] rows of every column
+    pub async fn update_back_page(
+        &mut self,
+        pages: &PageFlip,
+        buffer: &[u8],
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.update_partial_frame(
+            buffer,
+            0,
+            pages.back_row(),
+            M::GRAM_COLUMNS,
+            pages.page_rows(),
+        )
+        .await
+    }
+
+    /// Show the page last sent with [`VfdAsync::update_back_page`]
+    pub async fn flip_page(
+        &mut self,
+        pages: &mut PageFlip,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let offset = pages.flip();
+        self.set_display_offset(DisplayPosition::First, offset)
+            .await
+    }
+
+    /// Change the frame sync setting, see
+    /// [`InitConfig::frame_sync`](crate::prelude::InitConfig::frame_sync)
+    pub async fn set_frame_sync(
+        &mut self,
+        frame_sync: FrameSync,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.cmd_with_args(Command::FrameSyncSetting, &[frame_sync.0])
+            .await
+    }
+
+    /// Send the next sub-frame of a grayscale frame, call this once per
+    /// panel refresh. See [`crate::grayscale`].
+    #[cfg(feature = "graphics")]
+    pub async fn update_gray_frame<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize>(
+        &mut self,
+        frame: &mut GrayFrame<WIDTH, HEIGHT, BYTES>,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        let rows = GrayFrame::<WIDTH, HEIGHT, BYTES>::ROWS;
+        self.update_partial_frame(frame.next_subframe(), 0, 0, WIDTH, rows)
+            .await
+    }
+
+    async fn command(&mut self, command: Command) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.cmd_with_args(command, &[]).await
+    }
+
+    async fn cmd_with_args(
+        &mut self,
+        command: Command,
+        args: &[u8],
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.interface.cmd_with_arg(command, args).await
+    }
+}
@@ -0,0 +1,44 @@
+//! Errors returned by the drivers
+
+/// Everything that can go wrong talking to a display
+///
+/// `SpiE` and `PinE` are the error types of the SPI device and the reset pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<SpiE, PinE> {
+    /// The SPI transfer failed
+    Spi(SpiE),
+    /// Driving the reset pin failed
+    Pin(PinE),
+    /// Brightness above the 10 bit maximum of 1023
+    BrightnessOutOfRange(u32),
+    /// A partial update window or display offset doesn't fit in display RAM,
+    /// or the window isn't aligned to whole bytes
+    WindowOutOfBounds,
+    /// The buffer handed over doesn't have the size the update needs
+    BufferSize {
+        /// Bytes the update needs
+        expected: usize,
+        /// Bytes that were passed in
+        actual: usize,
+    },
+}
+
+impl<SpiE, PinE> core::fmt::Display for Error<SpiE, PinE>
+where
+    SpiE: core::fmt::Debug,
+    PinE: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Spi(e) => write!(f, "SPI error: {e:?}"),
+            Error::Pin(e) => write!(f, "reset pin error: {e:?}"),
+            Error::BrightnessOutOfRange(val) => {
+                write!(f, "brightness {val} is above the maximum of 1023")
+            }
+            Error::WindowOutOfBounds => write!(f, "update window or offset outside of display RAM"),
+            Error::BufferSize { expected, actual } => {
+                write!(f, "buffer is {actual} bytes, expected {expected}")
+            }
+        }
+    }
+}
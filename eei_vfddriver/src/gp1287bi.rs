@@ -1,169 +1,640 @@
 //! A simple Driver for the gp1287bi display for SPI
 
-use embedded_hal::{delay::DelayNs, digital::*, spi::SpiDevice};
-
-use crate::interface::DisplayInterface;
-use crate::traits::{EEIDisplay, EEIInit};
-
-/// Width of gp1287bi in pixels
-pub const WIDTH: u32 = 56;
-/// Height of gp1287bi in pixels
-pub const HEIGHT: u32 = 256;
-/// Default Background Color (white)
-pub const DEFAULT_BACKGROUND_COLOR: Color = Color::Dark;
-const NUM_DISPLAY_BITS: u32 = WIDTH * HEIGHT / 8;
-
-use crate::color::Color;
-
-pub(crate) mod command;
-
+use crate::color::BinaryColor;
+use crate::driver::Vfd;
+#[cfg(feature = "async")]
+use crate::driver::VfdAsync;
+use crate::model::Model;
+
+/// Width of the gp1287bi panel in pixels, with the default [`InitConfig`]
+pub const WIDTH: u32 = 256;
+/// Height of the gp1287bi panel in pixels, with the default [`InitConfig`]
+pub const HEIGHT: u32 = 50;
+/// Default Background Color (dark)
+pub const DEFAULT_BACKGROUND_COLOR: BinaryColor = BinaryColor::Off;
+
+/// Columns of display RAM, one for each pixel along the long side
+pub const GRAM_COLUMNS: u32 = 256;
+/// Rows of display RAM, more than the panel shows
+pub const GRAM_ROWS: u32 = 128;
+/// Bytes in a full frame: column after column, 8 rows to a byte
+pub const FRAME_BYTES: usize = Gp1287bi::FRAME_BYTES;
+/// The vendor defaults for the 256x50 panel
+pub const DEFAULT_CONFIG: InitConfig = InitConfig::new(WIDTH, HEIGHT);
+
+pub use crate::config::InitConfig;
+pub use crate::driver::MAX_BRIGHTNESS;
+
+/// Full size buffer for the gp1287bi, covering the whole display RAM, see
+/// [`crate::graphics::Frame`]
 #[cfg(feature = "graphics")]
-mod graphics;
+pub type Display256x50 = crate::graphics::Frame<WIDTH, HEIGHT, FRAME_BYTES>;
 
-use self::command::Command;
+/// Grayscale framebuffer for the rows the panel shows, see
+/// [`crate::grayscale`]
 #[cfg(feature = "graphics")]
-pub use self::graphics::Display256x50;
-
-/// vfd1in02 driver
-pub struct VFD256x50<SPI, RST, DELAY> {
-    interface: DisplayInterface<SPI, RST, DELAY>,
+pub type GrayDisplay256x50 = crate::grayscale::GrayFrame<
+    WIDTH,
+    HEIGHT,
+    { crate::buffer_len(WIDTH as usize, HEIGHT as usize) },
+>;
+
+/// The gp1287bi, 256x50 pixels out of a 256x128 display RAM
+pub struct Gp1287bi;
+
+impl Model for Gp1287bi {
+    const GRAM_COLUMNS: u32 = GRAM_COLUMNS;
+    const GRAM_ROWS: u32 = GRAM_ROWS;
+    const DEFAULT_CONFIG: InitConfig = DEFAULT_CONFIG;
 }
 
-impl<SPI, RST, DELAY> EEIInit<SPI, RST, DELAY> for VFD256x50<SPI, RST, DELAY>
-where
-    SPI: SpiDevice,
-    RST: OutputPin,
-    DELAY: DelayNs,
-{
-    fn init(&mut self) -> Result<(), SPI::Error> {
-        // Based on the spec (not public accessible)
-        self.interface.reset(1);
-
-        // software reset
-        self.command(Command::Reset)?;
-        self.interface.delay.delay_ms(1);
+/// vfd1in02 driver
+pub type VFD256x50<SPI, RST, DELAY> = Vfd<Gp1287bi, SPI, RST, DELAY>;
+
+/// vfd1in02 driver for async SPI
+#[cfg(feature = "async")]
+pub type VFD256x50Async<SPI, RST, DELAY> = VfdAsync<Gp1287bi, SPI, RST, DELAY>;
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::command::Command;
+    use crate::config::{
+        AreaOptions, DisplayMode, FrameSync, InternalSpeed, Oscillator, ScanDirection,
+    };
+    use crate::error::Error;
+    use crate::mock::{expect_command, expect_commands, reset_pin, vendor_init};
+    use crate::prelude::EEIDisplay;
+    use crate::scroll::{DisplayPosition, Marquee, Offset, PageFlip};
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::digital::{
+        Mock as PinMock, State as PinState, Transaction as PinTransaction,
+    };
+    use embedded_hal_mock::eh1::spi::{Mock as SpiMock, Transaction as SpiTransaction};
+    use embedded_hal_mock::eh1::MockError;
+    use std::vec::Vec;
+
+    /// Display area setting for the whole 256x50 panel
+    const AREA: &[u8] = &[0xFF, 0x31, 0x00, 0x20, 0x00, 0x00, 0x80];
+
+    fn expect_init() -> Vec<SpiTransaction<u8>> {
+        expect_commands(&vendor_init(AREA))
+    }
 
-        self.cmd_with_args(Command::OscillationSetting, &[0x08])?;
+    #[test]
+    fn blocking_init_and_frame() {
+        let frame = [0xA5u8; FRAME_BYTES];
+        let mut expected = expect_init();
+        expected.extend(expect_command(
+            Command::BrightnessSetting,
+            &[0b10, 0x00],
+            &[],
+        ));
+        expected.extend(expect_command(
+            Command::WriteGRAM,
+            &[0x00, 0x00, 0x7F],
+            &frame,
+        ));
+        expected.extend(expect_command(Command::ClearGRAM, &[], &[]));
+
+        let mut spi = SpiMock::new(&expected);
+        let mut rst = reset_pin();
+
+        let mut vfd = VFD256x50::new(spi.clone(), rst.clone(), NoopDelay).unwrap();
+        vfd.set_brightness(512).unwrap();
+        vfd.update_frame(&frame).unwrap();
+        vfd.clear_frame().unwrap();
+
+        spi.done();
+        rst.done();
+    }
 
-        // set VFD mode
-        self.cmd_with_args(Command::VFDModeSetting, &[0x02, 0x00])?;
+    fn initialised(
+        extra: &[SpiTransaction<u8>],
+    ) -> (
+        VFD256x50<SpiMock<u8>, PinMock, NoopDelay>,
+        SpiMock<u8>,
+        PinMock,
+    ) {
+        let mut expected = expect_init();
+        expected.extend_from_slice(extra);
+        let spi = SpiMock::new(&expected);
+        let rst = reset_pin();
+        let vfd = VFD256x50::new(spi.clone(), rst.clone(), NoopDelay).unwrap();
+        (vfd, spi, rst)
+    }
 
-        // set display area
-        self.cmd_with_args(
-            Command::DisplayAreaSetting,
-            &[0xFF, 0x31, 0x00, 0x20, 0x00, 0x00, 0x80],
-        )?;
+    #[test]
+    fn brightness_out_of_range() {
+        let (mut vfd, mut spi, mut rst) = initialised(&[]);
 
-        // set internal speed
-        self.cmd_with_args(Command::InternalSpeedSetting, &[0x20, 0x3F, 0x00, 0x01])?;
+        assert_eq!(
+            vfd.set_brightness(1024),
+            Err(Error::BrightnessOutOfRange(1024))
+        );
 
-        // set brightness
-        self.set_brightness(0x30)?;
+        // nothing was sent
+        spi.done();
+        rst.done();
+    }
 
-        // clear gram
-        self.command(Command::ClearGRAM)?;
-        self.interface.delay.delay_ms(15);
+    #[test]
+    fn frame_size_mismatch() {
+        let (mut vfd, mut spi, mut rst) = initialised(&[]);
 
-        // offset: no offset
-        self.cmd_with_args(Command::DisplayPosition1Offset, &[0x00, 0x00])?;
-        self.cmd_with_args(Command::DisplayPosition2Offset, &[0x00, 0x00])?;
+        assert_eq!(
+            vfd.update_frame(&[0; 16]),
+            Err(Error::BufferSize {
+                expected: FRAME_BYTES,
+                actual: 16
+            })
+        );
 
-        // unknown
-        self.cmd_with_args(Command::UnknownInit, &[0x00])?;
+        spi.done();
+        rst.done();
+    }
 
-        // set frame sync
-        self.cmd_with_args(Command::FrameSyncSetting, &[0x00])?;
+    #[test]
+    fn partial_frame() {
+        let window = [0xFFu8; 4 * 2];
+        let (mut vfd, mut spi, mut rst) =
+            initialised(&expect_command(Command::WriteGRAM, &[10, 8, 15], &window));
 
-        // set display mode
-        self.cmd_with_args(Command::DisplayModeSetting, &[0x00])
+        vfd.update_partial_frame(&window, 10, 8, 4, 16).unwrap();
 
+        spi.done();
+        rst.done();
     }
-}
 
-impl<SPI, RST, DELAY> EEIDisplay<SPI, RST, DELAY> for VFD256x50<SPI, RST, DELAY>
-where
-    SPI: SpiDevice,
-    RST: OutputPin,
-    DELAY: DelayNs,
-{
-    type DisplayColor = Color;
-    fn new(spi: SPI, rst: RST, delay: DELAY) -> Result<Self, SPI::Error> {
-        let interface = DisplayInterface::new(spi, rst, delay);
+    #[test]
+    fn partial_frame_rejected() {
+        let (mut vfd, mut spi, mut rst) = initialised(&[]);
+        let buffer = [0u8; 64];
+
+        for (x, y, width, height) in [
+            (250, 0, 8, 8),
+            (0, 120, 8, 16),
+            (0, 4, 8, 8),
+            (0, 0, 8, 12),
+            (0, 0, 0, 8),
+            (u32::MAX, 0, 2, 8),
+        ] {
+            assert_eq!(
+                vfd.update_partial_frame(&buffer, x, y, width, height),
+                Err(Error::WindowOutOfBounds),
+                "window {x},{y} {width}x{height}"
+            );
+        }
+        assert_eq!(
+            vfd.update_partial_frame(&buffer, 0, 0, 8, 8),
+            Err(Error::BufferSize {
+                expected: 8,
+                actual: 64
+            })
+        );
+
+        spi.done();
+        rst.done();
+    }
 
-        let mut vfd = VFD256x50 { interface };
+    #[test]
+    fn default_geometry() {
+        let (vfd, mut spi, mut rst) = initialised(&[]);
 
-        vfd.init()?;
+        assert_eq!((vfd.width(), vfd.height()), (256, 50));
+        assert_eq!((WIDTH, HEIGHT), (256, 50));
+        assert_eq!(*vfd.config(), DEFAULT_CONFIG);
 
-        Ok(vfd)
+        spi.done();
+        rst.done();
     }
 
-    fn set_brightness(&mut self, val: u32) -> Result<(), SPI::Error> {
-        self.cmd_with_args(
-            Command::BrightnessSetting,
-            &[((val >> 8) as u8) & 0b11, val as u8],
-        )
+    #[test]
+    fn custom_init_config() {
+        let config = DEFAULT_CONFIG
+            .display_area(128, 32)
+            .area_options(AreaOptions([1, 2, 3, 4, 5]))
+            .oscillator(Oscillator(0x0C))
+            .scan_direction(ScanDirection([0x02, 0x01]))
+            .internal_speed(InternalSpeed([0x10, 0x20, 0x30, 0x40]))
+            .brightness(0x123)
+            .position_offsets(Offset::new(0, 4), Offset::new(0, 60))
+            .frame_sync(FrameSync(0x01))
+            .display_mode(DisplayMode(0x02));
+        let mut spi = SpiMock::new(&expect_commands(&[
+            (Command::Reset, &[]),
+            (Command::OscillationSetting, &[0x0C]),
+            (Command::VFDModeSetting, &[0x02, 0x01]),
+            (Command::DisplayAreaSetting, &[127, 31, 1, 2, 3, 4, 5]),
+            (Command::InternalSpeedSetting, &[0x10, 0x20, 0x30, 0x40]),
+            (Command::BrightnessSetting, &[0x01, 0x23]),
+            (Command::ClearGRAM, &[]),
+            (Command::DisplayPosition1Offset, &[0x00, 0x04]),
+            (Command::DisplayPosition2Offset, &[0x00, 0x3C]),
+            (Command::UnknownInit, &[0x00]),
+            (Command::FrameSyncSetting, &[0x01]),
+            (Command::DisplayModeSetting, &[0x02]),
+        ]));
+        let mut rst = reset_pin();
+
+        let vfd = VFD256x50::with_config(spi.clone(), rst.clone(), NoopDelay, config).unwrap();
+        assert_eq!((vfd.width(), vfd.height()), (128, 32));
+
+        spi.done();
+        rst.done();
     }
 
-    fn sleep(&mut self) -> Result<(), SPI::Error> {
-        self.command(Command::Sleep)
+    #[test]
+    fn invalid_init_config() {
+        for (config, error) in [
+            (
+                DEFAULT_CONFIG.display_area(257, 50),
+                Error::WindowOutOfBounds,
+            ),
+            (
+                DEFAULT_CONFIG.display_area(256, 0),
+                Error::WindowOutOfBounds,
+            ),
+            (
+                DEFAULT_CONFIG.display_area(256, 129),
+                Error::WindowOutOfBounds,
+            ),
+            (
+                DEFAULT_CONFIG.brightness(1024),
+                Error::BrightnessOutOfRange(1024),
+            ),
+            (
+                DEFAULT_CONFIG.position_offsets(Offset::new(0, 0), Offset::new(0, 128)),
+                Error::WindowOutOfBounds,
+            ),
+        ] {
+            // rejected before anything is sent
+            let mut spi = SpiMock::new(&[]);
+            let mut rst = PinMock::new(&[]);
+
+            let result = VFD256x50::with_config(spi.clone(), rst.clone(), NoopDelay, config);
+            assert!(matches!(result, Err(e) if e == error), "{config:?}");
+
+            spi.done();
+            rst.done();
+        }
     }
 
-    fn wake_up(&mut self) -> Result<(), SPI::Error> {
-        self.command(Command::WakeUp)
+    #[test]
+    fn reset_pin_error() {
+        let error = MockError::Io(std::io::ErrorKind::NotConnected);
+        let mut spi = SpiMock::new(&[]);
+        let mut rst = PinMock::new(&[PinTransaction::set(PinState::Low).with_error(error.clone())]);
+
+        let result = VFD256x50::new(spi.clone(), rst.clone(), NoopDelay);
+        assert!(matches!(result, Err(Error::Pin(e)) if e == error));
+
+        spi.done();
+        rst.done();
     }
 
-    fn width(&self) -> u32 {
-        WIDTH
+    #[test]
+    fn wake_up_runs_init_again() {
+        let config = DEFAULT_CONFIG.brightness(0x200);
+        let mut commands = vendor_init(AREA);
+        commands[5].1 = &[0b10, 0x00];
+        let mut expected = expect_commands(&commands);
+        expected.extend(expect_command(Command::Sleep, &[], &[]));
+        expected.extend(expect_commands(&commands));
+        let mut spi = SpiMock::new(&expected);
+        // deep sleep is left through the reset pin
+        let mut rst = PinMock::new(&[
+            PinTransaction::set(PinState::Low),
+            PinTransaction::set(PinState::High),
+            PinTransaction::set(PinState::Low),
+            PinTransaction::set(PinState::High),
+        ]);
+
+        let mut vfd = VFD256x50::with_config(spi.clone(), rst.clone(), NoopDelay, config).unwrap();
+        vfd.sleep().unwrap();
+        vfd.wake_up().unwrap();
+
+        spi.done();
+        rst.done();
     }
 
-    fn height(&self) -> u32 {
-        HEIGHT
+    #[test]
+    fn display_offsets() {
+        let mut expected = expect_command(Command::DisplayPosition1Offset, &[10, 64], &[]);
+        expected.extend(expect_command(
+            Command::DisplayPosition2Offset,
+            &[255, 127],
+            &[],
+        ));
+        let (mut vfd, mut spi, mut rst) = initialised(&expected);
+
+        vfd.set_display_offset(DisplayPosition::First, Offset::new(10, 64))
+            .unwrap();
+        vfd.set_display_offset(DisplayPosition::Second, Offset::new(255, 127))
+            .unwrap();
+        assert_eq!(
+            vfd.set_display_offset(DisplayPosition::First, Offset::new(0, 128)),
+            Err(Error::WindowOutOfBounds)
+        );
+
+        spi.done();
+        rst.done();
     }
 
-    fn update_frame(&mut self, buffer: &[u8]) -> Result<(), SPI::Error> {
-        self.cmd_with_data(Command::WriteGRAM, &[0x00, 0x00, 0x7F], buffer)
+    #[test]
+    fn marquee_moves_only_the_offset() {
+        let mut expected = Vec::new();
+        for x in [8, 16, 24] {
+            expected.extend(expect_command(
+                Command::DisplayPosition1Offset,
+                &[x, 0],
+                &[],
+            ));
+        }
+        let (mut vfd, mut spi, mut rst) = initialised(&expected);
+
+        let mut marquee = Marquee::new::<Gp1287bi>(0);
+        for _ in 0..3 {
+            vfd.set_display_offset(DisplayPosition::First, marquee.step(8))
+                .unwrap();
+        }
+
+        spi.done();
+        rst.done();
     }
 
-    #[allow(unused)]
-    fn update_partial_frame(
-        &mut self,
-        buffer: &[u8],
-        x: u32,
-        y: u32,
-        width: u32,
-        height: u32,
-    ) -> Result<(), SPI::Error> {
-        unimplemented!()
+    #[test]
+    fn page_flip() {
+        let first = [0x11u8; 256 * 7];
+        let second = [0x22u8; 256 * 7];
+        let mut expected = expect_command(Command::WriteGRAM, &[0, 56, 55], &first);
+        expected.extend(expect_command(
+            Command::DisplayPosition1Offset,
+            &[0, 56],
+            &[],
+        ));
+        expected.extend(expect_command(Command::WriteGRAM, &[0, 0, 55], &second));
+        expected.extend(expect_command(
+            Command::DisplayPosition1Offset,
+            &[0, 0],
+            &[],
+        ));
+        let (mut vfd, mut spi, mut rst) = initialised(&expected);
+
+        let mut pages = PageFlip::new::<Gp1287bi>(HEIGHT).unwrap();
+        vfd.update_back_page(&pages, &first).unwrap();
+        vfd.flip_page(&mut pages).unwrap();
+        vfd.update_back_page(&pages, &second).unwrap();
+        vfd.flip_page(&mut pages).unwrap();
+        // a full frame is too big for a page
+        assert!(matches!(
+            vfd.update_back_page(&pages, &[0; FRAME_BYTES]),
+            Err(Error::BufferSize { .. })
+        ));
+
+        spi.done();
+        rst.done();
     }
 
-    fn clear_frame(&mut self) -> Result<(), SPI::Error> {
-        // Clear the black
-        self.command(Command::ClearGRAM)?;
-        self.interface.delay.delay_ms(10);
-        Ok(())
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn display_buffer_is_a_frame() {
+        use crate::graphics::Display;
+        use embedded_graphics_core::pixelcolor::BinaryColor;
+        use embedded_graphics_core::prelude::*;
+
+        let mut display = Display256x50::default();
+        assert_eq!(display.size(), Size::new(WIDTH, HEIGHT));
+        Pixel(Point::new(1, 49), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+        // below the panel, clipped
+        Pixel(Point::new(1, 50), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+
+        let mut frame = [0u8; FRAME_BYTES];
+        frame[16 + 6] = 0x40;
+        assert_eq!(display.buffer(), frame);
+
+        let (mut vfd, mut spi, mut rst) = initialised(&expect_command(
+            Command::WriteGRAM,
+            &[0x00, 0x00, 0x7F],
+            &frame,
+        ));
+        vfd.update_frame(display.buffer()).unwrap();
+
+        spi.done();
+        rst.done();
     }
-}
 
-impl<SPI, RST, DELAY> VFD256x50<SPI, RST, DELAY>
-where
-    SPI: SpiDevice,
-    RST: OutputPin,
-    DELAY: DelayNs,
-{
-    fn command(&mut self, command: Command) -> Result<(), SPI::Error> {
-        self.cmd_with_args(command, &[])
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn display_matches_transposed_framebuffer() {
+        use crate::graphics::Display;
+        use embedded_graphics::framebuffer::{buffer_size, Framebuffer};
+        use embedded_graphics::pixelcolor::raw::{LittleEndian, RawU1};
+        use embedded_graphics::pixelcolor::BinaryColor;
+        use embedded_graphics::prelude::*;
+
+        // what the app drew into and sent before this driver had a buffer:
+        // one 128 pixel row per GRAM column, drawn to with x and y swapped
+        let mut framebuffer = Framebuffer::<
+            BinaryColor,
+            RawU1,
+            LittleEndian,
+            128,
+            256,
+            { buffer_size::<BinaryColor>(128, 256) },
+        >::new();
+        let mut display = Display256x50::default();
+        let lit = (0..WIDTH as i32)
+            .flat_map(|x| (0..HEIGHT as i32).map(move |y| Point::new(x, y)))
+            .filter(|point| (point.x * 7 + point.y * 3) % 5 == 0);
+        for point in lit {
+            Pixel(Point::new(point.y, point.x), BinaryColor::On)
+                .draw(&mut framebuffer)
+                .unwrap();
+            Pixel(point, BinaryColor::On).draw(&mut display).unwrap();
+        }
+
+        assert_eq!(display.buffer(), framebuffer.data());
     }
 
-    fn cmd_with_args(&mut self, command: Command, args: &[u8]) -> Result<(), SPI::Error> {
-        self.interface.cmd_with_arg(command, args)
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn gray_frame_cycles_subframes() {
+        use embedded_graphics_core::pixelcolor::Gray2;
+        use embedded_graphics_core::prelude::*;
+
+        let mut frame = GrayDisplay256x50::new();
+        Pixel(Point::new(0, 0), Gray2::new(1))
+            .draw(&mut frame)
+            .unwrap();
+        Pixel(Point::new(0, 1), Gray2::new(2))
+            .draw(&mut frame)
+            .unwrap();
+
+        // only the 56 rows that cover the panel are sent
+        assert_eq!(frame.size(), Size::new(WIDTH, HEIGHT));
+        let mut expected = expect_command(Command::FrameSyncSetting, &[0x01], &[]);
+        for first_byte in [0xC0, 0x40, 0x00, 0xC0] {
+            let mut subframe = [0u8; 256 * 7];
+            subframe[0] = first_byte;
+            expected.extend(expect_command(
+                Command::WriteGRAM,
+                &[0x00, 0x00, 55],
+                &subframe,
+            ));
+        }
+        let (mut vfd, mut spi, mut rst) = initialised(&expected);
+
+        vfd.set_frame_sync(FrameSync(0x01)).unwrap();
+        for _ in 0..4 {
+            vfd.update_gray_frame(&mut frame).unwrap();
+        }
+
+        spi.done();
+        rst.done();
     }
 
-    fn cmd_with_data(
-        &mut self,
-        command: Command,
-        args: &[u8],
-        data: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.interface.cmd_with_data(command, args, data)
+    #[cfg(feature = "async")]
+    mod asynch {
+        use super::*;
+        use crate::prelude::EEIDisplayAsync;
+        use embassy_futures::block_on;
+
+        #[test]
+        fn init_matches_blocking() {
+            let mut spi = SpiMock::new(&expect_init());
+            let mut rst = reset_pin();
+
+            block_on(VFD256x50Async::new(spi.clone(), rst.clone(), NoopDelay)).unwrap();
+
+            spi.done();
+            rst.done();
+        }
+
+        #[test]
+        fn frame_brightness_and_sleep() {
+            let frame: Vec<u8> = (0..FRAME_BYTES).map(|i| i as u8).collect();
+            let mut expected = expect_init();
+            expected.extend(expect_command(
+                Command::WriteGRAM,
+                &[0x00, 0x00, 0x7F],
+                &frame,
+            ));
+            expected.extend(expect_command(
+                Command::BrightnessSetting,
+                &[0b11, 0xFF],
+                &[],
+            ));
+            expected.extend(expect_command(Command::Sleep, &[], &[]));
+            expected.extend(expect_init());
+            expected.extend(expect_command(Command::ClearGRAM, &[], &[]));
+
+            let mut spi = SpiMock::new(&expected);
+            let mut rst = PinMock::new(&[
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]);
+
+            block_on(async {
+                let mut vfd = VFD256x50Async::new(spi.clone(), rst.clone(), NoopDelay)
+                    .await
+                    .unwrap();
+                assert_eq!((vfd.width(), vfd.height()), (WIDTH, HEIGHT));
+                vfd.update_frame(&frame).await.unwrap();
+                vfd.set_brightness(1023).await.unwrap();
+                vfd.sleep().await.unwrap();
+                vfd.wake_up().await.unwrap();
+                vfd.clear_frame().await.unwrap();
+            });
+
+            spi.done();
+            rst.done();
+        }
+
+        #[test]
+        fn errors_match_blocking() {
+            let mut spi = SpiMock::new(&expect_init());
+            let mut rst = reset_pin();
+
+            block_on(async {
+                let mut vfd = VFD256x50Async::new(spi.clone(), rst.clone(), NoopDelay)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    vfd.set_brightness(4000).await,
+                    Err(Error::BrightnessOutOfRange(4000))
+                );
+                assert_eq!(
+                    vfd.update_frame(&[]).await,
+                    Err(Error::BufferSize {
+                        expected: FRAME_BYTES,
+                        actual: 0
+                    })
+                );
+                assert_eq!(
+                    vfd.update_partial_frame(&[], 0, 130, 1, 8).await,
+                    Err(Error::WindowOutOfBounds)
+                );
+            });
+
+            spi.done();
+            rst.done();
+        }
+
+        #[test]
+        fn page_flip_matches_blocking() {
+            let page = [0x33u8; 256 * 7];
+            let mut expected = expect_init();
+            expected.extend(expect_command(Command::WriteGRAM, &[0, 56, 55], &page));
+            expected.extend(expect_command(
+                Command::DisplayPosition1Offset,
+                &[0, 56],
+                &[],
+            ));
+            let mut spi = SpiMock::new(&expected);
+            let mut rst = reset_pin();
+
+            block_on(async {
+                let mut vfd = VFD256x50Async::new(spi.clone(), rst.clone(), NoopDelay)
+                    .await
+                    .unwrap();
+                let mut pages = PageFlip::new::<Gp1287bi>(HEIGHT).unwrap();
+                vfd.update_back_page(&pages, &page).await.unwrap();
+                vfd.flip_page(&mut pages).await.unwrap();
+            });
+
+            spi.done();
+            rst.done();
+        }
+
+        #[test]
+        fn custom_config_matches_blocking() {
+            let config = DEFAULT_CONFIG.display_area(200, 40).brightness(1023);
+            let mut commands = vendor_init(AREA);
+            commands[3].1 = &[199, 39, 0x00, 0x20, 0x00, 0x00, 0x80];
+            commands[5].1 = &[0b11, 0xFF];
+            let mut spi = SpiMock::new(&expect_commands(&commands));
+            let mut rst = reset_pin();
+
+            let vfd = block_on(VFD256x50Async::with_config(
+                spi.clone(),
+                rst.clone(),
+                NoopDelay,
+                config,
+            ))
+            .unwrap();
+            assert_eq!((vfd.width(), vfd.height()), (200, 40));
+
+            spi.done();
+            rst.done();
+        }
     }
 }
@@ -1,8 +1,19 @@
-//! Graphics Support for EPDs
+//! Drawing into framebuffers for the VFDs with embedded-graphics
+//!
+//! The buffers are laid out like the display RAM, so they can be sent as
+//! they are: column after column, each column `buffer().len() / width` bytes
+//! from the top down, 8 rows to a byte with the top row in bit 7 (MSB). See
+//! [`buffer_len`] for a picture of it.
+//!
+//! So a run along a row of the panel touches the same bit of one byte in
+//! every column, and a run down a column is a few whole bytes in a row.
+//! [`Display::fill_solid_helper`] fills a column at a time, masking only the
+//! first and last byte. [`Display::fill_contiguous_helper`] writes the runs
+//! straight into the bytes, in whichever direction the rotation turns them.
 
 use crate::buffer_len;
-use crate::color::Color;
-use embedded_graphics_core::prelude::*;
+use crate::color::{byte_value, BinaryColor};
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
 
 /// Displayrotation
 #[derive(Clone, Copy, Default)]
@@ -41,12 +52,10 @@ pub enum DisplayColorRendering {
 /// - Drawing (With the help of DrawTarget/Embedded Graphics)
 /// - Rotations
 /// - Clearing
-pub trait Display: DrawTarget<Color = Color> {
+pub trait Display: DrawTarget<Color = BinaryColor> {
     /// Clears the buffer of the display with the chosen background color
-    fn clear_buffer(&mut self, background_color: Color) {
-        for elem in self.get_mut_buffer().iter_mut() {
-            *elem = background_color.get_byte_value();
-        }
+    fn clear_buffer(&mut self, background_color: BinaryColor) {
+        self.get_mut_buffer().fill(byte_value(background_color));
     }
 
     /// Returns the buffer
@@ -63,12 +72,14 @@ pub trait Display: DrawTarget<Color = Color> {
 
     /// Helperfunction for the Embedded Graphics draw trait
     ///
-    /// Becomes uneccesary when const_generics become stablised
+    /// Every column takes up `buffer().len() / width` bytes, which may be
+    /// more than `height` needs when the display RAM has rows the panel
+    /// doesn't show.
     fn draw_helper(
         &mut self,
         width: u32,
         height: u32,
-        pixel: Pixel<Color>,
+        pixel: Pixel<BinaryColor>,
     ) -> Result<(), Self::Error> {
         let rotation = self.rotation();
         let buffer = self.get_mut_buffer();
@@ -79,29 +90,198 @@ pub trait Display: DrawTarget<Color = Color> {
         }
 
         // Give us index inside the buffer and the bit-position in that u8 which needs to be changed
-        let (index, bit) = find_position(point.x as u32, point.y as u32, width, height, rotation);
+        let column_bytes = buffer.len() as u32 / width;
+        let (index, bit) = find_position(
+            point.x as u32,
+            point.y as u32,
+            width,
+            height,
+            column_bytes,
+            rotation,
+        );
         let index = index as usize;
 
         // "Draw" the Pixel on that bit
         match color {
-            // Black
-            Color::Dark => {
+            BinaryColor::Off => {
                 buffer[index] &= !bit;
             }
-            // White
-            Color::Green => {
+            BinaryColor::On => {
                 buffer[index] |= bit;
             }
         }
         Ok(())
     }
+
+    /// Helperfunction for [`DrawTarget::fill_solid`]
+    ///
+    /// Whatever the rotation, `area` is a rectangle of the display RAM too,
+    /// filled a column at a time and up to 8 rows a byte at once.
+    fn fill_solid_helper(
+        &mut self,
+        width: u32,
+        height: u32,
+        area: &Rectangle,
+        color: BinaryColor,
+    ) -> Result<(), Self::Error> {
+        let rotation = self.rotation();
+        let area = area.intersection(&display_area(width, height, rotation));
+        let Some(bottom_right) = area.bottom_right() else {
+            return Ok(());
+        };
+
+        let (x0, y0) = find_rotation(
+            area.top_left.x as u32,
+            area.top_left.y as u32,
+            width,
+            height,
+            rotation,
+        );
+        let (x1, y1) = find_rotation(
+            bottom_right.x as u32,
+            bottom_right.y as u32,
+            width,
+            height,
+            rotation,
+        );
+        let rows = y0.min(y1)..=y0.max(y1);
+
+        let buffer = self.get_mut_buffer();
+        let column_bytes = buffer.len() / width as usize;
+        for column in buffer
+            .chunks_exact_mut(column_bytes)
+            .take(x0.max(x1) as usize + 1)
+            .skip(x0.min(x1) as usize)
+        {
+            fill_rows(column, rows.clone(), color);
+        }
+        Ok(())
+    }
+
+    /// Helperfunction for [`DrawTarget::fill_contiguous`]
+    ///
+    /// Every row of `area` is a run along a row of the display RAM, or down a
+    /// column of it when rotated by 90 or 270 degrees. The runs are written
+    /// straight into the buffer without working out every pixel's position.
+    fn fill_contiguous_helper<I>(
+        &mut self,
+        width: u32,
+        height: u32,
+        area: &Rectangle,
+        colors: I,
+    ) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = BinaryColor>,
+    {
+        let rotation = self.rotation();
+        let visible = area.intersection(&display_area(width, height, rotation));
+        if visible.is_zero_sized() {
+            return Ok(());
+        }
+
+        let buffer = self.get_mut_buffer();
+        let column_bytes = buffer.len() / width as usize;
+        let mut colors = colors.into_iter();
+
+        // colors of the parts of `area` outside the display are dropped
+        let skip = visible.top_left - area.top_left;
+        let left = skip.x as usize;
+        let right = (area.size.width - visible.size.width) as usize - left;
+        let run = visible.size.width as usize;
+        skip_colors(&mut colors, skip.y as usize * area.size.width as usize);
+
+        let x = visible.top_left.x as u32;
+        for y in visible.rows() {
+            skip_colors(&mut colors, left);
+            let (nx, ny) = find_rotation(x, y as u32, width, height, rotation);
+            let (nx, ny) = (nx as usize, ny as usize);
+            let run = colors.by_ref().take(run);
+            match rotation {
+                DisplayRotation::Rotate0 => {
+                    let bytes = buffer[nx * column_bytes + ny / 8..].iter_mut();
+                    set_bits(bytes.step_by(column_bytes), 0x80 >> (ny % 8), run);
+                }
+                DisplayRotation::Rotate180 => {
+                    let bytes = buffer[ny / 8..=nx * column_bytes + ny / 8].iter_mut();
+                    set_bits(bytes.rev().step_by(column_bytes), 0x80 >> (ny % 8), run);
+                }
+                DisplayRotation::Rotate90 => {
+                    let column = &mut buffer[nx * column_bytes..][..column_bytes];
+                    fill_column_run(column, ny, run, |row| row + 1);
+                }
+                DisplayRotation::Rotate270 => {
+                    let column = &mut buffer[nx * column_bytes..][..column_bytes];
+                    fill_column_run(column, ny, run, |row| row.wrapping_sub(1));
+                }
+            }
+            skip_colors(&mut colors, right);
+        }
+        Ok(())
+    }
+}
+
+/// Set or clear `bit` in every byte, one color each
+fn set_bits<'a>(
+    bytes: impl Iterator<Item = &'a mut u8>,
+    bit: u8,
+    colors: impl Iterator<Item = BinaryColor>,
+) {
+    for (byte, color) in bytes.zip(colors) {
+        match color {
+            BinaryColor::Off => *byte &= !bit,
+            BinaryColor::On => *byte |= bit,
+        }
+    }
+}
+
+/// Write colors down (or up) a column from `row` on, a byte at a time
+fn fill_column_run(
+    column: &mut [u8],
+    mut row: usize,
+    colors: impl Iterator<Item = BinaryColor>,
+    next: impl Fn(usize) -> usize,
+) {
+    let mut index = row / 8;
+    let (mut mask, mut bits) = (0u8, 0u8);
+    for color in colors {
+        if row / 8 != index {
+            column[index] = column[index] & !mask | bits;
+            (index, mask, bits) = (row / 8, 0, 0);
+        }
+        let bit = 0x80 >> (row % 8);
+        mask |= bit;
+        if color.is_on() {
+            bits |= bit;
+        }
+        row = next(row);
+    }
+    column[index] = column[index] & !mask | bits;
+}
+
+/// Fill `rows` of a column, masking the bytes they only partly cover
+fn fill_rows(column: &mut [u8], rows: core::ops::RangeInclusive<u32>, color: BinaryColor) {
+    let (first, last) = (*rows.start(), *rows.end());
+    let value = byte_value(color);
+    for index in first / 8..=last / 8 {
+        let low = if index == first / 8 { first % 8 } else { 0 };
+        let high = if index == last / 8 { last % 8 } else { 7 };
+        let mask = (0xFFu8 >> low) & (0xFFu8 << (7 - high));
+        let byte = &mut column[index as usize];
+        *byte = *byte & !mask | value & mask;
+    }
+}
+
+fn skip_colors(colors: &mut impl Iterator, count: usize) {
+    if count > 0 {
+        colors.nth(count - 1);
+    }
 }
 
 /// A variable Display without a predefined buffer
 ///
 /// The buffer can be created as following:
-/// buffer: [DEFAULT_BACKGROUND_COLOR.get_byte_value(); WIDTH / 8 * HEIGHT]
-/// If WIDTH is not a multiple of 8, don't forget to round it up (ie. (WIDTH + 7) / 8)
+/// buffer: [0x00; buffer_len(WIDTH, HEIGHT)]
+/// See [`buffer_len`] for how the bytes are laid out.
 pub struct VarDisplay<'a> {
     width: u32,
     height: u32,
@@ -112,10 +292,9 @@ pub struct VarDisplay<'a> {
 impl<'a> VarDisplay<'a> {
     /// Create a new variable sized display.
     ///
-    /// Buffersize must be at least (width + 7) / 8 * height bytes.
+    /// Buffersize must be [`buffer_len`] bytes.
     pub fn new(width: u32, height: u32, buffer: &'a mut [u8]) -> VarDisplay<'a> {
-        let len = buffer.len() as u32;
-        assert!(buffer_len(width as usize, height as usize) >= len as usize);
+        assert_eq!(buffer_len(width as usize, height as usize), buffer.len());
         VarDisplay {
             width,
             height,
@@ -126,7 +305,7 @@ impl<'a> VarDisplay<'a> {
 }
 
 impl<'a> DrawTarget for VarDisplay<'a> {
-    type Color = Color;
+    type Color = BinaryColor;
     type Error = core::convert::Infallible;
 
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
@@ -138,11 +317,29 @@ impl<'a> DrawTarget for VarDisplay<'a> {
         }
         Ok(())
     }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.fill_contiguous_helper(self.width, self.height, area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_solid_helper(self.width, self.height, area, color)
+    }
 }
 
 impl<'a> OriginDimensions for VarDisplay<'a> {
     fn size(&self) -> Size {
-        Size::new(self.width, self.height)
+        match self.rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                Size::new(self.width, self.height)
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                Size::new(self.height, self.width)
+            }
+        }
     }
 }
 
@@ -164,6 +361,118 @@ impl<'a> Display for VarDisplay<'a> {
     }
 }
 
+/// Framebuffer laid out like the display RAM of a model
+///
+/// `WIDTH` and `HEIGHT` are the pixels the panel shows, `BYTES` the size of
+/// a full frame of display RAM, which may have rows the panel doesn't show.
+/// Use the alias of a model, e.g.
+/// [`Display256x50`](crate::gp1287bi::Display256x50), rather than spelling
+/// them out.
+///
+/// Laid out like the display RAM, so [`Display::buffer`] can go straight to
+/// [`EEIDisplay::update_frame`](crate::prelude::EEIDisplay::update_frame).
+/// Drawing is clipped to the rows the panel shows, rectangles and images are
+/// filled a run at a time in any rotation.
+pub struct Frame<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> {
+    buffer: [u8; BYTES],
+    rotation: DisplayRotation,
+}
+
+impl<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> Frame<WIDTH, HEIGHT, BYTES> {
+    /// An all dark frame
+    pub const fn new() -> Self {
+        const {
+            assert!(BYTES.is_multiple_of(WIDTH as usize), "whole columns");
+            assert!(
+                BYTES / WIDTH as usize >= buffer_len(1, HEIGHT as usize),
+                "every column has room for the rows shown"
+            );
+        }
+        Frame {
+            buffer: [byte_value(BinaryColor::Off); BYTES],
+            rotation: DisplayRotation::Rotate0,
+        }
+    }
+}
+
+impl<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> Default
+    for Frame<WIDTH, HEIGHT, BYTES>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> DrawTarget
+    for Frame<WIDTH, HEIGHT, BYTES>
+{
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            self.draw_helper(WIDTH, HEIGHT, pixel)?;
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.fill_contiguous_helper(WIDTH, HEIGHT, area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_solid_helper(WIDTH, HEIGHT, area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.clear_buffer(color);
+        Ok(())
+    }
+}
+
+impl<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> OriginDimensions
+    for Frame<WIDTH, HEIGHT, BYTES>
+{
+    fn size(&self) -> Size {
+        display_area(WIDTH, HEIGHT, self.rotation).size
+    }
+}
+
+impl<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> Display
+    for Frame<WIDTH, HEIGHT, BYTES>
+{
+    fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    fn get_mut_buffer(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+
+    fn set_rotation(&mut self, rotation: DisplayRotation) {
+        self.rotation = rotation;
+    }
+
+    fn rotation(&self) -> DisplayRotation {
+        self.rotation
+    }
+}
+
+// The part of the coordinates that is on the display
+fn display_area(width: u32, height: u32, rotation: DisplayRotation) -> Rectangle {
+    let size = match rotation {
+        DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => Size::new(width, height),
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => Size::new(height, width),
+    };
+    Rectangle::new(Point::zero(), size)
+}
+
 // Checks if a pos is outside the defined display
 fn outside_display(p: Point, width: u32, height: u32, rotation: DisplayRotation) -> bool {
     if p.x < 0 || p.y < 0 {
@@ -211,10 +520,188 @@ fn find_rotation(x: u32, y: u32, width: u32, height: u32, rotation: DisplayRotat
 
 #[rustfmt::skip]
 //returns index position in the u8-slice and the bit-position inside that u8
-fn find_position(x: u32, y: u32, width: u32, height: u32, rotation: DisplayRotation) -> (u32, u8) {
+fn find_position(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    column_bytes: u32,
+    rotation: DisplayRotation,
+) -> (u32, u8) {
     let (nx, ny) = find_rotation(x, y, width, height, rotation);
     (
-        nx / 8 + ((width + 7) / 8) * ny,
-        0x80 >> (nx % 8),
+        nx * column_bytes + ny / 8,
+        0x80 >> (ny % 8),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixels_land_in_gram_order() {
+        let mut buffer = [0u8; buffer_len(3, 12)];
+        let mut display = VarDisplay::new(3, 12, &mut buffer);
+        Pixel(Point::new(1, 9), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+        Pixel(Point::new(2, 0), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+
+        // two bytes per column, top row in the highest bit
+        assert_eq!(display.buffer(), [0, 0, 0, 0x40, 0x80, 0]);
+    }
+
+    #[test]
+    fn off_clears_the_bit() {
+        let mut buffer = [0xFFu8; buffer_len(8, 8)];
+        let mut display = VarDisplay::new(8, 8, &mut buffer);
+        Pixel(Point::new(0, 7), BinaryColor::Off)
+            .draw(&mut display)
+            .unwrap();
+
+        assert_eq!(display.buffer()[0], 0xFE);
+        assert_eq!(display.buffer()[1..], [0xFF; 7]);
+    }
+
+    #[test]
+    fn rotation_and_clipping() {
+        let mut buffer = [0u8; buffer_len(4, 8)];
+        let mut display = VarDisplay::new(4, 8, &mut buffer);
+        display.set_rotation(DisplayRotation::Rotate90);
+        assert_eq!(display.size(), Size::new(8, 4));
+
+        // the top corners of the rotated display are the last column
+        for x in [0, 7] {
+            Pixel(Point::new(x, 0), BinaryColor::On)
+                .draw(&mut display)
+                .unwrap();
+        }
+        for outside in [Point::new(-1, 0), Point::new(8, 0), Point::new(0, 4)] {
+            Pixel(outside, BinaryColor::On).draw(&mut display).unwrap();
+        }
+
+        assert_eq!(display.buffer(), [0, 0, 0, 0x81]);
+    }
+
+    const ROTATIONS: [DisplayRotation; 4] = [
+        DisplayRotation::Rotate0,
+        DisplayRotation::Rotate90,
+        DisplayRotation::Rotate180,
+        DisplayRotation::Rotate270,
+    ];
+
+    /// Areas inside, across the edges of and outside a 13x20 display
+    fn areas() -> impl Iterator<Item = Rectangle> {
+        let corners = [-3, 0, 1, 6, 9, 12, 19];
+        let sizes = [1, 2, 7, 9, 17, 30];
+        corners.into_iter().flat_map(move |x| {
+            corners.into_iter().flat_map(move |y| {
+                sizes.into_iter().flat_map(move |w| {
+                    sizes
+                        .into_iter()
+                        .map(move |h| Rectangle::new(Point::new(x, y), Size::new(w, h)))
+                })
+            })
+        })
+    }
+
+    fn colors(area: &Rectangle) -> impl Iterator<Item = BinaryColor> {
+        (0..area.size.width * area.size.height).map(|i| (i % 3 == 0 || i % 7 == 2).into())
+    }
+
+    /// Check the fast fill against drawing `area` pixel by pixel
+    fn assert_fill_matches(
+        fill: impl Fn(&mut VarDisplay, &Rectangle),
+        draw: impl Fn(&mut VarDisplay, &Rectangle),
+    ) {
+        for rotation in ROTATIONS {
+            for area in areas() {
+                // a background with both colors shows bits that shouldn't change
+                let mut filled = [0xA5u8; buffer_len(13, 20)];
+                let mut drawn = filled;
+                let mut display = VarDisplay::new(13, 20, &mut filled);
+                display.set_rotation(rotation);
+                fill(&mut display, &area);
+
+                let mut display = VarDisplay::new(13, 20, &mut drawn);
+                display.set_rotation(rotation);
+                draw(&mut display, &area);
+
+                assert_eq!(filled, drawn, "{area:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_matches_pixels() {
+        assert_fill_matches(
+            |display, area| display.fill_contiguous(area, colors(area)).unwrap(),
+            |display, area| {
+                let pixels = area.points().zip(colors(area));
+                let pixels = pixels.map(|(point, color)| Pixel(point, color));
+                display.draw_iter(pixels).unwrap()
+            },
+        );
+    }
+
+    #[test]
+    fn fill_solid_matches_pixels() {
+        for color in [BinaryColor::On, BinaryColor::Off] {
+            assert_fill_matches(
+                |display, area| display.fill_solid(area, color).unwrap(),
+                |display, area| {
+                    let pixels = area.points().map(|point| Pixel(point, color));
+                    display.draw_iter(pixels).unwrap()
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_with_too_few_colors() {
+        let mut buffer = [0u8; buffer_len(4, 8)];
+        let mut display = VarDisplay::new(4, 8, &mut buffer);
+        display.set_rotation(DisplayRotation::Rotate90);
+        let area = Rectangle::new(Point::new(0, 1), Size::new(8, 2));
+        display
+            .fill_contiguous(&area, [BinaryColor::On; 3])
+            .unwrap();
+
+        assert_eq!(display.buffer(), [0, 0, 0b1110_0000, 0]);
+    }
+
+    #[test]
+    fn frame_clips_to_the_panel() {
+        // 10 rows shown out of 16 in the display RAM
+        let mut frame = Frame::<3, 10, 6>::new();
+        assert_eq!(frame.size(), Size::new(3, 10));
+        frame.set_rotation(DisplayRotation::Rotate90);
+        assert_eq!(frame.size(), Size::new(10, 3));
+
+        frame.set_rotation(DisplayRotation::Rotate0);
+        for point in [Point::new(1, 9), Point::new(1, 10), Point::new(3, 0)] {
+            Pixel(point, BinaryColor::On).draw(&mut frame).unwrap();
+        }
+        assert_eq!(frame.buffer(), [0, 0, 0, 0x40, 0, 0]);
+    }
+
+    #[test]
+    fn fills_leave_hidden_rows_alone() {
+        let mut display = crate::gp1287bi::Display256x50::default();
+        display.set_rotation(DisplayRotation::Rotate270);
+        let area = display.bounding_box();
+        display.fill_solid(&area, BinaryColor::On).unwrap();
+        display
+            .fill_contiguous(&area, core::iter::repeat(BinaryColor::On))
+            .unwrap();
+
+        for column in display.buffer().chunks(16) {
+            // 50 rows: six whole bytes and two bits
+            assert_eq!(column[..7], [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xC0]);
+            assert_eq!(column[7..], [0; 9]);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Grayscale on a 1 bit panel, by temporal dithering
+//!
+//! The controllers only know lit and dark pixels. [`GrayFrame`] keeps two
+//! bit planes for a [`Gray2`] level per pixel and turns them into
+//! [`SUBFRAMES`] plain frames: a pixel at level `n` is lit in `n` of them.
+//! Sent one after the other, once per panel refresh, the eye averages them
+//! into four brightness levels.
+//!
+//! For the levels to hold steady every sub-frame has to be shown for the same
+//! number of refreshes. Set the frame sync (see
+//! [`InitConfig::frame_sync`](crate::prelude::InitConfig::frame_sync) and
+//! [`Vfd::set_frame_sync`](crate::driver::Vfd::set_frame_sync)) so that a
+//! new frame only takes over at the start of a refresh, then send
+//! [`GrayFrame::next_subframe`] at the panel's refresh rate, e.g. with
+//! [`Vfd::update_gray_frame`](crate::driver::Vfd::update_gray_frame).
+
+use crate::buffer_len;
+use embedded_graphics_core::pixelcolor::{Gray2, GrayColor};
+use embedded_graphics_core::prelude::*;
+
+/// Plain frames one [`GrayFrame`] is shown as
+pub const SUBFRAMES: usize = 3;
+
+/// Framebuffer with four gray levels, in the layout of the display RAM
+///
+/// `WIDTH` and `HEIGHT` are the pixels the panel shows, `BYTES` has to be
+/// [`buffer_len`]`(WIDTH, HEIGHT)`. Use the alias of a model, e.g.
+/// [`GrayDisplay256x50`](crate::gp1287bi::GrayDisplay256x50), rather than
+/// spelling them out.
+///
+/// Like the display RAM, the buffer goes column after column, 8 rows to a
+/// byte with the top row in the highest bit. It only covers the top of the
+/// display RAM, down to `HEIGHT` rounded up to whole bytes, and is sent as a
+/// window of it.
+pub struct GrayFrame<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> {
+    /// Low and high bit of every pixel's level
+    planes: [[u8; BYTES]; 2],
+    /// The sub-frame last handed out
+    subframe: [u8; BYTES],
+    phase: usize,
+}
+
+impl<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> GrayFrame<WIDTH, HEIGHT, BYTES> {
+    /// Rows of display RAM a sub-frame covers
+    pub const ROWS: u32 = HEIGHT.div_ceil(8) * 8;
+
+    /// An all dark frame
+    pub const fn new() -> Self {
+        const {
+            assert!(
+                BYTES == buffer_len(WIDTH as usize, HEIGHT as usize),
+                "wrong buffer size"
+            );
+        }
+        GrayFrame {
+            planes: [[0; BYTES]; 2],
+            subframe: [0; BYTES],
+            phase: 0,
+        }
+    }
+
+    /// Level of the pixel at `point`, `None` outside the frame
+    pub fn pixel(&self, point: Point) -> Option<Gray2> {
+        let (index, bit) = Self::position(point)?;
+        let low = self.planes[0][index] & bit != 0;
+        let high = self.planes[1][index] & bit != 0;
+        Some(Gray2::new(u8::from(high) << 1 | u8::from(low)))
+    }
+
+    /// Which sub-frame [`GrayFrame::next_subframe`] hands out next
+    pub fn phase(&self) -> usize {
+        self.phase
+    }
+
+    /// Work out sub-frame `phase` (0 to [`SUBFRAMES`] - 1), ready to be sent
+    /// to the top [`GrayFrame::ROWS`] rows of the display RAM
+    pub fn subframe(&mut self, phase: usize) -> &[u8] {
+        let [low, high] = &self.planes;
+        for ((out, &low), &high) in self.subframe.iter_mut().zip(low).zip(high) {
+            // lit in the sub-frames below the pixel's level
+            *out = match phase % SUBFRAMES {
+                0 => low | high,
+                1 => high,
+                _ => low & high,
+            };
+        }
+        &self.subframe
+    }
+
+    /// The sub-frame to show next, moving on to the one after
+    pub fn next_subframe(&mut self) -> &[u8] {
+        let phase = self.phase;
+        self.phase = (phase + 1) % SUBFRAMES;
+        self.subframe(phase)
+    }
+
+    fn position(point: Point) -> Option<(usize, u8)> {
+        let (x, y) = (u32::try_from(point.x).ok()?, u32::try_from(point.y).ok()?);
+        if x >= WIDTH || y >= HEIGHT {
+            return None;
+        }
+        let index = x * (Self::ROWS / 8) + y / 8;
+        Some((index as usize, 0x80 >> (y % 8)))
+    }
+}
+
+impl<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> Default
+    for GrayFrame<WIDTH, HEIGHT, BYTES>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> DrawTarget
+    for GrayFrame<WIDTH, HEIGHT, BYTES>
+{
+    type Color = Gray2;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let Some((index, bit)) = Self::position(point) else {
+                continue;
+            };
+            let luma = color.luma();
+            for (plane, mask) in self.planes.iter_mut().zip([0b01, 0b10]) {
+                if luma & mask != 0 {
+                    plane[index] |= bit;
+                } else {
+                    plane[index] &= !bit;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let luma = color.luma();
+        for (plane, mask) in self.planes.iter_mut().zip([0b01, 0b10]) {
+            plane.fill(if luma & mask != 0 { 0xFF } else { 0x00 });
+        }
+        Ok(())
+    }
+}
+
+impl<const WIDTH: u32, const HEIGHT: u32, const BYTES: usize> OriginDimensions
+    for GrayFrame<WIDTH, HEIGHT, BYTES>
+{
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Small = GrayFrame<4, 16, 8>;
+
+    fn lit(frame: &mut Small, phase: usize, point: Point) -> bool {
+        let (index, bit) = Small::position(point).unwrap();
+        frame.subframe(phase)[index] & bit != 0
+    }
+
+    #[test]
+    fn level_sets_lit_subframes() {
+        let mut frame = Small::new();
+        for level in 0..4 {
+            Pixel(Point::new(level, 9), Gray2::new(level as u8))
+                .draw(&mut frame)
+                .unwrap();
+        }
+
+        for level in 0..4 {
+            let point = Point::new(level, 9);
+            assert_eq!(frame.pixel(point), Some(Gray2::new(level as u8)));
+            let count = (0..SUBFRAMES)
+                .filter(|&phase| lit(&mut frame, phase, point))
+                .count();
+            assert_eq!(count, level as usize, "level {level}");
+        }
+    }
+
+    #[test]
+    fn layout_matches_display_ram() {
+        let mut frame = Small::new();
+        Pixel(Point::new(2, 9), Gray2::WHITE)
+            .draw(&mut frame)
+            .unwrap();
+
+        // column 2, second byte of it, second bit
+        let mut expected = [0u8; 8];
+        expected[2 * 2 + 1] = 0x40;
+        for phase in 0..SUBFRAMES {
+            assert_eq!(frame.subframe(phase), expected);
+        }
+    }
+
+    #[test]
+    fn redraw_and_clear() {
+        let mut frame = Small::new();
+        let point = Point::new(1, 1);
+        Pixel(point, Gray2::WHITE).draw(&mut frame).unwrap();
+        Pixel(point, Gray2::new(1)).draw(&mut frame).unwrap();
+        assert_eq!(frame.pixel(point), Some(Gray2::new(1)));
+
+        frame.clear(Gray2::new(2)).unwrap();
+        assert_eq!(frame.pixel(Point::new(3, 15)), Some(Gray2::new(2)));
+        assert_eq!(frame.subframe(0), [0xFF; 8]);
+        assert_eq!(frame.subframe(2), [0x00; 8]);
+    }
+
+    #[test]
+    fn outside_is_ignored() {
+        let mut frame = Small::new();
+        for point in [Point::new(-1, 0), Point::new(4, 0), Point::new(0, 16)] {
+            Pixel(point, Gray2::WHITE).draw(&mut frame).unwrap();
+            assert_eq!(frame.pixel(point), None);
+        }
+        assert_eq!(frame.subframe(0), [0; 8]);
+    }
+
+    #[test]
+    fn clipped_to_the_panel() {
+        // 10 rows shown, kept in two bytes a column like the display RAM
+        let mut frame = GrayFrame::<4, 10, 8>::new();
+        assert_eq!(frame.size(), Size::new(4, 10));
+        for y in [9, 10, 15] {
+            Pixel(Point::new(1, y), Gray2::WHITE)
+                .draw(&mut frame)
+                .unwrap();
+        }
+        assert_eq!(frame.pixel(Point::new(1, 10)), None);
+        assert_eq!(frame.subframe(0), [0, 0, 0, 0x40, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn phases_cycle() {
+        let mut frame = Small::new();
+        Pixel(Point::zero(), Gray2::new(1))
+            .draw(&mut frame)
+            .unwrap();
+
+        let lit: [bool; 6] = core::array::from_fn(|_| frame.next_subframe()[0] & 0x80 != 0);
+        assert_eq!(lit, [true, false, false, true, false, false]);
+        assert_eq!(frame.phase(), 0);
+    }
+}
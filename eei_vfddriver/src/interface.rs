@@ -1,6 +1,15 @@
+use crate::error::Error;
 use crate::traits::Command;
 use embedded_hal::{delay::DelayNs, digital::*, spi::Operation, spi::SpiDevice};
 
+/// The byte sent on the wire for `command`.
+///
+/// The controller shifts in LSB first, but [`SPI_MODE`](crate::SPI_MODE)
+/// is MSB first, so every command byte goes out bit reversed.
+pub(crate) fn command_byte<T: Command>(command: T) -> u8 {
+    command.address().reverse_bits()
+}
+
 /// The Connection Interface of all (?) EEI VFD
 ///
 pub(crate) struct DisplayInterface<SPI, RST, DELAY> {
@@ -19,11 +28,7 @@ where
     DELAY: DelayNs,
 {
     pub fn new(spi: SPI, rst: RST, delay: DELAY) -> Self {
-        DisplayInterface {
-            spi,
-            delay,
-            rst,
-        }
+        DisplayInterface { spi, delay, rst }
     }
 
     /// Basic function for sending [Commands](Command) and the data belonging to it.
@@ -34,13 +39,15 @@ where
         command: T,
         args: &[u8],
         data: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.spi.write(&[])?;
-        self.spi.transaction(&mut [
-            Operation::Write(&[command.address().reverse_bits()]),
-            Operation::Write(args),
-            Operation::Write(data),
-        ])
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.spi.write(&[]).map_err(Error::Spi)?;
+        self.spi
+            .transaction(&mut [
+                Operation::Write(&[command_byte(command)]),
+                Operation::Write(args),
+                Operation::Write(data),
+            ])
+            .map_err(Error::Spi)
     }
 
     /// Basic function for sending [Commands](Command) and the data belonging to it.
@@ -50,23 +57,28 @@ where
         &mut self,
         command: T,
         args: &[u8],
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
         // this is nessessary for shifting out the previous frame when communicating
         // with high frequency
-        self.spi.write(&[])?;
-        self.spi.transaction(&mut [
-            Operation::Write(&[command.address().reverse_bits()]),
-            Operation::Write(args),
-        ])
+        self.spi.write(&[]).map_err(Error::Spi)?;
+        self.spi
+            .transaction(&mut [
+                Operation::Write(&[command_byte(command)]),
+                Operation::Write(args),
+            ])
+            .map_err(Error::Spi)
     }
 
     /// Basic function for sending the same byte of data (one u8) multiple times over spi
     ///
     /// Enables direct interaction with the device with the help of [command()](ConnectionInterface::command())
     #[allow(unused)]
-    pub(crate) fn data_x_times<const R: usize>(&mut self, val: u8) -> Result<(), SPI::Error> {
+    pub(crate) fn data_x_times<const R: usize>(
+        &mut self,
+        val: u8,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
         // Transfer data (u8) over spi
-        self.spi.write(&[val; R])
+        self.spi.write(&[val; R]).map_err(Error::Spi)
     }
 
     /// Resets the device.
@@ -76,10 +88,83 @@ where
     /// The timing of keeping the reset pin low seems to be important and different per device.
     /// Most displays seem to require keeping it low for 10ms, but the 7in5_v2 only seems to reset
     /// properly with 2ms
-    pub(crate) fn reset(&mut self, duration: u32) {
-        let _ = self.rst.set_low();
+    pub(crate) fn reset(&mut self, duration: u32) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.rst.set_low().map_err(Error::Pin)?;
         self.delay.delay_ms(duration);
-        let _ = self.rst.set_high();
-        self.delay.delay_ms(1)
+        self.rst.set_high().map_err(Error::Pin)?;
+        self.delay.delay_ms(1);
+        Ok(())
+    }
+}
+
+/// Async version of [`DisplayInterface`], for SPI devices that can await
+/// their transfers (e.g. DMA)
+#[cfg(feature = "async")]
+pub(crate) struct AsyncDisplayInterface<SPI, RST, DELAY> {
+    /// SPI
+    spi: SPI,
+    /// DELAY
+    pub(crate) delay: DELAY,
+    /// Pin for Resetting
+    rst: RST,
+}
+
+#[cfg(feature = "async")]
+impl<SPI, RST, DELAY> AsyncDisplayInterface<SPI, RST, DELAY>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    RST: OutputPin,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    pub fn new(spi: SPI, rst: RST, delay: DELAY) -> Self {
+        AsyncDisplayInterface { spi, delay, rst }
+    }
+
+    /// See [`DisplayInterface::cmd_with_data`]
+    pub(crate) async fn cmd_with_data<T: Command>(
+        &mut self,
+        command: T,
+        args: &[u8],
+        data: &[u8],
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.spi.write(&[]).await.map_err(Error::Spi)?;
+        self.spi
+            .transaction(&mut [
+                Operation::Write(&[command_byte(command)]),
+                Operation::Write(args),
+                Operation::Write(data),
+            ])
+            .await
+            .map_err(Error::Spi)
+    }
+
+    /// See [`DisplayInterface::cmd_with_arg`]
+    pub(crate) async fn cmd_with_arg<T: Command>(
+        &mut self,
+        command: T,
+        args: &[u8],
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        // this is nessessary for shifting out the previous frame when communicating
+        // with high frequency
+        self.spi.write(&[]).await.map_err(Error::Spi)?;
+        self.spi
+            .transaction(&mut [
+                Operation::Write(&[command_byte(command)]),
+                Operation::Write(args),
+            ])
+            .await
+            .map_err(Error::Spi)
+    }
+
+    /// See [`DisplayInterface::reset`]
+    pub(crate) async fn reset(
+        &mut self,
+        duration: u32,
+    ) -> Result<(), Error<SPI::Error, RST::Error>> {
+        self.rst.set_low().map_err(Error::Pin)?;
+        self.delay.delay_ms(duration).await;
+        self.rst.set_high().map_err(Error::Pin)?;
+        self.delay.delay_ms(1).await;
+        Ok(())
     }
 }
@@ -12,19 +12,42 @@
 #[cfg(feature = "graphics")]
 pub mod graphics;
 
+#[cfg(feature = "graphics")]
+pub mod grayscale;
+
 mod traits;
 
 pub mod color;
 
+pub mod error;
+
 /// Interface for the physical connection between display and the controlling device
 mod interface;
 
+mod command;
+
+pub mod config;
+
+pub mod driver;
+
+pub mod model;
+
+pub mod scroll;
+
+#[cfg(test)]
+mod mock;
+
 pub mod gp1287bi;
 
 /// Includes everything important besides the chosen Display
 pub mod prelude {
-    pub use crate::color::Color;
+    pub use crate::color::BinaryColor;
+    pub use crate::config::InitConfig;
+    pub use crate::error::Error;
+    pub use crate::model::Model;
     pub use crate::traits::EEIDisplay;
+    #[cfg(feature = "async")]
+    pub use crate::traits::EEIDisplayAsync;
 
     pub use crate::SPI_MODE;
 
@@ -32,17 +55,20 @@ pub mod prelude {
     pub use crate::graphics::{Display, DisplayRotation};
 }
 
-/// Computes the needed buffer length. Takes care of rounding up in case width
-/// is not divisible by 8.
+/// Computes the needed buffer length. Takes care of rounding up in case
+/// height is not divisible by 8.
+///
+/// The buffer is laid out like the display RAM, one column after the other,
+/// with the top row of every byte in bit 7:
 ///
-///  unused
-///  bits        width
-/// <----><------------------------>
-/// \[XXXXX210\]\[76543210\]...\[76543210\] ^
-/// \[XXXXX210\]\[76543210\]...\[76543210\] | height
-/// \[XXXXX210\]\[76543210\]...\[76543210\] v
+///  column 0    column 1         column width - 1
+/// \[76543210\]  \[76543210\] ...  \[76543210\]  rows 0 - 7
+/// \[76543210\]  \[76543210\] ...  \[76543210\]  rows 8 - 15
+/// \[765XXXXX\]  \[765XXXXX\] ...  \[765XXXXX\]  rows 16 - height - 1
+///      unused
+///       bits
 pub const fn buffer_len(width: usize, height: usize) -> usize {
-    (width + 7) / 8 * height
+    height.div_ceil(8) * width
 }
 
 use embedded_hal::spi::{Mode, Phase, Polarity};
@@ -0,0 +1,80 @@
+//! What the mocked SPI bus and reset pin should see, for the driver tests
+
+extern crate std;
+
+use crate::command::Command;
+use crate::config::InitConfig;
+use crate::model::Model;
+use crate::traits::Command as _;
+use embedded_hal_mock::eh1::digital::{
+    Mock as PinMock, State as PinState, Transaction as PinTransaction,
+};
+use embedded_hal_mock::eh1::spi::Transaction as SpiTransaction;
+use std::vec;
+use std::vec::Vec;
+
+/// What the SPI device should see for one command
+pub(crate) fn expect_command(
+    command: Command,
+    args: &[u8],
+    data: &[u8],
+) -> Vec<SpiTransaction<u8>> {
+    let mut expected = vec![
+        // empty write that flushes the previous transfer
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(vec![]),
+        SpiTransaction::transaction_end(),
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(vec![command.address().reverse_bits()]),
+        SpiTransaction::write_vec(args.to_vec()),
+    ];
+    if !data.is_empty() {
+        expected.push(SpiTransaction::write_vec(data.to_vec()));
+    }
+    expected.push(SpiTransaction::transaction_end());
+    expected
+}
+
+pub(crate) fn expect_commands(commands: &[(Command, &[u8])]) -> Vec<SpiTransaction<u8>> {
+    commands
+        .iter()
+        .flat_map(|&(command, args)| expect_command(command, args, &[]))
+        .collect()
+}
+
+/// Init commands with the vendor's values and the given display area
+/// setting, as in the vendor sample code
+pub(crate) fn vendor_init(display_area: &[u8]) -> Vec<(Command, &[u8])> {
+    vec![
+        (Command::Reset, &[]),
+        (Command::OscillationSetting, &[0x08]),
+        (Command::VFDModeSetting, &[0x02, 0x00]),
+        (Command::DisplayAreaSetting, display_area),
+        (Command::InternalSpeedSetting, &[0x20, 0x3F, 0x00, 0x01]),
+        (Command::BrightnessSetting, &[0x00, 0x30]),
+        (Command::ClearGRAM, &[]),
+        (Command::DisplayPosition1Offset, &[0x00, 0x00]),
+        (Command::DisplayPosition2Offset, &[0x00, 0x00]),
+        (Command::UnknownInit, &[0x00]),
+        (Command::FrameSyncSetting, &[0x00]),
+        (Command::DisplayModeSetting, &[0x00]),
+    ]
+}
+
+/// Reset pin pulsed once, as at the start of init
+pub(crate) fn reset_pin() -> PinMock {
+    PinMock::new(&[
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ])
+}
+
+/// A made up model with a display RAM only as tall as its 256x48 panel, for
+/// checking the driver takes its limits from the [`Model`]
+pub(crate) struct Short;
+
+impl Model for Short {
+    const GRAM_COLUMNS: u32 = 256;
+    const GRAM_ROWS: u32 = 48;
+    const DEFAULT_CONFIG: InitConfig = InitConfig::new(256, 48);
+}
@@ -0,0 +1,21 @@
+//! What sets one VFD module apart from another
+//!
+//! The controllers on the EEI modules share a command set, so a new module
+//! only needs a [`Model`] describing its display RAM and power up settings.
+//! The drivers in [`crate::driver`] do the rest.
+
+use crate::config::InitConfig;
+
+/// Description of a VFD module, everything the shared driver needs to know
+pub trait Model {
+    /// Columns of display RAM, one for each pixel along the long side
+    const GRAM_COLUMNS: u32;
+    /// Rows of display RAM, may be more than the panel shows
+    const GRAM_ROWS: u32;
+    /// Settings [`EEIDisplay::new`](crate::prelude::EEIDisplay::new)
+    /// initialises the module with
+    const DEFAULT_CONFIG: InitConfig;
+
+    /// Bytes in a full frame: column after column, 8 rows to a byte
+    const FRAME_BYTES: usize = (Self::GRAM_COLUMNS * Self::GRAM_ROWS.div_ceil(8)) as usize;
+}
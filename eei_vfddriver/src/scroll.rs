@@ -0,0 +1,251 @@
+//! Hardware scrolling and page flipping with the display position offsets
+//!
+//! The panel doesn't have to show the display RAM from its first column and
+//! row: the two display position offsets say where it starts. Moving them
+//! scrolls whatever is in the display RAM without sending a frame again, and
+//! on a model with more RAM rows than the panel shows (the gp1287bi has 128
+//! for 50) a second frame can be written out of sight and flipped to with a
+//! single command.
+//!
+//! The types here only work out the offsets, send them with
+//! [`Vfd::set_display_offset`](crate::driver::Vfd::set_display_offset) and
+//! friends.
+
+use crate::command::Command;
+use crate::error::Error;
+use crate::model::Model;
+
+/// One of the controller's two display position offset registers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayPosition {
+    /// Display position 1
+    First,
+    /// Display position 2
+    Second,
+}
+
+impl DisplayPosition {
+    pub(crate) fn command(self) -> Command {
+        match self {
+            DisplayPosition::First => Command::DisplayPosition1Offset,
+            DisplayPosition::Second => Command::DisplayPosition2Offset,
+        }
+    }
+}
+
+/// Where in the display RAM a display position starts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Offset {
+    /// First display RAM column shown
+    pub x: u32,
+    /// First display RAM row shown
+    pub y: u32,
+}
+
+impl Offset {
+    /// Start showing the display RAM at column `x`, row `y`
+    pub const fn new(x: u32, y: u32) -> Self {
+        Offset { x, y }
+    }
+
+    /// Argument bytes of the offset command, refusing offsets outside of the
+    /// display RAM
+    pub(crate) fn args<M: Model, SpiE, PinE>(self) -> Result<[u8; 2], Error<SpiE, PinE>> {
+        if self.x >= M::GRAM_COLUMNS || self.y >= M::GRAM_ROWS {
+            return Err(Error::WindowOutOfBounds);
+        }
+        Ok([self.x as u8, self.y as u8])
+    }
+}
+
+/// Ticker that runs a strip of the display RAM sideways, wrapping around
+///
+/// The strip is as wide as the display RAM, whatever leaves on the left
+/// comes back in on the right.
+#[derive(Clone, Copy, Debug)]
+pub struct Marquee {
+    columns: u32,
+    x: u32,
+    y: u32,
+}
+
+impl Marquee {
+    /// Marquee over the whole width of `M`'s display RAM, showing it from
+    /// row `y` on
+    pub const fn new<M: Model>(y: u32) -> Self {
+        Marquee {
+            columns: M::GRAM_COLUMNS,
+            x: 0,
+            y,
+        }
+    }
+
+    /// The offset showing the strip as it is now
+    pub fn offset(&self) -> Offset {
+        Offset::new(self.x, self.y)
+    }
+
+    /// Move the text `columns` to the left
+    pub fn step(&mut self, columns: u32) -> Offset {
+        self.x = (self.x + columns % self.columns) % self.columns;
+        self.offset()
+    }
+}
+
+/// Smooth scrolling through content taller than the panel
+///
+/// The content sits in the display RAM from row 0 down; the offset stops
+/// when the last row of it reaches the bottom of the panel.
+#[derive(Clone, Copy, Debug)]
+pub struct VerticalScroll {
+    /// Highest first row that still shows nothing but content
+    last: u32,
+    y: u32,
+}
+
+impl VerticalScroll {
+    /// Scroll over `content` rows of `M`'s display RAM on a panel showing
+    /// `visible` rows at a time.
+    ///
+    /// `None` if the content doesn't fit in the display RAM.
+    pub const fn new<M: Model>(content: u32, visible: u32) -> Option<Self> {
+        if content > M::GRAM_ROWS {
+            return None;
+        }
+        Some(VerticalScroll {
+            last: content.saturating_sub(visible),
+            y: 0,
+        })
+    }
+
+    /// The offset showing the content as it is now
+    pub fn offset(&self) -> Offset {
+        Offset::new(0, self.y)
+    }
+
+    /// Whether the end of the content is on the panel
+    pub fn at_end(&self) -> bool {
+        self.y == self.last
+    }
+
+    /// Scroll `rows` down, or up for a negative count
+    pub fn step(&mut self, rows: i32) -> Offset {
+        self.y = self.y.saturating_add_signed(rows).min(self.last);
+        self.offset()
+    }
+
+    /// Jump back to the top of the content
+    pub fn reset(&mut self) -> Offset {
+        self.y = 0;
+        self.offset()
+    }
+}
+
+/// Two frames in the display RAM, one on the panel and one being drawn
+///
+/// Draw the next frame into [`PageFlip::back_row`] and onwards, then flip:
+/// the panel switches over in one go, so it never shows a half sent frame.
+#[derive(Clone, Copy, Debug)]
+pub struct PageFlip {
+    page_rows: u32,
+    front: u32,
+}
+
+impl PageFlip {
+    /// Pages for a panel showing `visible` rows of `M`'s display RAM.
+    ///
+    /// `None` if two of them don't fit in the display RAM.
+    pub const fn new<M: Model>(visible: u32) -> Option<Self> {
+        // partial updates start on whole bytes
+        let page_rows = visible.div_ceil(8) * 8;
+        if page_rows == 0 || 2 * page_rows > M::GRAM_ROWS {
+            return None;
+        }
+        Some(PageFlip {
+            page_rows,
+            front: 0,
+        })
+    }
+
+    /// Display RAM rows in a page
+    pub fn page_rows(&self) -> u32 {
+        self.page_rows
+    }
+
+    /// First row of the page on the panel
+    pub fn front_row(&self) -> u32 {
+        self.front * self.page_rows
+    }
+
+    /// First row of the page to draw into
+    pub fn back_row(&self) -> u32 {
+        (1 - self.front) * self.page_rows
+    }
+
+    /// Swap the pages, returning the offset that shows the new front page
+    pub fn flip(&mut self) -> Offset {
+        self.front = 1 - self.front;
+        Offset::new(0, self.front_row())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gp1287bi::Gp1287bi;
+    use crate::mock::Short;
+
+    #[test]
+    fn offsets_stay_in_gram() {
+        assert_eq!(
+            Offset::new(255, 127).args::<Gp1287bi, (), ()>(),
+            Ok([255, 127])
+        );
+        assert_eq!(
+            Offset::new(256, 0).args::<Gp1287bi, (), ()>(),
+            Err(Error::WindowOutOfBounds)
+        );
+        assert_eq!(
+            Offset::new(0, 48).args::<Short, (), ()>(),
+            Err(Error::WindowOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn marquee_wraps() {
+        let mut marquee = Marquee::new::<Gp1287bi>(64);
+        assert_eq!(marquee.step(100), Offset::new(100, 64));
+        assert_eq!(marquee.step(156), Offset::new(0, 64));
+        assert_eq!(marquee.step(3 * 256 + 1), Offset::new(1, 64));
+    }
+
+    #[test]
+    fn vertical_scroll_stops_at_the_ends() {
+        let mut scroll = VerticalScroll::new::<Gp1287bi>(100, 50).unwrap();
+        assert_eq!(scroll.step(-4), Offset::new(0, 0));
+        assert_eq!(scroll.step(30), Offset::new(0, 30));
+        assert!(!scroll.at_end());
+        assert_eq!(scroll.step(30), Offset::new(0, 50));
+        assert!(scroll.at_end());
+        assert_eq!(scroll.reset(), Offset::new(0, 0));
+
+        assert!(VerticalScroll::new::<Gp1287bi>(129, 50).is_none());
+        // content shorter than the panel doesn't move
+        let mut short = VerticalScroll::new::<Short>(20, 48).unwrap();
+        assert_eq!(short.step(5), Offset::new(0, 0));
+    }
+
+    #[test]
+    fn page_flip_alternates() {
+        let mut pages = PageFlip::new::<Gp1287bi>(50).unwrap();
+        assert_eq!(pages.page_rows(), 56);
+        assert_eq!((pages.front_row(), pages.back_row()), (0, 56));
+
+        assert_eq!(pages.flip(), Offset::new(0, 56));
+        assert_eq!((pages.front_row(), pages.back_row()), (56, 0));
+        assert_eq!(pages.flip(), Offset::new(0, 0));
+
+        // no room for a second page
+        assert!(PageFlip::new::<Short>(48).is_none());
+    }
+}
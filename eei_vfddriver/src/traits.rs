@@ -1,3 +1,4 @@
+use crate::error::Error;
 use core::marker::Sized;
 use embedded_hal::{delay::DelayNs, digital::*, spi::SpiDevice};
 
@@ -23,7 +24,7 @@ where
     /// This function calls [reset](EEIDisplay::reset),
     /// so you don't need to call reset your self when trying to wake your device up
     /// after setting it to sleep.
-    fn init(&mut self) -> Result<(), SPI::Error>;
+    fn init(&mut self) -> Result<(), Error<SPI::Error, RST::Error>>;
 }
 
 /// All the functions to interact with the EEI VFDs
@@ -38,19 +39,22 @@ where
     /// Creates a new driver from a SPI peripheral, CS Pin, Busy InputPin, DC
     ///
     /// This already initialises the device.
-    fn new(spi: SPI, rst: RST, delay: DELAY) -> Result<Self, SPI::Error>
+    fn new(spi: SPI, rst: RST, delay: DELAY) -> Result<Self, Error<SPI::Error, RST::Error>>
     where
         Self: Sized;
 
     /// Let the device enter deep-sleep mode to save power.
     ///
     /// The deep sleep mode returns to standby with a hardware reset.
-    fn sleep(&mut self) -> Result<(), SPI::Error>;
+    fn sleep(&mut self) -> Result<(), Error<SPI::Error, RST::Error>>;
 
     /// Wakes the device up from sleep
     ///
-    /// Also reintialises the device if necessary.
-    fn wake_up(&mut self) -> Result<(), SPI::Error>;
+    /// Deep sleep is only left through a hardware reset, so this runs the
+    /// whole init again, with the settings the device was created with. The
+    /// display RAM is cleared and the brightness is back to its initial
+    /// value: send both again afterwards.
+    fn wake_up(&mut self) -> Result<(), Error<SPI::Error, RST::Error>>;
 
     /// Get the width of the display
     fn width(&self) -> u32;
@@ -59,16 +63,23 @@ where
     fn height(&self) -> u32;
 
     /// set brightness of screen
-    fn set_brightness(&mut self, val: u32) -> Result<(), SPI::Error>;
+    ///
+    /// Fails with [`Error::BrightnessOutOfRange`] above 1023.
+    fn set_brightness(&mut self, val: u32) -> Result<(), Error<SPI::Error, RST::Error>>;
 
     /// Transmit a full frame to the SRAM of the EPD
-    fn update_frame(&mut self, buffer: &[u8]) -> Result<(), SPI::Error>;
+    ///
+    /// Fails with [`Error::BufferSize`] unless `buffer` is exactly one frame.
+    fn update_frame(&mut self, buffer: &[u8]) -> Result<(), Error<SPI::Error, RST::Error>>;
 
     /// Transmits partial data to the SRAM of the EPD
     ///
     /// (x,y) is the top left corner
     ///
     /// BUFFER needs to be of size: width / 8 * height !
+    ///
+    /// Fails with [`Error::WindowOutOfBounds`] if the window doesn't fit on
+    /// the display and [`Error::BufferSize`] if the buffer doesn't match it.
     fn update_partial_frame(
         &mut self,
         buffer: &[u8],
@@ -76,9 +87,79 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), Error<SPI::Error, RST::Error>>;
 
     /// Clears the frame buffer on the VFD with the declared background color
     ///
-    fn clear_frame(&mut self) -> Result<(), SPI::Error>;
+    fn clear_frame(&mut self) -> Result<(), Error<SPI::Error, RST::Error>>;
+}
+
+#[cfg(feature = "async")]
+pub(crate) trait EEIInitAsync<SPI, RST, DELAY>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    RST: OutputPin,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    /// Async version of [`EEIInit::init`]
+    async fn init(&mut self) -> Result<(), Error<SPI::Error, RST::Error>>;
+}
+
+/// Async version of [`EEIDisplay`], for SPI devices that can await their
+/// transfers.
+///
+/// Sends exactly the same commands as the blocking driver, but lets the
+/// executor run other tasks while a frame is going out.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait EEIDisplayAsync<SPI, RST, DELAY>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    RST: OutputPin,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    /// The Color Type used by the Display
+    type DisplayColor;
+    /// Creates a new driver from a SPI device, reset pin and delay
+    ///
+    /// This already initialises the device.
+    async fn new(spi: SPI, rst: RST, delay: DELAY) -> Result<Self, Error<SPI::Error, RST::Error>>
+    where
+        Self: Sized;
+
+    /// Let the device enter deep-sleep mode to save power.
+    ///
+    /// The deep sleep mode returns to standby with a hardware reset.
+    async fn sleep(&mut self) -> Result<(), Error<SPI::Error, RST::Error>>;
+
+    /// Wakes the device up from sleep
+    ///
+    /// Same as [`EEIDisplay::wake_up`]: the device is reset and initialised
+    /// again, so the frame and brightness have to be sent again.
+    async fn wake_up(&mut self) -> Result<(), Error<SPI::Error, RST::Error>>;
+
+    /// Get the width of the display
+    fn width(&self) -> u32;
+
+    /// Get the height of the display
+    fn height(&self) -> u32;
+
+    /// set brightness of screen, at most 1023
+    async fn set_brightness(&mut self, val: u32) -> Result<(), Error<SPI::Error, RST::Error>>;
+
+    /// Transmit a full frame to the SRAM of the VFD
+    async fn update_frame(&mut self, buffer: &[u8]) -> Result<(), Error<SPI::Error, RST::Error>>;
+
+    /// Transmits part of a frame, see [`EEIDisplay::update_partial_frame`]
+    async fn update_partial_frame(
+        &mut self,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), Error<SPI::Error, RST::Error>>;
+
+    /// Clears the frame buffer on the VFD
+    async fn clear_frame(&mut self) -> Result<(), Error<SPI::Error, RST::Error>>;
 }
@@ -0,0 +1,154 @@
+//! `fill_contiguous` through the transforms against the pixel by pixel path.
+//!
+//! The display works like a typical SPI panel driver: every `fill_contiguous`
+//! sets an address window and streams the colours into it, while `draw_iter`
+//! has to set a window for every single pixel. The bus traffic goes into a
+//! buffer, so the numbers are for the CPU side only. Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use embedded_graphics_core::{
+    pixelcolor::{raw::RawU16, Rgb565},
+    prelude::*,
+    primitives::Rectangle,
+};
+use embedded_graphics_transform::{
+    FlipX, FlipY, Rotate0, Rotate180, Rotate270, Rotate90, Transpose,
+};
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 240;
+
+struct WindowedDisplay {
+    /// Words sent to the panel
+    bus: Vec<u16>,
+}
+
+impl WindowedDisplay {
+    fn new() -> Self {
+        WindowedDisplay {
+            bus: Vec::with_capacity(4 * (WIDTH * HEIGHT) as usize),
+        }
+    }
+
+    fn set_window(&mut self, area: &Rectangle) {
+        let Point { x, y } = area.top_left;
+        let end = area.top_left + area.size - Point::new(1, 1);
+        self.bus.extend([
+            0x2A,
+            x as u16,
+            end.x as u16,
+            0x2B,
+            y as u16,
+            end.y as u16,
+            0x2C,
+        ]);
+    }
+}
+
+impl OriginDimensions for WindowedDisplay {
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+impl DrawTarget for WindowedDisplay {
+    type Color = Rgb565;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if bounds.contains(point) {
+                self.set_window(&Rectangle::new(point, Size::new(1, 1)));
+                self.bus.push(RawU16::from(color).into_inner());
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        if area.intersection(&self.bounding_box()) != *area {
+            return self.draw_iter(area.points().zip(colors).map(|(pt, c)| Pixel(pt, c)));
+        }
+        self.set_window(area);
+        let pixels = (area.size.width * area.size.height) as usize;
+        // colour by colour, like a driver writing them out
+        for color in colors.into_iter().take(pixels) {
+            self.bus.push(RawU16::from(color).into_inner());
+        }
+        Ok(())
+    }
+}
+
+fn colors() -> impl Iterator<Item = Rgb565> {
+    (0u32..).map(|i| Rgb565::new(i as u8 & 31, (i >> 5) as u8 & 63, (i >> 11) as u8 & 31))
+}
+
+/// Average time of `f` over enough runs to fill a few hundred milliseconds
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0;
+    while start.elapsed() < Duration::from_millis(300) {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn bench<T>(name: &str, mut target: T)
+where
+    T: DrawTarget<Color = Rgb565, Error = core::convert::Infallible> + AsMut<WindowedDisplay>,
+{
+    // the whole (transformed) screen and a text sized box
+    let areas = [
+        ("full", target.bounding_box()),
+        (
+            "small",
+            Rectangle::new(Point::new(17, 9), Size::new(40, 16)),
+        ),
+    ];
+    for (area_name, area) in areas {
+        let pixels = (area.size.width * area.size.height) as usize;
+        let fast = time(|| {
+            target.as_mut().bus.clear();
+            target
+                .fill_contiguous(&area, black_box(colors().take(pixels)))
+                .unwrap()
+        });
+        let fast_words = target.as_mut().bus.len();
+        let generic = time(|| {
+            target.as_mut().bus.clear();
+            target
+                .draw_iter(
+                    area.points()
+                        .zip(black_box(colors()))
+                        .map(|(pt, c)| Pixel(pt, c)),
+                )
+                .unwrap()
+        });
+        let generic_words = target.as_mut().bus.len();
+        println!(
+            "{name:<10} {area_name:<6} fill_contiguous {fast:>10.2?} {fast_words:>7} words  \
+             draw_iter {generic:>10.2?} {generic_words:>7} words  x{:.1}",
+            generic.as_secs_f64() / fast.as_secs_f64()
+        );
+    }
+}
+
+fn main() {
+    bench("Rotate0", Rotate0::new(WindowedDisplay::new()));
+    bench("Rotate90", Rotate90::new(WindowedDisplay::new()));
+    bench("Rotate180", Rotate180::new(WindowedDisplay::new()));
+    bench("Rotate270", Rotate270::new(WindowedDisplay::new()));
+    bench("Transpose", Transpose::new(WindowedDisplay::new()));
+    bench("FlipX", FlipX::new(WindowedDisplay::new()));
+    bench("FlipY", FlipY::new(WindowedDisplay::new()));
+}
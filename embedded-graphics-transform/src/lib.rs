@@ -8,16 +8,29 @@
 //! Specifically, it implements:
 //! - rotation by 90/180/270 degrees (and 0, for consistency)
 //! - mirroring
-//! - transposition
+//! - transposition, along either diagonal
 //!
-//! Note that these transformations can be composed if needed.
+//! Note that these transformations can be composed if needed. [`Orient`]
+//! picks any of them at runtime from an [`Orientation`], which can also be
+//! composed and inverted without touching the display.
 //!
 //! Because this is a completely generic implementation, it cannot take
-//! advantage of any hardware or driver specific specializations. In particular,
-//! [`DrawTarget::fill_contiguous`] must fall back to a generic implementation
-//! using [`draw_iter`](DrawTarget::draw_iter).
-//! ([`fill_solid`](DrawTarget::fill_solid) and [`clear`](DrawTarget::clear) can
-//! use specialized implementations, however.)
+//! advantage of any hardware or driver specific specializations. It does pass
+//! [`fill_solid`](DrawTarget::fill_solid), [`clear`](DrawTarget::clear) and
+//! [`fill_contiguous`](DrawTarget::fill_contiguous) on to the display as area
+//! fills rather than single pixels, so a display with fast area fills keeps
+//! most of its speed. For `fill_contiguous` the colours have to be reordered
+//! to match the display's rows: mirroring and transposing go through a small
+//! buffer on the stack ([`SCRATCH_PIXELS`] colours) and send the area a few
+//! rows or one row at a time.
+//!
+//! For drawing into part of a display there are [`Translate`], [`Clip`] and
+//! [`Viewport`], made with the methods of [`ViewportExt`]. A viewport gives a
+//! widget a display of its own, with its own origin and size, that it cannot
+//! draw outside of.
+//!
+//! [`Scale`] and [`ScaleBy`] draw every pixel as a square block, to blow up
+//! fonts and images by a whole number.
 //!
 //! All the transforms implement [`AsRef<D>`]/[`AsMut<D>`] to get access to the
 //! underlying display object so that its inherent functions can be called.
@@ -26,8 +39,14 @@
 use core::ops::{Deref, DerefMut};
 use embedded_graphics_core::{prelude::*, primitives::Rectangle};
 
+pub use r#impl::SCRATCH_PIXELS;
+pub use scale::{Scale, ScaleBy};
+pub use viewport::{Clip, Translate, Viewport, ViewportExt};
+
+mod scale;
 #[cfg(test)]
 mod tests;
+mod viewport;
 
 macro_rules! xform_type {
     ($inner:ident , ) => { $inner };
@@ -163,6 +182,9 @@ impl_xform! {
     FlipX: MirrorX;
     /// Mirror image around Y axis.
     FlipY: MirrorY;
+    /// Transpose X and Y coordinates across the other diagonal, from top
+    /// right to bottom left.
+    AntiTranspose: MirrorX MirrorY TransposeXY;
 }
 
 /// Image rotation direction and amount.
@@ -300,9 +322,336 @@ impl<D: DrawTarget> DrawTarget for Rotate<D> {
     }
 }
 
+/// Any of the eight ways to put an image on a rectangular display: one of the
+/// four rotations, with or without mirroring.
+///
+/// Mirrored orientations flip the image left to right first and then rotate
+/// it, so [`Orientation::FlipX`] is the mirrored [`Orientation::Rotate0`] and
+/// [`Orientation::FlipY`] the mirrored [`Orientation::Rotate180`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Orientation {
+    /// No-op (identity) orientation.
+    Rotate0,
+    /// Rotate 90 degrees to the right.
+    Rotate90,
+    /// Rotate 180 degrees.
+    Rotate180,
+    /// Rotate 90 degrees to the left.
+    Rotate270,
+    /// Mirror around the X axis.
+    FlipX,
+    /// Mirror and rotate 90 degrees to the right, see [`AntiTranspose`].
+    AntiTranspose,
+    /// Mirror around the Y axis.
+    FlipY,
+    /// Mirror and rotate 90 degrees to the left, see [`Transpose`].
+    Transpose,
+}
+
+impl Orientation {
+    /// All eight orientations, the four rotations first.
+    pub const ALL: [Orientation; 8] = [
+        Orientation::Rotate0,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+        Orientation::FlipX,
+        Orientation::AntiTranspose,
+        Orientation::FlipY,
+        Orientation::Transpose,
+    ];
+
+    /// The orientation that mirrors the image if `mirrored` and then rotates
+    /// it by `rotation`.
+    pub const fn new(rotation: Rotation, mirrored: bool) -> Self {
+        Self::from_parts(rotation as u8, mirrored)
+    }
+
+    /// The rotation, applied after any mirroring.
+    pub const fn rotation(self) -> Rotation {
+        match self.quarter_turns() {
+            0 => Rotation::Rotate0,
+            1 => Rotation::Rotate90,
+            2 => Rotation::Rotate180,
+            _ => Rotation::Rotate270,
+        }
+    }
+
+    /// Whether the image is mirrored.
+    pub const fn is_mirrored(self) -> bool {
+        self as u8 >= 4
+    }
+
+    /// The orientation of applying `self` and then `next` to the image.
+    ///
+    /// This is the same as drawing through an [`Orient`] for `self` wrapped
+    /// around one for `next`.
+    pub const fn then(self, next: Orientation) -> Self {
+        // mirroring first turns the rotations before it the other way
+        let turns = if next.is_mirrored() {
+            next.quarter_turns() + 4 - self.quarter_turns()
+        } else {
+            next.quarter_turns() + self.quarter_turns()
+        };
+        Self::from_parts(turns, self.is_mirrored() != next.is_mirrored())
+    }
+
+    /// The orientation that undoes `self`.
+    pub const fn inverse(self) -> Self {
+        if self.is_mirrored() {
+            // every mirrored orientation is its own inverse
+            self
+        } else {
+            Self::from_parts(4 - self.quarter_turns(), false)
+        }
+    }
+
+    /// Whether width and height swap over.
+    pub const fn is_transposing(self) -> bool {
+        self.quarter_turns() % 2 == 1
+    }
+
+    const fn quarter_turns(self) -> u8 {
+        self as u8 % 4
+    }
+
+    const fn from_parts(quarter_turns: u8, mirrored: bool) -> Self {
+        Self::ALL[(quarter_turns % 4 + if mirrored { 4 } else { 0 }) as usize]
+    }
+}
+
+impl From<Rotation> for Orientation {
+    fn from(rotation: Rotation) -> Self {
+        Orientation::new(rotation, false)
+    }
+}
+
+enum OrientInner<D> {
+    Rotate0(Rotate0<D>),
+    Rotate90(Rotate90<D>),
+    Rotate180(Rotate180<D>),
+    Rotate270(Rotate270<D>),
+    FlipX(FlipX<D>),
+    AntiTranspose(AntiTranspose<D>),
+    FlipY(FlipY<D>),
+    Transpose(Transpose<D>),
+}
+
+/// Orient an image with runtime configuration.
+///
+/// Like [`Rotate`], but for any [`Orientation`], so a display can be mounted
+/// upside down or viewed in a mirror without changing types. It dispatches to
+/// the fixed transforms the same way.
+pub struct Orient<D> {
+    orientation: Orientation,
+    target: OrientInner<D>,
+}
+
+macro_rules! orient_impl {
+    (& $orient:expr, $func:ident ( $($args:expr),* $(,)?)) => {
+        orient_impl!(@match &$orient.target, $func($($args),*))
+    };
+    (&mut $orient:expr, $func:ident ( $($args:expr),* $(,)?)) => {
+        orient_impl!(@match &mut $orient.target, $func($($args),*))
+    };
+    (@match $target:expr, $func:ident ( $($args:expr),* )) => {
+        match $target {
+            OrientInner::Rotate0(inner) => inner.$func($($args),*),
+            OrientInner::Rotate90(inner) => inner.$func($($args),*),
+            OrientInner::Rotate180(inner) => inner.$func($($args),*),
+            OrientInner::Rotate270(inner) => inner.$func($($args),*),
+            OrientInner::FlipX(inner) => inner.$func($($args),*),
+            OrientInner::AntiTranspose(inner) => inner.$func($($args),*),
+            OrientInner::FlipY(inner) => inner.$func($($args),*),
+            OrientInner::Transpose(inner) => inner.$func($($args),*),
+        }
+    };
+    ($orient:expr, $func:ident ( $($args:expr),* $(,)?)) => {
+        orient_impl!(@match $orient.target, $func($($args),*))
+    };
+}
+
+impl<D> Orient<D> {
+    /// Create a new transformation using the given [`Orientation`].
+    pub fn new(orientation: Orientation, target: D) -> Self {
+        let target = match orientation {
+            Orientation::Rotate0 => OrientInner::Rotate0(Rotate0::new(target)),
+            Orientation::Rotate90 => OrientInner::Rotate90(Rotate90::new(target)),
+            Orientation::Rotate180 => OrientInner::Rotate180(Rotate180::new(target)),
+            Orientation::Rotate270 => OrientInner::Rotate270(Rotate270::new(target)),
+            Orientation::FlipX => OrientInner::FlipX(FlipX::new(target)),
+            Orientation::AntiTranspose => OrientInner::AntiTranspose(AntiTranspose::new(target)),
+            Orientation::FlipY => OrientInner::FlipY(FlipY::new(target)),
+            Orientation::Transpose => OrientInner::Transpose(Transpose::new(target)),
+        };
+        Orient {
+            orientation,
+            target,
+        }
+    }
+
+    /// The orientation images are drawn in.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Recover the inner display instance.
+    pub fn into_inner(self) -> D {
+        orient_impl!(self, into_inner())
+    }
+}
+
+impl<D> Deref for Orient<D> {
+    type Target = D;
+
+    fn deref(&self) -> &D {
+        self.as_ref()
+    }
+}
+
+impl<D> DerefMut for Orient<D> {
+    fn deref_mut(&mut self) -> &mut D {
+        self.as_mut()
+    }
+}
+
+impl<D> AsRef<D> for Orient<D> {
+    fn as_ref(&self) -> &D {
+        orient_impl!(&self, as_ref())
+    }
+}
+
+impl<D> AsMut<D> for Orient<D> {
+    fn as_mut(&mut self) -> &mut D {
+        orient_impl!(&mut self, as_mut())
+    }
+}
+
+impl<D: Dimensions> Dimensions for Orient<D> {
+    fn bounding_box(&self) -> Rectangle {
+        orient_impl!(&self, bounding_box())
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Orient<D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        orient_impl!(&mut self, draw_iter(pixels))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        orient_impl!(&mut self, fill_contiguous(area, colors))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        orient_impl!(&mut self, fill_solid(area, color))
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        orient_impl!(&mut self, clear(color))
+    }
+}
+
 mod r#impl {
     use embedded_graphics_core::{prelude::*, primitives::Rectangle};
 
+    /// Colours buffered on the stack to reorder a
+    /// [`fill_contiguous`](DrawTarget::fill_contiguous) for the display
+    pub const SCRATCH_PIXELS: usize = 256;
+
+    /// Rows of `area` that fit in the scratch buffer together, at least 1
+    pub(crate) fn band_rows(area: &Rectangle) -> u32 {
+        let rows = SCRATCH_PIXELS as u32 / area.size.width.max(1);
+        rows.clamp(1, area.size.height.max(1))
+    }
+
+    /// Fill `area` of `target` `band` rows at a time, `band * width` being at
+    /// most [`SCRATCH_PIXELS`].
+    ///
+    /// The colours of each band are gathered in the scratch buffer, in the
+    /// order they came in, and handed to `block` with the band's part of
+    /// `area`. If they run out part way through a band, what there is gets
+    /// drawn pixel by pixel instead.
+    pub(crate) fn fill_bands<T, I>(
+        target: &mut T,
+        area: &Rectangle,
+        band: u32,
+        colors: I,
+        mut block: impl FnMut(&mut T, &Rectangle, &[T::Color]) -> Result<(), T::Error>,
+    ) -> Result<(), T::Error>
+    where
+        T: DrawTarget,
+        I: IntoIterator<Item = T::Color>,
+    {
+        let mut colors = colors.into_iter().peekable();
+        let Some(&first) = colors.peek() else {
+            return Ok(());
+        };
+        let Size { width, height } = area.size;
+        let mut scratch = [first; SCRATCH_PIXELS];
+
+        let mut row = 0;
+        while row < height {
+            let rows = band.min(height - row);
+            let part = Rectangle::new(
+                area.top_left + Point::new(0, row as i32),
+                Size::new(width, rows),
+            );
+            let wanted = (width * rows) as usize;
+            let mut count = 0;
+            for (slot, color) in scratch[..wanted].iter_mut().zip(colors.by_ref()) {
+                *slot = color;
+                count += 1;
+            }
+
+            if count < wanted {
+                let pixels = part.points().zip(&scratch[..count]);
+                return target.draw_iter(pixels.map(|(pt, &c)| Pixel(pt, c)));
+            }
+            block(target, &part, &scratch[..wanted])?;
+            row += rows;
+        }
+        Ok(())
+    }
+
+    /// `area` mirrored left to right in a display `width` wide
+    fn mirror_x(area: &Rectangle, width: u32) -> Rectangle {
+        Rectangle {
+            top_left: Point {
+                x: width as i32 - area.top_left.x - area.size.width as i32,
+                y: area.top_left.y,
+            },
+            size: area.size,
+        }
+    }
+
+    /// `area` mirrored top to bottom in a display `height` high
+    fn mirror_y(area: &Rectangle, height: u32) -> Rectangle {
+        Rectangle {
+            top_left: Point {
+                x: area.top_left.x,
+                y: height as i32 - area.top_left.y - area.size.height as i32,
+            },
+            size: area.size,
+        }
+    }
+
+    /// The `row`th row of `area`, `len` pixels from column `x`
+    fn row_span(area: &Rectangle, row: u32, x: u32, len: u32) -> Rectangle {
+        Rectangle::new(
+            area.top_left + Point::new(x as i32, row as i32),
+            Size::new(len, 1),
+        )
+    }
+
     pub(crate) trait Transpose {
         fn transpose(self) -> Self;
     }
@@ -383,6 +732,39 @@ mod r#impl {
                     .map(|Pixel(loc, col)| Pixel(loc.transpose(), col)),
             )
         }
+        /// Outer rows are columns of the display. Narrow areas go out in
+        /// bands of rows, each turned into a block of whole display rows;
+        /// wide ones one display column at a time.
+        fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Self::Color>,
+        {
+            if area.is_zero_sized() {
+                return Ok(());
+            }
+
+            let band = band_rows(area);
+            if band == 1 {
+                // a single row is a single display column, already in order
+                let mut colors = colors.into_iter().peekable();
+                for row in 0..area.size.height {
+                    if colors.peek().is_none() {
+                        break;
+                    }
+                    let span = row_span(area, row, 0, area.size.width).transpose();
+                    let row = colors.by_ref().take(area.size.width as usize);
+                    self.target.fill_contiguous(&span, row)?;
+                }
+                return Ok(());
+            }
+
+            fill_bands(self, area, band, colors, |this, part, scratch| {
+                let (width, rows) = (part.size.width as usize, part.size.height as usize);
+                let columns =
+                    (0..width).flat_map(|x| (0..rows).map(move |y| scratch[y * width + x]));
+                this.target.fill_contiguous(&part.transpose(), columns)
+            })
+        }
 
         fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
             let area = area.transpose();
@@ -444,16 +826,58 @@ mod r#impl {
                     .map(|Pixel(Point { x, y }, col)| Pixel(Point { x: width - x, y }, col)),
             )
         }
+        /// Narrow areas go out in bands of rows, every row reversed; wide
+        /// ones a row at a time, reversed through the scratch buffer in
+        /// pieces if it is too long for it.
+        fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Self::Color>,
+        {
+            if area.is_zero_sized() {
+                return Ok(());
+            }
+            let display_width = self.bounding_box().size.width;
+
+            let band = band_rows(area);
+            if band > 1 {
+                return fill_bands(self, area, band, colors, |this, part, scratch| {
+                    let rows = scratch.chunks_exact(part.size.width as usize);
+                    let reversed = rows.flat_map(|row| row.iter().rev()).copied();
+                    this.target
+                        .fill_contiguous(&mirror_x(part, display_width), reversed)
+                });
+            }
 
-        fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
-            let width = self.bounding_box().size.width as i32 - 1;
-            let area = Rectangle {
-                top_left: Point {
-                    x: width - area.top_left.x - area.size.width as i32,
-                    y: area.top_left.y,
-                },
-                size: area.size,
+            let Size { width, height } = area.size;
+            let mut colors = colors.into_iter().peekable();
+            let Some(&first) = colors.peek() else {
+                return Ok(());
             };
+            let mut scratch = [first; SCRATCH_PIXELS];
+            for row in 0..height {
+                let mut x = 0;
+                while x < width {
+                    let len = (width - x).min(SCRATCH_PIXELS as u32) as usize;
+                    let mut count = 0;
+                    for (slot, color) in scratch[..len].iter_mut().zip(colors.by_ref()) {
+                        *slot = color;
+                        count += 1;
+                    }
+                    if count == 0 {
+                        return Ok(());
+                    }
+
+                    let span = mirror_x(&row_span(area, row, x, count as u32), display_width);
+                    let reversed = scratch[..count].iter().rev().copied();
+                    self.target.fill_contiguous(&span, reversed)?;
+                    x += len as u32;
+                }
+            }
+            Ok(())
+        }
+
+        fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+            let area = mirror_x(area, self.bounding_box().size.width);
             self.target.fill_solid(&area, color)
         }
 
@@ -512,16 +936,42 @@ mod r#impl {
                     .map(|Pixel(Point { x, y }, col)| Pixel(Point { x, y: height - y }, col)),
             )
         }
+        /// Rows keep their order, they just go out bottom to top: narrow
+        /// areas in bands of rows flipped over, wide ones a row at a time
+        /// straight from the iterator.
+        fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Self::Color>,
+        {
+            if area.is_zero_sized() {
+                return Ok(());
+            }
+            let display_height = self.bounding_box().size.height;
+
+            let band = band_rows(area);
+            if band > 1 {
+                return fill_bands(self, area, band, colors, |this, part, scratch| {
+                    let rows = scratch.chunks_exact(part.size.width as usize);
+                    let flipped = rows.rev().flatten().copied();
+                    this.target
+                        .fill_contiguous(&mirror_y(part, display_height), flipped)
+                });
+            }
+
+            let mut colors = colors.into_iter().peekable();
+            for row in 0..area.size.height {
+                if colors.peek().is_none() {
+                    break;
+                }
+                let span = mirror_y(&row_span(area, row, 0, area.size.width), display_height);
+                let row = colors.by_ref().take(area.size.width as usize);
+                self.target.fill_contiguous(&span, row)?;
+            }
+            Ok(())
+        }
 
         fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
-            let height = self.bounding_box().size.height as i32 - 1;
-            let area = Rectangle {
-                top_left: Point {
-                    x: area.top_left.x,
-                    y: height - area.top_left.y - area.size.height as i32,
-                },
-                size: area.size,
-            };
+            let area = mirror_y(area, self.bounding_box().size.height);
             self.target.fill_solid(&area, color)
         }
 
@@ -0,0 +1,233 @@
+//! Integer scaling, every pixel drawn as a square block of display pixels
+//!
+//! Handy for big digits out of an ordinary mono font. [`Scale`] has the
+//! factor in its type, [`ScaleBy`] takes it at runtime. Both draw a pixel at
+//! `(x, y)` as the `N`×`N` block with its top left corner at `(N * x, N * y)`,
+//! so to scale up a part of the display put them around a
+//! [`Viewport`](crate::Viewport).
+
+use core::iter::repeat_n;
+use core::ops::{Deref, DerefMut};
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+use crate::r#impl::{band_rows, fill_bands, SCRATCH_PIXELS};
+
+/// Scale by `N` in both directions.
+pub struct Scale<const N: u32, D> {
+    target: D,
+}
+
+impl<const N: u32, D> Scale<N, D> {
+    /// Draw on `target` `N` times as big.
+    pub fn new(target: D) -> Self {
+        const { assert!(N > 0, "can't scale by 0") };
+        Scale { target }
+    }
+
+    /// How many display pixels across and down a pixel takes up.
+    pub const fn factor(&self) -> u32 {
+        N
+    }
+
+    /// Recover the inner display instance.
+    pub fn into_inner(self) -> D {
+        self.target
+    }
+}
+
+/// Scale by a factor chosen at runtime.
+///
+/// The same as [`Scale`], with the factor in a field rather than the type.
+pub struct ScaleBy<D> {
+    target: D,
+    factor: u32,
+}
+
+impl<D> ScaleBy<D> {
+    /// Draw on `target` `factor` times as big.
+    ///
+    /// # Panics
+    ///
+    /// If `factor` is 0.
+    pub fn new(factor: u32, target: D) -> Self {
+        assert!(factor > 0, "can't scale by 0");
+        ScaleBy { target, factor }
+    }
+
+    /// How many display pixels across and down a pixel takes up.
+    pub fn factor(&self) -> u32 {
+        self.factor
+    }
+
+    /// Recover the inner display instance.
+    pub fn into_inner(self) -> D {
+        self.target
+    }
+}
+
+/// `area` of the display in scaled pixels, only the ones that fit entirely
+fn scale_down(area: Rectangle, factor: u32) -> Rectangle {
+    let factor = factor as i32;
+    let top_left = Point::new(
+        area.top_left.x.div_euclid(factor) + (area.top_left.x.rem_euclid(factor) != 0) as i32,
+        area.top_left.y.div_euclid(factor) + (area.top_left.y.rem_euclid(factor) != 0) as i32,
+    );
+    let bottom_right = (area.top_left + area.size).component_div(Point::new(factor, factor));
+    let size = bottom_right - top_left;
+    Rectangle::new(
+        top_left,
+        Size::new(size.x.max(0) as u32, size.y.max(0) as u32),
+    )
+}
+
+/// `area` in scaled pixels as display pixels
+fn scale_up(area: &Rectangle, factor: u32) -> Rectangle {
+    Rectangle::new(area.top_left * factor as i32, area.size * factor)
+}
+
+/// The scaling itself, for either wrapper
+struct Scaler<'a, D> {
+    target: &'a mut D,
+    factor: u32,
+}
+
+impl<D: Dimensions> Dimensions for Scaler<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        scale_down(self.target.bounding_box(), self.factor)
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Scaler<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let factor = self.factor;
+        self.target
+            .draw_iter(pixels.into_iter().flat_map(|Pixel(point, color)| {
+                let block = scale_up(&Rectangle::new(point, Size::new(1, 1)), factor);
+                block.points().map(move |point| Pixel(point, color))
+            }))
+    }
+
+    /// Every row of `area` goes out `factor` times, each colour in it
+    /// `factor` times over, a band of rows at a time.
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let factor = self.factor;
+        if factor == 1 {
+            return self.target.fill_contiguous(area, colors);
+        }
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        if area.size.width as usize > SCRATCH_PIXELS {
+            let pixels = area.points().zip(colors);
+            return self.draw_iter(pixels.map(|(point, color)| Pixel(point, color)));
+        }
+
+        let band = band_rows(area);
+        fill_bands(self, area, band, colors, |this, part, scratch| {
+            let n = factor as usize;
+            let rows = scratch.chunks_exact(part.size.width as usize);
+            let colors = rows
+                .flat_map(|row| repeat_n(row, n))
+                .flat_map(|row| row.iter().flat_map(|&color| repeat_n(color, n)));
+            this.target.fill_contiguous(&scale_up(part, factor), colors)
+        })
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.fill_solid(&scale_up(area, self.factor), color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.clear(color)
+    }
+}
+
+macro_rules! impl_scale {
+    ([$($generics:tt)*] $name:ty) => {
+        impl<$($generics)*> Deref for $name {
+            type Target = D;
+
+            fn deref(&self) -> &D {
+                &self.target
+            }
+        }
+
+        impl<$($generics)*> DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut D {
+                &mut self.target
+            }
+        }
+
+        impl<$($generics)*> AsRef<D> for $name {
+            fn as_ref(&self) -> &D {
+                &self.target
+            }
+        }
+
+        impl<$($generics)*> AsMut<D> for $name {
+            fn as_mut(&mut self) -> &mut D {
+                &mut self.target
+            }
+        }
+
+        impl<$($generics)*> Dimensions for $name
+        where
+            D: Dimensions,
+        {
+            fn bounding_box(&self) -> Rectangle {
+                scale_down(self.target.bounding_box(), self.factor())
+            }
+        }
+
+        impl<$($generics)*> DrawTarget for $name
+        where
+            D: DrawTarget,
+        {
+            type Color = D::Color;
+            type Error = D::Error;
+
+            fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+            where
+                I: IntoIterator<Item = Pixel<Self::Color>>,
+            {
+                self.scaler().draw_iter(pixels)
+            }
+
+            fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+            where
+                I: IntoIterator<Item = Self::Color>,
+            {
+                self.scaler().fill_contiguous(area, colors)
+            }
+
+            fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+                self.scaler().fill_solid(area, color)
+            }
+
+            fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+                self.scaler().clear(color)
+            }
+        }
+
+        impl<$($generics)*> $name {
+            fn scaler(&mut self) -> Scaler<'_, D> {
+                Scaler {
+                    factor: self.factor(),
+                    target: &mut self.target,
+                }
+            }
+        }
+    };
+}
+
+impl_scale!([const N: u32, D] Scale<N, D>);
+impl_scale!([D] ScaleBy<D>);
@@ -1,5 +1,16 @@
+extern crate std;
+
 use super::*;
-use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor, primitives::Triangle};
+use embedded_graphics::{
+    mock_display::MockDisplay,
+    pixelcolor::{BinaryColor, Rgb565},
+    primitives::Triangle,
+};
+use std::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 
 fn triangle() -> impl PointsIter {
     let tri = Triangle::new(Point::new(0, 10), Point::new(0, 0), Point::new(20, 0));
@@ -295,7 +306,7 @@ fn solid_flipx() {
     assert_eq!(
         disp.as_ref().affected_area(),
         Rectangle {
-            top_left: Point::new(64 - 1 - 1 - 5, 1),
+            top_left: Point::new(64 - 1 - 5, 1),
             size: Size {
                 width: 5,
                 height: 10
@@ -323,7 +334,7 @@ fn solid_flipy() {
     assert_eq!(
         disp.as_ref().affected_area(),
         Rectangle {
-            top_left: Point::new(1, 64 - 1 - 1 - 10),
+            top_left: Point::new(1, 64 - 1 - 10),
             size: Size {
                 width: 5,
                 height: 10
@@ -351,7 +362,7 @@ fn solid_rot90() {
     assert_eq!(
         disp.as_ref().affected_area(),
         Rectangle {
-            top_left: Point::new(64 - 1 - 1 - 10, 1),
+            top_left: Point::new(64 - 1 - 10, 1),
             size: Size {
                 width: 10,
                 height: 5
@@ -379,7 +390,7 @@ fn solid_rot180() {
     assert_eq!(
         disp.as_ref().affected_area(),
         Rectangle {
-            top_left: Point::new(64 - 1 - 1 - 5, 64 - 1 - 1 - 10),
+            top_left: Point::new(64 - 1 - 5, 64 - 1 - 10),
             size: Size {
                 width: 5,
                 height: 10
@@ -407,7 +418,7 @@ fn solid_rot270() {
     assert_eq!(
         disp.as_ref().affected_area(),
         Rectangle {
-            top_left: Point::new(1, 64 - 1 - 1 - 5),
+            top_left: Point::new(1, 64 - 1 - 5),
             size: Size {
                 width: 10,
                 height: 5
@@ -438,3 +449,486 @@ fn rect_transpose() {
 
     assert_eq!(lower_right(&rx), Point::new(110, 25));
 }
+
+/// Colour that is different for every pixel of a 64x64 display
+fn pattern(i: usize) -> Rgb565 {
+    Rgb565::new((i % 32) as u8, (i / 32 % 64) as u8, (i / 2048) as u8)
+}
+
+/// Draw `area` with `fill_contiguous` and pixel by pixel, with `len` colours,
+/// and check both come out the same
+fn assert_fill_contiguous_matches<T>(
+    wrap: fn(MockDisplay<Rgb565>) -> T,
+    area: Rectangle,
+    len: usize,
+) where
+    T: DrawTarget<Color = Rgb565, Error = core::convert::Infallible> + AsRef<MockDisplay<Rgb565>>,
+{
+    let display = || {
+        let mut display = MockDisplay::new();
+        display.set_allow_out_of_bounds_drawing(true);
+        wrap(display)
+    };
+
+    let mut fast = display();
+    fast.fill_contiguous(&area, (0..len).map(pattern)).unwrap();
+
+    let mut generic = display();
+    generic
+        .draw_iter(
+            area.points()
+                .zip((0..len).map(pattern))
+                .map(|(pt, c)| Pixel(pt, c)),
+        )
+        .unwrap();
+
+    fast.as_ref().assert_eq(generic.as_ref());
+}
+
+fn check_fill_contiguous<T>(wrap: fn(MockDisplay<Rgb565>) -> T)
+where
+    T: DrawTarget<Color = Rgb565, Error = core::convert::Infallible> + AsRef<MockDisplay<Rgb565>>,
+{
+    let areas = [
+        Rectangle::new(Point::new(3, 5), Size::new(7, 11)),
+        Rectangle::new(Point::new(0, 0), Size::new(64, 64)),
+        // narrow and tall, several bands through the scratch buffer
+        Rectangle::new(Point::new(10, 2), Size::new(3, 60)),
+        // rows only just fitting in the scratch buffer, and longer than it
+        Rectangle::new(Point::new(-50, 20), Size::new(200, 3)),
+        Rectangle::new(Point::new(-100, 7), Size::new(300, 2)),
+        // partly off the display
+        Rectangle::new(Point::new(-4, 50), Size::new(20, 30)),
+        Rectangle::new(Point::new(5, 5), Size::new(0, 10)),
+    ];
+    for area in areas {
+        let pixels = area.size.width as usize * area.size.height as usize;
+        for len in [
+            pixels,
+            pixels / 2,
+            pixels.saturating_sub(1),
+            pixels / 3 + 1,
+            0,
+        ] {
+            assert_fill_contiguous_matches(wrap, area, len);
+        }
+    }
+}
+
+#[test]
+fn fill_contiguous_matches_draw_iter() {
+    check_fill_contiguous(Rotate0::new);
+    check_fill_contiguous(Rotate90::new);
+    check_fill_contiguous(Rotate180::new);
+    check_fill_contiguous(Rotate270::new);
+    check_fill_contiguous(Transpose::new);
+    check_fill_contiguous(FlipX::new);
+    check_fill_contiguous(FlipY::new);
+    check_fill_contiguous(AntiTranspose::new);
+}
+
+#[test]
+fn runtime_fill_contiguous_matches_draw_iter() {
+    check_fill_contiguous(|d| Rotate::new(Rotation::Rotate0, d));
+    check_fill_contiguous(|d| Rotate::new(Rotation::Rotate90, d));
+    check_fill_contiguous(|d| Rotate::new(Rotation::Rotate180, d));
+    check_fill_contiguous(|d| Rotate::new(Rotation::Rotate270, d));
+    check_fill_contiguous(|d| Orient::new(Orientation::FlipX, d));
+    check_fill_contiguous(|d| Orient::new(Orientation::AntiTranspose, d));
+}
+
+#[test]
+fn fill_solid_matches_draw_iter() {
+    fn check<T>(wrap: fn(MockDisplay<BinaryColor>) -> T)
+    where
+        T: DrawTarget<Color = BinaryColor, Error = core::convert::Infallible>
+            + AsRef<MockDisplay<BinaryColor>>,
+    {
+        let area = Rectangle::new(Point::new(2, 3), Size::new(9, 4));
+        let mut fast = wrap(MockDisplay::new());
+        fast.fill_solid(&area, BinaryColor::On).unwrap();
+        let mut generic = wrap(MockDisplay::new());
+        generic
+            .draw_iter(area.points().map(|pt| Pixel(pt, BinaryColor::On)))
+            .unwrap();
+        fast.as_ref().assert_eq(generic.as_ref());
+    }
+
+    check(Rotate90::new);
+    check(Rotate180::new);
+    check(Rotate270::new);
+    check(Transpose::new);
+    check(FlipX::new);
+    check(FlipY::new);
+    check(AntiTranspose::new);
+}
+
+/// An F, which looks different in every orientation
+const GLYPH: [&str; 4] = ["###", "#  ", "## ", "#  "];
+
+fn glyph_colors() -> impl Iterator<Item = BinaryColor> {
+    GLYPH
+        .into_iter()
+        .flat_map(|row| row.chars())
+        .map(|c| BinaryColor::from(c == '#'))
+}
+
+fn glyph_area() -> Rectangle {
+    Rectangle::new(Point::zero(), Size::new(3, 4))
+}
+
+/// Where the drawing ended up and what it looks like
+fn drawn(display: &MockDisplay<BinaryColor>) -> (Point, Vec<String>) {
+    let area = display.affected_area();
+    let rows = area
+        .rows()
+        .map(|y| {
+            area.columns()
+                .map(|x| match display.get_pixel(Point::new(x, y)) {
+                    Some(BinaryColor::On) => '#',
+                    _ => ' ',
+                })
+                .collect()
+        })
+        .collect();
+    (area.top_left, rows)
+}
+
+#[test]
+fn orientation_matrix() {
+    let matrix: [(Orientation, Point, &[&str]); 8] = [
+        (Orientation::Rotate0, Point::new(0, 0), &GLYPH),
+        (
+            Orientation::Rotate90,
+            Point::new(60, 0),
+            &["####", " # #", "   #"],
+        ),
+        (
+            Orientation::Rotate180,
+            Point::new(61, 60),
+            &["  #", " ##", "  #", "###"],
+        ),
+        (
+            Orientation::Rotate270,
+            Point::new(0, 61),
+            &["#   ", "# # ", "####"],
+        ),
+        (
+            Orientation::FlipX,
+            Point::new(61, 0),
+            &["###", "  #", " ##", "  #"],
+        ),
+        (
+            Orientation::AntiTranspose,
+            Point::new(60, 61),
+            &["   #", " # #", "####"],
+        ),
+        (
+            Orientation::FlipY,
+            Point::new(0, 60),
+            &["#  ", "## ", "#  ", "###"],
+        ),
+        (
+            Orientation::Transpose,
+            Point::new(0, 0),
+            &["####", "# # ", "#   "],
+        ),
+    ];
+
+    for (orientation, top_left, pattern) in matrix {
+        let mut disp = Orient::new(orientation, MockDisplay::new());
+        disp.draw_iter(
+            glyph_area()
+                .points()
+                .zip(glyph_colors())
+                .filter(|(_, c)| c.is_on())
+                .map(|(pt, c)| Pixel(pt, c)),
+        )
+        .unwrap();
+        let expected = (
+            top_left,
+            pattern.iter().map(|row| row.to_string()).collect(),
+        );
+        assert_eq!(drawn(disp.as_ref()), expected, "{orientation:?}");
+
+        // the area fills land in the same place
+        let mut filled = Orient::new(orientation, MockDisplay::new());
+        filled
+            .fill_contiguous(&glyph_area(), glyph_colors())
+            .unwrap();
+        assert_eq!(
+            drawn(filled.as_ref()),
+            expected,
+            "{orientation:?} fill_contiguous"
+        );
+    }
+}
+
+#[test]
+fn orientation_parts() {
+    for rotation in [
+        Rotation::Rotate0,
+        Rotation::Rotate90,
+        Rotation::Rotate180,
+        Rotation::Rotate270,
+    ] {
+        let plain = Orientation::from(rotation);
+        let mirrored = Orientation::new(rotation, true);
+        assert_eq!((plain.rotation(), plain.is_mirrored()), (rotation, false));
+        assert_eq!(
+            (mirrored.rotation(), mirrored.is_mirrored()),
+            (rotation, true)
+        );
+        // mirrored means flipped first, then rotated
+        assert_eq!(Orientation::FlipX.then(plain), mirrored);
+    }
+    assert!(Orientation::Rotate270.is_transposing());
+    assert!(Orientation::AntiTranspose.is_transposing());
+    assert!(!Orientation::FlipY.is_transposing());
+}
+
+#[test]
+fn orientation_then_matches_nesting() {
+    for first in Orientation::ALL {
+        for next in Orientation::ALL {
+            let mut nested = Orient::new(first, Orient::new(next, MockDisplay::new()));
+            nested
+                .fill_contiguous(&glyph_area(), glyph_colors())
+                .unwrap();
+
+            let mut combined = Orient::new(first.then(next), MockDisplay::new());
+            combined
+                .fill_contiguous(&glyph_area(), glyph_colors())
+                .unwrap();
+
+            assert_eq!(
+                drawn(nested.as_ref()),
+                drawn(combined.as_ref()),
+                "{first:?} then {next:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn orientation_inverse_undoes() {
+    for orientation in Orientation::ALL {
+        let inverse = orientation.inverse();
+        assert_eq!(orientation.then(inverse), Orientation::Rotate0);
+        assert_eq!(inverse.then(orientation), Orientation::Rotate0);
+
+        let mut disp = Orient::new(orientation, Orient::new(inverse, MockDisplay::new()));
+        disp.fill_contiguous(&glyph_area(), glyph_colors()).unwrap();
+        assert_eq!(
+            drawn(disp.as_ref()),
+            (Point::zero(), GLYPH.map(String::from).to_vec()),
+            "{orientation:?}"
+        );
+    }
+}
+
+#[test]
+fn viewport_draws_in_local_coordinates() {
+    let mut disp = MockDisplay::new();
+    disp.set_allow_overdraw(true);
+    let area = Rectangle::new(Point::new(10, 5), Size::new(6, 3));
+    let mut view = disp.viewport(area);
+    assert_eq!(
+        view.bounding_box(),
+        Rectangle::new(Point::zero(), area.size)
+    );
+
+    // mostly outside, only the viewport gets filled
+    view.fill_solid(
+        &Rectangle::new(Point::new(-4, -4), Size::new(20, 20)),
+        BinaryColor::On,
+    )
+    .unwrap();
+    Pixel(Point::new(1, 1), BinaryColor::Off)
+        .draw(&mut view)
+        .unwrap();
+    Pixel(Point::new(6, 1), BinaryColor::Off)
+        .draw(&mut view)
+        .unwrap();
+
+    assert_eq!(
+        drawn(&disp),
+        (
+            area.top_left,
+            ["######", "# ####", "######"].map(String::from).to_vec()
+        )
+    );
+}
+
+#[test]
+fn viewport_clear_stays_inside() {
+    let mut disp = MockDisplay::new();
+    let area = Rectangle::new(Point::new(60, 62), Size::new(8, 8));
+    disp.viewport(area).clear(BinaryColor::On).unwrap();
+
+    // clipped to the display as well
+    assert_eq!(
+        disp.affected_area(),
+        Rectangle::new(area.top_left, Size::new(4, 2))
+    );
+}
+
+#[test]
+fn nested_viewports() {
+    let mut disp = Orient::new(Orientation::FlipX, MockDisplay::new());
+    let mut outer = disp.viewport(Rectangle::new(Point::new(4, 4), Size::new(10, 10)));
+    let mut inner = outer.viewport(Rectangle::new(Point::new(2, 3), Size::new(20, 2)));
+    inner
+        .fill_contiguous(&glyph_area(), glyph_colors())
+        .unwrap();
+
+    // the inner viewport is cut down to the outer one: 2 rows from (6, 7),
+    // mirrored on the display
+    assert_eq!(
+        drawn(disp.as_ref()),
+        (
+            Point::new(64 - 6 - 3, 7),
+            vec!["###".to_string(), "  #".to_string()]
+        )
+    );
+}
+
+#[test]
+fn clip_fill_contiguous_matches_draw_iter() {
+    let clip_area = Rectangle::new(Point::new(5, 7), Size::new(9, 6));
+    let areas = [
+        Rectangle::new(Point::new(0, 0), Size::new(20, 20)),
+        Rectangle::new(Point::new(6, 8), Size::new(3, 2)),
+        Rectangle::new(Point::new(10, 2), Size::new(10, 8)),
+        Rectangle::new(Point::new(30, 30), Size::new(4, 4)),
+    ];
+    for area in areas {
+        let pixels = (area.size.width * area.size.height) as usize;
+        for len in [pixels, pixels / 2] {
+            let mut fast = MockDisplay::new();
+            fast.clip(clip_area)
+                .fill_contiguous(&area, (0..len).map(pattern))
+                .unwrap();
+
+            let mut generic = MockDisplay::new();
+            generic
+                .clip(clip_area)
+                .draw_iter(
+                    area.points()
+                        .zip((0..len).map(pattern))
+                        .map(|(pt, c)| Pixel(pt, c)),
+                )
+                .unwrap();
+
+            fast.assert_eq(&generic);
+        }
+    }
+}
+
+#[test]
+fn translate_moves_origin() {
+    let mut disp = MockDisplay::new();
+    let mut moved = disp.translate(Point::new(3, -2));
+    assert_eq!(moved.bounding_box().top_left, Point::new(-3, 2));
+
+    moved
+        .fill_contiguous(
+            &Rectangle::new(Point::new(0, 2), Size::new(3, 4)),
+            glyph_colors(),
+        )
+        .unwrap();
+    assert_eq!(
+        drawn(&disp),
+        (Point::new(3, 0), GLYPH.map(String::from).to_vec())
+    );
+}
+
+#[test]
+fn scale_blows_up_pixels() {
+    let mut disp = Scale::<2, _>::new(MockDisplay::new());
+    assert_eq!(disp.bounding_box().size, Size::new(32, 32));
+
+    disp.draw_iter(
+        glyph_area()
+            .points()
+            .zip(glyph_colors())
+            .filter(|(_, c)| c.is_on())
+            .map(|(pt, c)| Pixel(pt, c)),
+    )
+    .unwrap();
+
+    assert_eq!(
+        drawn(disp.as_ref()),
+        (
+            Point::zero(),
+            ["######", "######", "##    ", "##    ", "####  ", "####  ", "##    ", "##    "]
+                .map(String::from)
+                .to_vec()
+        )
+    );
+}
+
+#[test]
+fn scale_fill_solid() {
+    let mut disp = ScaleBy::new(3, MockDisplay::new());
+    disp.fill_solid(
+        &Rectangle::new(Point::new(2, 1), Size::new(2, 3)),
+        BinaryColor::On,
+    )
+    .unwrap();
+
+    assert_eq!(
+        disp.as_ref().affected_area(),
+        Rectangle::new(Point::new(6, 3), Size::new(6, 9))
+    );
+}
+
+#[test]
+fn scale_fill_contiguous_matches_draw_iter() {
+    check_fill_contiguous(Scale::<1, _>::new);
+    check_fill_contiguous(Scale::<2, _>::new);
+    check_fill_contiguous(Scale::<3, _>::new);
+    check_fill_contiguous(|d| ScaleBy::new(5, d));
+}
+
+#[test]
+fn scale_bounding_box_keeps_whole_pixels() {
+    let mut disp = MockDisplay::<BinaryColor>::new();
+    let view = disp.translate(Point::new(-4, 2));
+    // the display goes from (4, -2) to (67, 61) in the translated coordinates
+    let scaled = ScaleBy::new(3, view);
+    assert_eq!(
+        scaled.bounding_box(),
+        Rectangle::new(Point::new(2, 0), Size::new(20, 20))
+    );
+}
+
+#[test]
+fn scaled_text_matches_plain() {
+    use embedded_graphics::{
+        mono_font::{ascii::FONT_8X13_BOLD, MonoTextStyle},
+        text::{Baseline, Text},
+        Drawable,
+    };
+
+    let text = Text::with_baseline(
+        "27",
+        Point::zero(),
+        MonoTextStyle::new(&FONT_8X13_BOLD, BinaryColor::On),
+        Baseline::Top,
+    );
+    let mut plain = MockDisplay::new();
+    text.draw(&mut plain).unwrap();
+    let mut scaled = Scale::<3, _>::new(MockDisplay::new());
+    text.draw(&mut scaled).unwrap();
+
+    let scaled = scaled.into_inner();
+    assert_eq!(scaled.affected_area().size, plain.affected_area().size * 3);
+    for point in scaled.affected_area().points() {
+        assert_eq!(
+            scaled.get_pixel(point),
+            plain.get_pixel(point / 3),
+            "{point:?}"
+        );
+    }
+}
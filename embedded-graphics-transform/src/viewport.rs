@@ -0,0 +1,288 @@
+//! Drawing into part of a display
+//!
+//! Unlike the orientation transforms these borrow the display rather than
+//! owning it, so they can be made for one widget and dropped again:
+//! - [`Translate`] moves the origin, without limiting where drawing goes
+//! - [`Clip`] drops everything outside a rectangle, coordinates stay as they are
+//! - [`Viewport`] does both, a rectangle of the display becomes a display of
+//!   its own with its origin in the top left corner
+//!
+//! They nest and combine with the other transforms, e.g. a viewport of an
+//! [`Orient`](crate::Orient) or a viewport inside another viewport.
+
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+/// Create [`Translate`], [`Clip`] and [`Viewport`] wrappers from a display.
+pub trait ViewportExt: DrawTarget + Sized {
+    /// Draw with the origin moved to `offset`.
+    fn translate(&mut self, offset: Point) -> Translate<'_, Self>;
+
+    /// Only draw inside `area`.
+    fn clip(&mut self, area: Rectangle) -> Clip<'_, Self>;
+
+    /// Draw into `area` as if it was a display of its own.
+    fn viewport(&mut self, area: Rectangle) -> Viewport<'_, Self>;
+}
+
+impl<D: DrawTarget> ViewportExt for D {
+    fn translate(&mut self, offset: Point) -> Translate<'_, Self> {
+        Translate::new(self, offset)
+    }
+
+    fn clip(&mut self, area: Rectangle) -> Clip<'_, Self> {
+        Clip::new(self, area)
+    }
+
+    fn viewport(&mut self, area: Rectangle) -> Viewport<'_, Self> {
+        Viewport::new(self, area)
+    }
+}
+
+/// Move the origin of a display.
+///
+/// A point `p` is drawn at `p + offset` on the display. Nothing is clipped,
+/// and [`clear`](DrawTarget::clear) still clears the whole display.
+pub struct Translate<'a, D> {
+    target: &'a mut D,
+    offset: Point,
+}
+
+impl<'a, D> Translate<'a, D> {
+    /// Draw on `target` with the origin at `offset`.
+    pub fn new(target: &'a mut D, offset: Point) -> Self {
+        Translate { target, offset }
+    }
+
+    /// Where the origin is on the display.
+    pub fn offset(&self) -> Point {
+        self.offset
+    }
+}
+
+impl<D> AsRef<D> for Translate<'_, D> {
+    fn as_ref(&self) -> &D {
+        self.target
+    }
+}
+
+impl<D> AsMut<D> for Translate<'_, D> {
+    fn as_mut(&mut self) -> &mut D {
+        self.target
+    }
+}
+
+impl<D: Dimensions> Dimensions for Translate<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        let bounds = self.target.bounding_box();
+        Rectangle::new(bounds.top_left - self.offset, bounds.size)
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Translate<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let offset = self.offset;
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point + offset, color)),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let area = Rectangle::new(area.top_left + self.offset, area.size);
+        self.target.fill_contiguous(&area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = Rectangle::new(area.top_left + self.offset, area.size);
+        self.target.fill_solid(&area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.clear(color)
+    }
+}
+
+/// Limit drawing to a rectangle of a display.
+///
+/// Coordinates are those of the display, anything outside of the rectangle is
+/// dropped. [`clear`](DrawTarget::clear) only clears the rectangle.
+pub struct Clip<'a, D> {
+    target: &'a mut D,
+    area: Rectangle,
+}
+
+impl<'a, D: Dimensions> Clip<'a, D> {
+    /// Only draw on the part of `target` inside `area`.
+    pub fn new(target: &'a mut D, area: Rectangle) -> Self {
+        let area = area.intersection(&target.bounding_box());
+        Clip { target, area }
+    }
+}
+
+impl<D> AsRef<D> for Clip<'_, D> {
+    fn as_ref(&self) -> &D {
+        self.target
+    }
+}
+
+impl<D> AsMut<D> for Clip<'_, D> {
+    fn as_mut(&mut self) -> &mut D {
+        self.target
+    }
+}
+
+impl<D> Dimensions for Clip<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Clip<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let area = self.area;
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .filter(|Pixel(point, _)| area.contains(*point)),
+        )
+    }
+
+    /// Sends just the visible part of `area` on, as a single area.
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let visible = area.intersection(&self.area);
+        if visible == *area {
+            return self.target.fill_contiguous(area, colors);
+        }
+        if visible.is_zero_sized() {
+            return Ok(());
+        }
+
+        let width = area.size.width as usize;
+        let skip = visible.top_left - area.top_left;
+        let columns = skip.x as usize..skip.x as usize + visible.size.width as usize;
+        let colors = colors
+            .into_iter()
+            .skip(skip.y as usize * width)
+            .take(visible.size.height as usize * width)
+            .enumerate()
+            .filter(move |(i, _)| columns.contains(&(i % width)))
+            .map(|(_, color)| color);
+        self.target.fill_contiguous(&visible, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.area);
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        self.target.fill_solid(&area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.area;
+        self.fill_solid(&area, color)
+    }
+}
+
+/// A rectangle of a display, used as a display of its own.
+///
+/// The viewport's origin is the top left corner of the rectangle and its
+/// [`bounding_box`](Dimensions::bounding_box) is the rectangle's size, so
+/// whatever is drawn into it can use local coordinates and
+/// [`clear`](DrawTarget::clear). Nothing is drawn outside of the rectangle.
+pub struct Viewport<'a, D> {
+    clip: Clip<'a, D>,
+    area: Rectangle,
+}
+
+impl<'a, D: Dimensions> Viewport<'a, D> {
+    /// Make `area` of `target` into a display.
+    pub fn new(target: &'a mut D, area: Rectangle) -> Self {
+        Viewport {
+            clip: Clip::new(target, area),
+            area,
+        }
+    }
+}
+
+impl<D> Viewport<'_, D> {
+    /// Where the viewport is on the display.
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    fn to_display(&self, area: &Rectangle) -> Rectangle {
+        Rectangle::new(area.top_left + self.area.top_left, area.size)
+    }
+}
+
+impl<D> AsRef<D> for Viewport<'_, D> {
+    fn as_ref(&self) -> &D {
+        self.clip.as_ref()
+    }
+}
+
+impl<D> AsMut<D> for Viewport<'_, D> {
+    fn as_mut(&mut self) -> &mut D {
+        self.clip.as_mut()
+    }
+}
+
+impl<D> Dimensions for Viewport<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), self.area.size)
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Viewport<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let offset = self.area.top_left;
+        self.clip.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point + offset, color)),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let area = self.to_display(area);
+        self.clip.fill_contiguous(&area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.to_display(area);
+        self.clip.fill_solid(&area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.clip.clear(color)
+    }
+}
@@ -0,0 +1,19 @@
+//! Packs `../assets` and converts `../assets/fonts` the same way the
+//! firmware build does, so the included `vfdgraphics` and `fonts` modules
+//! find their images and fonts.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+#[path = "../build/assetgen.rs"]
+mod assetgen;
+#[path = "../build/fontgen.rs"]
+mod fontgen;
+
+fn main() {
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    assetgen::generate(Path::new("../assets"), &out);
+    println!("cargo:rerun-if-changed=../build/assetgen.rs");
+    fontgen::generate(Path::new("../assets/fonts"), &out);
+    println!("cargo:rerun-if-changed=../build/fontgen.rs");
+}
@@ -0,0 +1,54 @@
+//! Render the boot animation and every page transition frame by frame into
+//! `target/frames/`, to look through in an image viewer:
+//!
+//! ```sh
+//! cargo run --example export_frames --target x86_64-unknown-linux-gnu
+//! ```
+//!
+//! The transitions go from the climate page's background to the boot logo.
+//! Brightness isn't shown in the images, a fade's levels are printed instead.
+
+use z31_hvac_host_tests::animation::{Scene, Transition};
+use z31_hvac_host_tests::frames::{export, sequence_frames, transition_frames};
+use z31_hvac_host_tests::vfdgraphics::{BinaryTarget, Graphics, boot};
+
+struct Background<'a>(&'a Graphics);
+
+impl Scene for Background<'_> {
+    fn draw<D: BinaryTarget>(&self, display: &mut D) {
+        self.0.draw_background(display);
+    }
+}
+
+struct Logo<'a>(&'a Graphics);
+
+impl Scene for Logo<'_> {
+    fn draw<D: BinaryTarget>(&self, display: &mut D) {
+        self.0.draw_boot_image(display);
+    }
+}
+
+fn main() {
+    let dir = export("boot", sequence_frames(&boot::SEQUENCE));
+    println!("boot: {}", dir.display());
+
+    let graphics = Graphics::load();
+    let (from, to) = (Background(&graphics), Logo(&graphics));
+    for (name, transition) in [
+        ("slide_left", Transition::SlideLeft),
+        ("slide_right", Transition::SlideRight),
+        ("wipe", Transition::Wipe),
+        ("fade", Transition::Fade),
+    ] {
+        let mut levels = Vec::new();
+        let frames = transition_frames(transition, &from, &to).map(|(at, brightness, target)| {
+            levels.push(format!("{}", brightness.get()));
+            (at, target)
+        });
+        let dir = export(name, frames);
+        println!("{name}: {}", dir.display());
+        if transition == Transition::Fade {
+            println!("  brightness per frame (of 1000): {}", levels.join(" "));
+        }
+    }
+}
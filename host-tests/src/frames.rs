@@ -0,0 +1,51 @@
+//! Rendering animations frame by frame, for tests and for looking at.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::animation::{FRAME_TIME, Progress, Scene, Sequence, Transition, draw_transition};
+use crate::snapshot::{Target, new_target, to_pbm};
+
+/// Every frame of `sequence` the display would draw with no frames dropped,
+/// with its time
+pub fn sequence_frames(sequence: &Sequence) -> impl Iterator<Item = (u32, Target)> + '_ {
+    (0..=sequence.length)
+        .step_by(FRAME_TIME as usize)
+        .map(|at| {
+            let mut target = new_target();
+            sequence.draw(at, &mut target);
+            (at, target)
+        })
+}
+
+/// Every frame of `transition` from `from` to `to`, with its time and the
+/// share of the brightness it's shown at
+pub fn transition_frames<'a, F: Scene, T: Scene>(
+    transition: Transition,
+    from: &'a F,
+    to: &'a T,
+) -> impl Iterator<Item = (u32, Progress, Target)> + 'a {
+    let duration = transition.duration();
+    (0..=duration).step_by(FRAME_TIME as usize).map(move |at| {
+        let mut target = new_target();
+        let progress = Progress::through(at, duration);
+        let brightness = draw_transition(transition, progress, from, to, &mut target);
+        (at, brightness, target)
+    })
+}
+
+/// Write `frames` as PBM images into `target/frames/<name>/`, named by
+/// their time, and return the directory
+pub fn export(name: &str, frames: impl IntoIterator<Item = (u32, Target)>) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../target/frames")
+        .join(name);
+    // don't leave frames from a longer run behind
+    _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("failed to create frame dir");
+    for (at, target) in frames {
+        fs::write(dir.join(format!("{at:05}ms.pbm")), to_pbm(&target))
+            .expect("failed to write frame");
+    }
+    dir
+}
@@ -0,0 +1,38 @@
+//! Host side harness for the hardware independent parts of `z31_hvac`.
+//!
+//! The modules below are the firmware's own source files, included by path so
+//! the tests exercise exactly the code that ships.
+
+extern crate alloc;
+
+#[path = "../../src/animation.rs"]
+pub mod animation;
+#[path = "../../src/brightness.rs"]
+pub mod brightness;
+#[path = "../../src/burnin.rs"]
+pub mod burnin;
+#[path = "../../src/buttons.rs"]
+pub mod buttons;
+#[path = "../../src/climatecontrol.rs"]
+pub mod climatecontrol;
+#[path = "../../src/diagnostics.rs"]
+pub mod diagnostics;
+#[path = "../../src/fonts.rs"]
+pub mod fonts;
+#[path = "../../src/framediff.rs"]
+pub mod framediff;
+#[path = "../../src/power.rs"]
+pub mod power;
+#[path = "../../src/screens.rs"]
+pub mod screens;
+#[path = "../../src/segformat.rs"]
+pub mod segformat;
+#[path = "../../src/settings.rs"]
+pub mod settings;
+#[path = "../../src/vfdgraphics.rs"]
+pub mod vfdgraphics;
+#[path = "../../src/widgets.rs"]
+pub mod widgets;
+
+pub mod frames;
+pub mod snapshot;
@@ -0,0 +1,133 @@
+//! Golden image comparison for VFD renders.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use eei_vfd::gp1287bi::{Display256x50, GRAM_ROWS};
+use eei_vfd::graphics::Display;
+use embedded_graphics::prelude::*;
+use embedded_graphics_transform::{Orient, Orientation};
+
+/// What `vfddisplay::Display` draws into
+pub type Target = Orient<Display256x50>;
+
+/// An empty target, the right way up like `vfddisplay::VFD_ORIENTATION`
+pub fn new_target() -> Target {
+    Orient::new(Orientation::Rotate0, Display256x50::default())
+}
+
+/// Whether the pixel at `point` on the panel is lit, for a target the right
+/// way up like the one from [`new_target`]
+pub fn lit(target: &Target, point: Point) -> bool {
+    let (x, y) = (point.x as usize, point.y as usize);
+    let column_bytes = GRAM_ROWS as usize / 8;
+    target.buffer()[x * column_bytes + y / 8] & (0x80 >> (y % 8)) != 0
+}
+
+/// Encode the target as a binary (P4) PBM, the way the panel shows it
+pub fn to_pbm(target: &Target) -> Vec<u8> {
+    let size = target.bounding_box().size;
+    let mut out = format!("P4\n{} {}\n", size.width, size.height).into_bytes();
+    let row_bytes = size.width.div_ceil(8) as usize;
+    for y in 0..size.height as i32 {
+        let mut row = vec![0u8; row_bytes];
+        for x in 0..size.width as i32 {
+            if lit(target, Point::new(x, y)) {
+                row[x as usize / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        out.extend_from_slice(&row);
+    }
+    out
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("golden")
+        .join(format!("{name}.pbm"))
+}
+
+fn actual_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../target/snapshots")
+        .join(format!("{name}.pbm"))
+}
+
+/// Where two PBM images of the same size differ, as a pixel count and the
+/// bounding box of the differences
+fn describe_diff(expected: &[u8], actual: &[u8]) -> String {
+    let header_len = |pbm: &[u8]| {
+        pbm.iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'\n')
+            .nth(1)
+            .map_or(0, |(i, _)| i + 1)
+    };
+    let (eh, ah) = (header_len(expected), header_len(actual));
+    if expected[..eh] != actual[..ah] {
+        return format!(
+            "image size differs: golden {:?}, rendered {:?}",
+            String::from_utf8_lossy(&expected[..eh]).trim(),
+            String::from_utf8_lossy(&actual[..ah]).trim()
+        );
+    }
+
+    let header = String::from_utf8_lossy(&expected[..eh]);
+    let width: usize = header
+        .split_whitespace()
+        .nth(1)
+        .and_then(|w| w.parse().ok())
+        .unwrap_or(0);
+    let row_bytes = width.div_ceil(8);
+
+    let mut count = 0;
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (usize::MAX, usize::MAX, 0, 0);
+    for (i, (e, a)) in expected[eh..].iter().zip(&actual[ah..]).enumerate() {
+        let diff = e ^ a;
+        for bit in 0..8 {
+            if diff & (0x80 >> bit) != 0 {
+                let (x, y) = ((i % row_bytes) * 8 + bit, i / row_bytes);
+                count += 1;
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+            }
+        }
+    }
+    format!("{count} pixels differ between ({min_x}, {min_y}) and ({max_x}, {max_y})")
+}
+
+/// Compare the render with `golden/<name>.pbm`.
+///
+/// With `UPDATE_GOLDEN` set in the environment the golden image is
+/// (re)written instead.
+pub fn assert_snapshot(name: &str, target: &Target) {
+    let actual = to_pbm(target);
+    let golden = golden_path(name);
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&golden, &actual).expect("failed to write golden image");
+        return;
+    }
+
+    let expected = fs::read(&golden).unwrap_or_else(|_| {
+        panic!(
+            "no golden image at {}, run with UPDATE_GOLDEN=1 to create it",
+            golden.display()
+        )
+    });
+
+    if expected != actual {
+        let out = actual_path(name);
+        fs::create_dir_all(out.parent().unwrap()).expect("failed to create snapshot dir");
+        fs::write(&out, &actual).expect("failed to write rendered image");
+        panic!(
+            "render of {name} doesn't match {}: {}\nrendered image written to {}",
+            golden.display(),
+            describe_diff(&expected, &actual),
+            out.display()
+        );
+    }
+}
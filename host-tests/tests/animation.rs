@@ -0,0 +1,355 @@
+use eei_vfd::graphics::Display as _;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics_transform::ViewportExt;
+use z31_hvac_host_tests::animation::*;
+use z31_hvac_host_tests::frames::{sequence_frames, transition_frames};
+use z31_hvac_host_tests::snapshot::{Target, assert_snapshot, lit, new_target};
+use z31_hvac_host_tests::vfdgraphics::{BinaryTarget, Graphics, PackedImage, assets, boot};
+
+fn progress(per_mille: u16) -> Progress {
+    Progress::per_mille(per_mille)
+}
+
+fn draw(image: &PackedImage) -> Target {
+    let mut target = new_target();
+    _ = image.draw(&mut target);
+    target
+}
+
+fn frame(sequence: &Sequence, at: u32) -> Target {
+    let mut target = new_target();
+    sequence.draw(at, &mut target);
+    target
+}
+
+#[test]
+fn progress_through_time() {
+    assert_eq!(Progress::through(0, 200), Progress::ZERO);
+    assert_eq!(Progress::through(50, 200), progress(250));
+    assert_eq!(Progress::through(200, 200), Progress::FULL);
+    assert_eq!(Progress::through(500, 200), Progress::FULL);
+    // nothing to go through
+    assert_eq!(Progress::through(0, 0), Progress::FULL);
+    assert_eq!(progress(2000), Progress::FULL);
+
+    assert_eq!(progress(250).of(256), 64);
+    assert_eq!(progress(500).of(-40), -20);
+}
+
+#[test]
+fn easing() {
+    for easing in [Easing::Linear, Easing::In, Easing::Out, Easing::InOut] {
+        assert_eq!(Progress::ZERO.ease(easing), Progress::ZERO, "{easing:?}");
+        assert_eq!(Progress::FULL.ease(easing), Progress::FULL, "{easing:?}");
+        let eased: Vec<_> = (0..=1000)
+            .step_by(50)
+            .map(|p| progress(p).ease(easing))
+            .collect();
+        assert!(eased.is_sorted(), "{easing:?} goes backwards");
+    }
+    let half = progress(500);
+    assert_eq!(half.ease(Easing::Linear), half);
+    assert!(half.ease(Easing::In) < half);
+    assert!(half.ease(Easing::Out) > half);
+    assert_eq!(half.ease(Easing::InOut), half);
+    assert!(progress(250).ease(Easing::InOut) < progress(250));
+    assert!(progress(750).ease(Easing::InOut) > progress(750));
+}
+
+#[test]
+fn tracks() {
+    static KEYS: [Keyframe; 3] = [
+        Keyframe { at: 100, value: 10 },
+        Keyframe { at: 200, value: 50 },
+        Keyframe {
+            at: 400,
+            value: -50,
+        },
+    ];
+    let track = Track::new(&KEYS, Easing::Linear);
+    for (t, value) in [
+        (0, 10),
+        (100, 10),
+        (150, 30),
+        (200, 50),
+        (300, 0),
+        (400, -50),
+        (1000, -50),
+    ] {
+        assert_eq!(track.sample(t), value, "at {t}");
+    }
+
+    let eased = Track::new(&KEYS, Easing::In);
+    assert!(eased.sample(150) < 30);
+    assert_eq!(eased.sample(200), 50);
+    assert_eq!(Track::new(&[], Easing::Linear).sample(100), 0);
+}
+
+#[test]
+fn wipe_directions() {
+    let area = Rectangle::new(Point::new(10, 20), Size::new(100, 40));
+    let quarter = progress(250);
+    for (direction, shown) in [
+        (
+            Direction::LeftToRight,
+            Rectangle::new(Point::new(10, 20), Size::new(25, 40)),
+        ),
+        (
+            Direction::RightToLeft,
+            Rectangle::new(Point::new(85, 20), Size::new(25, 40)),
+        ),
+        (
+            Direction::TopToBottom,
+            Rectangle::new(Point::new(10, 20), Size::new(100, 10)),
+        ),
+        (
+            Direction::BottomToTop,
+            Rectangle::new(Point::new(10, 50), Size::new(100, 10)),
+        ),
+    ] {
+        assert_eq!(direction.reveal(area, quarter), shown, "{direction:?}");
+        assert!(direction.reveal(area, Progress::ZERO).is_zero_sized());
+        assert_eq!(direction.reveal(area, Progress::FULL), area);
+    }
+}
+
+#[test]
+fn pacer_keeps_to_time() {
+    let mut pacer = FramePacer::new(20);
+    // on time, one slot after the other
+    assert_eq!(
+        pacer.advance(5),
+        NextFrame {
+            at: 20,
+            late: false
+        }
+    );
+    assert_eq!(
+        pacer.advance(40),
+        NextFrame {
+            at: 40,
+            late: false
+        }
+    );
+    // 70ms in, the slot at 60 is gone
+    assert_eq!(pacer.advance(70), NextFrame { at: 80, late: true });
+    assert_eq!(pacer.dropped(), 1);
+    assert_eq!(
+        pacer.advance(85),
+        NextFrame {
+            at: 100,
+            late: false
+        }
+    );
+    // a long stall
+    assert_eq!(
+        pacer.advance(200),
+        NextFrame {
+            at: 200,
+            late: true
+        }
+    );
+    assert_eq!(pacer.dropped(), 5);
+}
+
+#[test]
+fn boot_frames() {
+    // nothing before the sweep starts moving
+    assert!(frame(&boot::SEQUENCE, 0).buffer() == new_target().buffer());
+    for at in [100, 450, 800, 1000] {
+        assert_snapshot(&format!("boot_{at:04}ms"), &frame(&boot::SEQUENCE, at));
+    }
+    // it ends on the still boot image
+    let mut still = new_target();
+    Graphics::load().draw_boot_image(&mut still);
+    assert!(frame(&boot::SEQUENCE, boot::SEQUENCE.length).buffer() == still.buffer());
+}
+
+#[test]
+fn boot_brightness() {
+    let sequence = &boot::SEQUENCE;
+    assert_eq!(sequence.brightness(0), Progress::ZERO);
+    assert!(sequence.brightness(100) > Progress::ZERO);
+    for at in [250, 1000, sequence.length] {
+        assert_eq!(sequence.brightness(at), Progress::FULL);
+    }
+}
+
+#[test]
+fn every_boot_frame_fits_the_screen() {
+    let screen = Rectangle::new(Point::zero(), Size::new(256, 48));
+    for (at, target) in sequence_frames(&boot::SEQUENCE) {
+        let outside = target
+            .bounding_box()
+            .points()
+            .filter(|point| !screen.contains(*point))
+            .find(|point| lit(&target, *point));
+        assert_eq!(outside, None, "frame at {at}ms is off screen");
+    }
+}
+
+#[test]
+fn sprites_cycle() {
+    static FRAMES: [&PackedImage; 3] = [&assets::FACE, &assets::FEET, &assets::DEF];
+    static CUES: [Cue; 1] = [Cue {
+        start: 100,
+        end: 1000,
+        content: Content::Sprite {
+            frames: &FRAMES,
+            frame_time: 50,
+        },
+        motion: Motion::Still,
+    }];
+    let sequence = Sequence {
+        length: 1000,
+        cues: &CUES,
+        brightness: Track::new(&[], Easing::Linear),
+    };
+
+    // times are from the start of the cue
+    assert!(frame(&sequence, 50).buffer() == new_target().buffer());
+    for (at, image) in [
+        (100, &assets::FACE),
+        (149, &assets::FACE),
+        (150, &assets::FEET),
+        (200, &assets::DEF),
+        (250, &assets::FACE),
+    ] {
+        assert!(
+            frame(&sequence, at).buffer() == draw(image).buffer(),
+            "at {at}"
+        );
+    }
+    assert!(frame(&sequence, 1001).buffer() == new_target().buffer());
+}
+
+#[test]
+fn slides_follow_their_tracks() {
+    static X: [Keyframe; 2] = [
+        Keyframe { at: 0, value: -40 },
+        Keyframe { at: 100, value: 0 },
+    ];
+    static Y: [Keyframe; 1] = [Keyframe { at: 0, value: 3 }];
+    static CUES: [Cue; 1] = [Cue {
+        start: 0,
+        end: 100,
+        content: Content::Image(&assets::DEFSYMBOL),
+        motion: Motion::Slide {
+            x: Track::new(&X, Easing::Linear),
+            y: Track::new(&Y, Easing::Linear),
+        },
+    }];
+    let sequence = Sequence {
+        length: 100,
+        cues: &CUES,
+        brightness: Track::new(&[], Easing::Linear),
+    };
+
+    for (at, offset) in [
+        (0, Point::new(-40, 3)),
+        (50, Point::new(-20, 3)),
+        (100, Point::new(0, 3)),
+    ] {
+        let mut moved = new_target();
+        _ = assets::DEFSYMBOL.draw(&mut moved.translate(offset));
+        assert!(frame(&sequence, at).buffer() == moved.buffer(), "at {at}");
+    }
+}
+
+struct Background;
+
+impl Scene for Background {
+    fn draw<D: BinaryTarget>(&self, display: &mut D) {
+        Graphics::load().draw_background(display);
+    }
+}
+
+struct Logo;
+
+impl Scene for Logo {
+    fn draw<D: BinaryTarget>(&self, display: &mut D) {
+        Graphics::load().draw_boot_image(display);
+    }
+}
+
+fn render_scene(scene: &impl Scene) -> Target {
+    let mut target = new_target();
+    scene.draw(&mut target);
+    target
+}
+
+#[test]
+fn transitions_start_and_end_on_the_pages() {
+    let (from, to) = (render_scene(&Background), render_scene(&Logo));
+    for transition in [
+        Transition::SlideLeft,
+        Transition::SlideRight,
+        Transition::Wipe,
+        Transition::Fade,
+    ] {
+        let frames: Vec<_> = transition_frames(transition, &Background, &Logo).collect();
+        let (_, brightness, first) = frames.first().unwrap();
+        assert!(
+            first.buffer() == from.buffer(),
+            "{transition:?} doesn't start on the old page"
+        );
+        assert_eq!(*brightness, Progress::FULL);
+        let (at, brightness, last) = frames.last().unwrap();
+        assert_eq!(*at, transition.duration());
+        assert!(
+            last.buffer() == to.buffer(),
+            "{transition:?} doesn't end on the new page"
+        );
+        assert_eq!(*brightness, Progress::FULL);
+    }
+
+    let mut cut = new_target();
+    let brightness = draw_transition(
+        Transition::Cut,
+        Progress::ZERO,
+        &Background,
+        &Logo,
+        &mut cut,
+    );
+    assert!(cut.buffer() == to.buffer());
+    assert_eq!(brightness, Progress::FULL);
+}
+
+#[test]
+fn transition_frames_half_way() {
+    for (name, transition) in [
+        ("slide_left", Transition::SlideLeft),
+        ("slide_right", Transition::SlideRight),
+        ("wipe", Transition::Wipe),
+    ] {
+        let mut target = new_target();
+        let brightness =
+            draw_transition(transition, progress(500), &Background, &Logo, &mut target);
+        assert_eq!(brightness, Progress::FULL);
+        assert_snapshot(&format!("transition_{name}"), &target);
+    }
+}
+
+#[test]
+fn fades_dim_out_and_back_in() {
+    let fade = |per_mille| {
+        let mut target = new_target();
+        let brightness = draw_transition(
+            Transition::Fade,
+            progress(per_mille),
+            &Background,
+            &Logo,
+            &mut target,
+        );
+        (brightness, target)
+    };
+    let (brightness, target) = fade(250);
+    assert_eq!(brightness, progress(500));
+    assert!(target.buffer() == render_scene(&Background).buffer());
+    let (brightness, _) = fade(499);
+    assert!(brightness < progress(10));
+    let (brightness, target) = fade(750);
+    assert_eq!(brightness, progress(500));
+    assert!(target.buffer() == render_scene(&Logo).buffer());
+}
@@ -0,0 +1,46 @@
+use embedded_graphics::prelude::*;
+use z31_hvac_host_tests::snapshot::{lit, new_target};
+use z31_hvac_host_tests::vfdgraphics::{PackedImage, assets};
+
+#[test]
+fn every_asset_is_packed() {
+    let names: Vec<&str> = assets::ALL.iter().map(|(name, _)| *name).collect();
+    for expected in [
+        "ClimateControlBackground",
+        "Def",
+        "DefSymbol",
+        "Face",
+        "FaceandFeet",
+        "Feet",
+        "fairlady",
+    ] {
+        assert!(
+            names.contains(&expected),
+            "{expected} missing from {names:?}"
+        );
+    }
+}
+
+#[test]
+fn draw_matches_packed_pixels() {
+    for (name, image) in assets::ALL {
+        let size = image.size();
+        for offset in [image.offset(), Point::new(3, 1), Point::new(10, 9)] {
+            let moved = PackedImage::new(size.width, size.height, offset, image.data());
+            let mut target = new_target();
+            moved.draw(&mut target).unwrap();
+
+            let screen = target.bounding_box();
+            for (x, y) in (0..size.height).flat_map(|y| (0..size.width).map(move |x| (x, y))) {
+                let point = offset + Point::new(x as i32, y as i32);
+                if screen.contains(point) {
+                    assert_eq!(
+                        lit(&target, point),
+                        moved.pixel(x, y).is_on(),
+                        "{name} at {offset:?}, pixel ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use z31_hvac_host_tests::brightness::*;
+
+/// Records every level it is given
+#[derive(Default)]
+struct MockDimmer {
+    levels: Vec<u16>,
+}
+
+impl Dimmable for MockDimmer {
+    type Error = ();
+
+    fn set_brightness(&mut self, level: u16) -> Result<(), ()> {
+        self.levels.push(level);
+        Ok(())
+    }
+}
+
+const DARK: u16 = 100;
+const SUNNY: u16 = ADC_MAX;
+
+fn lights(dimmer: u16, ambient: u16) -> LightInputs {
+    LightInputs { dimmer, ambient }
+}
+
+#[test]
+fn day_curve_spans_day_range() {
+    assert_eq!(day_curve(0), DAY_MIN);
+    assert_eq!(day_curve(ADC_MAX), DAY_MAX);
+    assert_eq!(day_curve(u16::MAX), DAY_MAX);
+    assert!(day_curve(1000) < day_curve(3000));
+}
+
+#[test]
+fn night_curve_favours_low_end() {
+    assert_eq!(night_curve(0), NIGHT_MIN);
+    assert_eq!(night_curve(LIGHTS_ON_THRESHOLD), NIGHT_MIN);
+    assert_eq!(night_curve(ADC_MAX), NIGHT_MAX);
+
+    let half = LIGHTS_ON_THRESHOLD + (ADC_MAX - LIGHTS_ON_THRESHOLD) / 2;
+    let quarter = NIGHT_MIN + (NIGHT_MAX - NIGHT_MIN) / 4;
+    assert!(night_curve(half).abs_diff(quarter) <= 1);
+}
+
+#[test]
+fn lights_off_follows_photodiode() {
+    assert_eq!(target_level(lights(0, DARK), 0), day_curve(DARK));
+    assert_eq!(target_level(lights(0, SUNNY), 0), DAY_MAX);
+}
+
+#[test]
+fn lights_on_in_the_dark_follows_dimmer() {
+    assert_eq!(target_level(lights(ADC_MAX, DARK), 0), NIGHT_MAX);
+    assert_eq!(target_level(lights(1000, DARK), 0), night_curve(1000));
+}
+
+#[test]
+fn lights_on_in_daylight_stays_bright() {
+    assert!(!lights(ADC_MAX, SUNNY).night());
+    assert_eq!(target_level(lights(1000, SUNNY), 0), DAY_MAX);
+}
+
+#[test]
+fn offset_is_added_and_clamped() {
+    let base = target_level(lights(1000, DARK), 0);
+    assert_eq!(target_level(lights(1000, DARK), 64), base + 64);
+    assert_eq!(target_level(lights(1000, DARK), -512), MIN_LEVEL);
+    assert_eq!(target_level(lights(0, SUNNY), 512), MAX_LEVEL);
+}
+
+#[test]
+fn fade_converges_without_overshoot() {
+    let mut controller = BrightnessController::new(0);
+    let mut display = MockDimmer::default();
+    controller.set_target(MAX_LEVEL);
+
+    let mut steps = 0;
+    while controller.apply(&mut display).unwrap() {
+        steps += 1;
+        assert!(steps < 200, "fade never finished");
+    }
+
+    assert_eq!(display.levels.last(), Some(&MAX_LEVEL));
+    assert!(display.levels.windows(2).all(|w| w[0] < w[1]));
+    // starts fast, finishes with single steps
+    assert!(display.levels[0] >= MAX_LEVEL / FADE_DIVISOR);
+    assert_eq!(display.levels[display.levels.len() - 2], MAX_LEVEL - 1);
+}
+
+#[test]
+fn fade_down() {
+    let mut controller = BrightnessController::new(MAX_LEVEL);
+    let mut display = MockDimmer::default();
+    controller.update(lights(LIGHTS_ON_THRESHOLD + 1, DARK));
+
+    while controller.apply(&mut display).unwrap() {}
+
+    assert_eq!(controller.level(), NIGHT_MIN);
+    assert!(display.levels.windows(2).all(|w| w[0] > w[1]));
+}
+
+#[test]
+fn settled_controller_sends_nothing() {
+    let mut controller = BrightnessController::new(300);
+    let mut display = MockDimmer::default();
+
+    assert!(!controller.apply(&mut display).unwrap());
+    assert!(display.levels.is_empty());
+}
+
+#[test]
+fn jump_to_skips_the_fade() {
+    let mut controller = BrightnessController::new(0);
+    controller.set_target(500);
+    controller.jump_to(200);
+
+    assert_eq!(controller.level(), 200);
+    assert_eq!(controller.tick(), None);
+}
+
+#[test]
+fn ambient_is_smoothed() {
+    let mut controller = BrightnessController::new(0);
+    controller.update(lights(0, 0));
+    assert_eq!(controller.target(), DAY_MIN);
+
+    // one bright reading only moves the target part of the way
+    controller.update(lights(0, SUNNY));
+    assert!(controller.target() > DAY_MIN);
+    assert!(controller.target() < day_curve(SUNNY / 2));
+
+    for _ in 0..100 {
+        controller.update(lights(0, SUNNY));
+    }
+    assert!(controller.target() >= day_curve(SUNNY - 16));
+}
+
+#[test]
+fn offset_applies_on_next_update() {
+    let mut controller = BrightnessController::new(0);
+    controller.update(lights(2000, DARK));
+    let base = controller.target();
+
+    controller.set_offset(-64);
+    assert_eq!(controller.target(), base);
+    controller.update(lights(2000, DARK));
+    assert_eq!(controller.target(), base - 64);
+}
+
+#[test]
+fn limit_caps_target_until_lifted() {
+    let mut controller = BrightnessController::new(0);
+    controller.update(lights(0, SUNNY));
+    assert_eq!(controller.target(), DAY_MAX);
+
+    controller.set_limit(Some(64));
+    assert_eq!(controller.target(), 64);
+    // new readings stay under the limit
+    controller.update(lights(0, SUNNY));
+    assert_eq!(controller.target(), 64);
+
+    controller.set_limit(None);
+    assert_eq!(controller.target(), DAY_MAX);
+}
+
+#[test]
+fn limit_can_blank() {
+    let mut controller = BrightnessController::new(300);
+    let mut display = MockDimmer::default();
+    controller.set_limit(Some(0));
+
+    while controller.apply(&mut display).unwrap() {}
+
+    assert_eq!(display.levels.last(), Some(&0));
+}
@@ -0,0 +1,118 @@
+use embedded_graphics::prelude::*;
+use z31_hvac_host_tests::burnin::*;
+
+fn guard() -> BurnInGuard {
+    BurnInGuard::new(BurnInConfig::default(), 1000)
+}
+
+#[test]
+fn idle_steps_through_dim_and_saver() {
+    let mut guard = guard();
+    let config = *guard.config();
+
+    assert_eq!(guard.update(1000 + config.dim_after - 1, true), None);
+    assert_eq!(guard.state(), IdleState::Active);
+    assert_eq!(guard.brightness_limit(), None);
+
+    assert_eq!(
+        guard.update(1000 + config.dim_after, true),
+        Some(IdleState::Dimmed)
+    );
+    assert_eq!(guard.brightness_limit(), Some(config.dim_level));
+
+    assert_eq!(
+        guard.update(1000 + config.saver_after, true),
+        Some(IdleState::Saver)
+    );
+    // lights on, never blanks
+    assert_eq!(guard.update(1000 + 100_000, true), None);
+}
+
+#[test]
+fn blanks_with_lights_off() {
+    let mut guard = guard();
+    let config = *guard.config();
+
+    assert_eq!(
+        guard.update(1000 + config.blank_after, false),
+        Some(IdleState::Blank)
+    );
+    assert_eq!(guard.brightness_limit(), Some(0));
+
+    // lights coming on brings back the dimmed display, still idle
+    assert_eq!(
+        guard.update(1000 + config.blank_after, true),
+        Some(IdleState::Dimmed)
+    );
+}
+
+#[test]
+fn input_wakes_up() {
+    let mut guard = guard();
+    let config = *guard.config();
+
+    assert!(!guard.input(1010));
+    guard.update(1010 + config.saver_after, true);
+
+    assert!(guard.input(1010 + config.saver_after));
+    assert_eq!(guard.state(), IdleState::Active);
+    assert_eq!(guard.idle_for(1010 + config.saver_after + 5), 5);
+    assert_eq!(guard.update(1010 + config.saver_after + 5, true), None);
+}
+
+#[test]
+fn shift_walks_the_pattern() {
+    let guard = guard();
+    let period = guard.config().shift_period;
+
+    let shifts: Vec<Point> = (0..SHIFT_PATTERN.len() as u32 + 1)
+        .map(|step| guard.shift(step * period))
+        .collect();
+
+    assert_eq!(shifts[..SHIFT_PATTERN.len()], SHIFT_PATTERN);
+    assert_eq!(shifts[SHIFT_PATTERN.len()], SHIFT_PATTERN[0]);
+    assert_eq!(guard.shift(period - 1), guard.shift(0));
+    // never more than a pixel between neighbouring steps
+    for pair in shifts.windows(2) {
+        let d = pair[1] - pair[0];
+        assert!(d.x.abs() <= 1 && d.y.abs() <= 1);
+    }
+}
+
+#[test]
+fn shift_disabled() {
+    let guard = BurnInGuard::new(
+        BurnInConfig {
+            shift_period: 0,
+            ..BurnInConfig::default()
+        },
+        0,
+    );
+    assert_eq!(guard.shift(12345), Point::zero());
+}
+
+#[test]
+fn saver_stays_on_screen_and_moves() {
+    let guard = guard();
+    let size = Size::new(40, 20);
+    let area = Size::new(256, 48);
+
+    let mut seen_x = [false; 256];
+    for t in 0..1000 {
+        let p = guard.saver_position(t, size, area);
+        assert!(p.x >= 0 && p.x + size.width as i32 <= area.width as i32);
+        assert!(p.y >= 0 && p.y + size.height as i32 <= area.height as i32);
+        seen_x[p.x as usize] = true;
+
+        let next = guard.saver_position(t + 1, size, area);
+        assert!((next - p).x.abs() <= 1 && (next - p).y.abs() <= 1);
+    }
+    assert!(seen_x[..=216].iter().all(|&seen| seen));
+}
+
+#[test]
+fn saver_item_bigger_than_area() {
+    let guard = guard();
+    let p = guard.saver_position(77, Size::new(300, 60), Size::new(256, 48));
+    assert_eq!(p, Point::zero());
+}
@@ -0,0 +1,158 @@
+use z31_hvac_host_tests::climatecontrol::ClimateControlBacker;
+use z31_hvac_host_tests::diagnostics::*;
+use z31_hvac_host_tests::snapshot::{assert_snapshot, new_target};
+
+fn thermistor(filtered: u16) -> Reading {
+    Reading {
+        raw: filtered + 3,
+        filtered,
+        ohms: Some(10_000),
+        tenths: 770,
+        fault: divider_fault(filtered),
+    }
+}
+
+#[test]
+fn divider_faults() {
+    assert_eq!(divider_fault(ADC_MAX), Fault::Open);
+    assert_eq!(divider_fault(ADC_MAX - RAIL_MARGIN), Fault::Open);
+    assert_eq!(divider_fault(ADC_MAX - RAIL_MARGIN - 1), Fault::None);
+    assert_eq!(divider_fault(2048), Fault::None);
+    assert_eq!(divider_fault(RAIL_MARGIN + 1), Fault::None);
+    assert_eq!(divider_fault(RAIL_MARGIN), Fault::Short);
+    assert_eq!(divider_fault(0), Fault::Short);
+}
+
+#[test]
+fn onboard_range() {
+    // 77 °F
+    assert_eq!(onboard_fault(770), Fault::None);
+    assert_eq!(onboard_fault(-400), Fault::None);
+    assert_eq!(onboard_fault(2570), Fault::None);
+    assert_eq!(onboard_fault(-401), Fault::Range);
+    assert_eq!(onboard_fault(3000), Fault::Range);
+}
+
+#[test]
+fn percentages() {
+    assert_eq!(percent_tenths(0), 0);
+    assert_eq!(percent_tenths(ADC_MAX / 2), 500);
+    assert_eq!(percent_tenths(ADC_MAX), 1000);
+}
+
+#[test]
+fn formatting() {
+    assert_eq!(format_tenths(770), "77.0");
+    assert_eq!(format_tenths(-5), "-0.5");
+    assert_eq!(format_tenths(-400), "-40.0");
+    assert_eq!(format_ohms(4700), "4700");
+    assert_eq!(format_ohms(10_000), "10.0K");
+    assert_eq!(format_ohms(326_540), "326.5K");
+    assert_eq!(format_ohms(u32::MAX), ">1M");
+}
+
+#[test]
+fn readings_round_trip() {
+    let diagnostics = Diagnostics::new();
+    assert_eq!(diagnostics.snapshot(), Snapshot::default());
+
+    let ambient = Reading {
+        fault: Fault::Open,
+        ..thermistor(ADC_MAX)
+    };
+    let onboard = Reading {
+        raw: 900,
+        filtered: 876,
+        ohms: None,
+        tenths: -123,
+        fault: Fault::Range,
+    };
+    diagnostics.record(Sensor::Ambient, ambient);
+    diagnostics.record(Sensor::Onboard, onboard);
+
+    let snapshot = diagnostics.snapshot();
+    assert_eq!(snapshot.reading(Sensor::Ambient), Some(ambient));
+    assert_eq!(snapshot.reading(Sensor::Onboard), Some(onboard));
+    assert_eq!(snapshot.reading(Sensor::Dimmer), None);
+
+    // the latest reading wins
+    diagnostics.record(Sensor::Ambient, thermistor(2048));
+    let snapshot = diagnostics.snapshot();
+    assert_eq!(snapshot.reading(Sensor::Ambient), Some(thermistor(2048)));
+}
+
+#[test]
+fn buttons_and_outputs() {
+    let diagnostics = Diagnostics::new();
+    // AUTO and OFF
+    diagnostics.set_buttons(0x09);
+    diagnostics.set_output(Output::AcLed, true);
+    diagnostics.set_output(Output::RecircLed, true);
+    diagnostics.set_output(Output::AcLed, false);
+
+    let snapshot = diagnostics.snapshot();
+    let pressed: Vec<_> = (0..BUTTONS.len())
+        .filter(|&i| snapshot.pressed(i))
+        .map(|i| BUTTONS[i])
+        .collect();
+    assert_eq!(pressed, ["AUTO", "OFF"]);
+    let high: Vec<_> = Output::ALL
+        .into_iter()
+        .filter(|&output| snapshot.output_high(output))
+        .collect();
+    assert_eq!(high, [Output::RecircLed]);
+}
+
+#[test]
+fn sections_go_round() {
+    let mut section = Section::default();
+    for _ in Section::ALL {
+        section = section.next();
+    }
+    assert_eq!(section, Section::Sensors);
+    assert_eq!(Section::Sensors.prev(), Section::Control);
+    assert_eq!(Section::Control.next(), Section::Sensors);
+    for section in Section::ALL {
+        assert_eq!(section.next().prev(), section);
+    }
+}
+
+#[test]
+fn sections_render() {
+    let mut snapshot = Snapshot {
+        buttons: 0x84,
+        // only the AC and fan low LEDs lit
+        outputs: Output::ALL
+            .into_iter()
+            .filter(|output| ![Output::AcLed, Output::FanLowLed].contains(output))
+            .fold(0, |bits, output| bits | 1 << output as u8),
+        ..Snapshot::default()
+    };
+    snapshot.sensors[Sensor::Ambient as usize] = Some(thermistor(2051));
+    snapshot.sensors[Sensor::Onboard as usize] = Some(Reading {
+        raw: 880,
+        filtered: 876,
+        ohms: None,
+        tenths: 812,
+        fault: Fault::None,
+    });
+    snapshot.sensors[Sensor::Dimmer as usize] = Some(Reading {
+        raw: 4095,
+        filtered: 4093,
+        ohms: None,
+        tenths: percent_tenths(4093),
+        fault: Fault::None,
+    });
+    let backend = ClimateControlBacker::new();
+
+    for (name, section) in [
+        ("diagnostics_sensors", Section::Sensors),
+        ("diagnostics_buttons", Section::Buttons),
+        ("diagnostics_outputs", Section::Outputs),
+        ("diagnostics_control", Section::Control),
+    ] {
+        let mut target = new_target();
+        draw_section(section, &snapshot, &backend, &mut target);
+        assert_snapshot(name, &target);
+    }
+}
@@ -0,0 +1,163 @@
+use eei_vfd::graphics::Display as _;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text};
+use z31_hvac_host_tests::fonts::{self, FontStyle, align_text};
+use z31_hvac_host_tests::snapshot::{Target, assert_snapshot, lit, new_target};
+use z31_hvac_host_tests::vfdgraphics::{
+    LABEL_STYLE, LARGE_SEGMENT_STYLE, SEGMENT_STYLE, VALUE_STYLE,
+};
+
+fn draw(text: &str, style: FontStyle, position: Point, baseline: Baseline) -> Target {
+    let mut target = new_target();
+    _ = Text::with_baseline(text, position, style, baseline).draw(&mut target);
+    target
+}
+
+/// Smallest rectangle around the lit pixels
+fn ink(target: &Target) -> Option<Rectangle> {
+    let mut points = target
+        .bounding_box()
+        .points()
+        .filter(|point| lit(target, *point));
+    let first = points.next()?;
+    let (min, max) = points.fold((first, first), |(min, max), point| {
+        (min.component_min(point), max.component_max(point))
+    });
+    Some(Rectangle::with_corners(min, max))
+}
+
+#[test]
+fn every_font_has_numerals() {
+    assert_eq!(fonts::ALL.len(), 4);
+    for (name, font) in fonts::ALL {
+        for ch in "0123456789-.:° CF".chars() {
+            assert!(font.glyph(ch).is_some(), "{name} has no {ch:?}");
+        }
+        // only the text fonts have letters, all of printable ASCII
+        let text = name.starts_with("text");
+        assert_eq!(font.glyph('x').is_some(), text, "{name}");
+        if text {
+            for ch in ' '..='~' {
+                assert!(font.glyph(ch).is_some(), "{name} has no {ch:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn spacing_is_proportional() {
+    for (style, narrow, wide) in [
+        (SEGMENT_STYLE, "1", "8"),
+        (LARGE_SEGMENT_STYLE, "1", "8"),
+        (LABEL_STYLE, "l", "M"),
+        (VALUE_STYLE, "l", "M"),
+    ] {
+        let width = |text| {
+            style
+                .measure_string(text, Point::zero(), Baseline::Top)
+                .next_position
+                .x
+        };
+        assert!(width(narrow) < width(wide));
+        assert!(width(".") < width("8"));
+        assert_eq!(width("18"), width("1") + width("8"));
+    }
+}
+
+/// The box is the whole line high and starts where the text does, the ink
+/// is inside it and reaches its right edge
+#[test]
+fn measured_box_matches_the_ink() {
+    let position = Point::new(20, 5);
+    for style in [SEGMENT_STYLE, LARGE_SEGMENT_STYLE, LABEL_STYLE, VALUE_STYLE] {
+        for text in ["88", "-12", "71", "3.5", "12:34"] {
+            let measured = style.measure_string(text, position, Baseline::Top);
+            let drawn = ink(&draw(text, style, position, Baseline::Top)).unwrap();
+            assert_eq!(measured.bounding_box.top_left, position, "{text}");
+            assert_eq!(measured.bounding_box.size.height, style.line_height());
+            assert_eq!(measured.bounding_box.intersection(&drawn), drawn, "{text}");
+            assert_eq!(
+                drawn.bottom_right().unwrap().x,
+                measured.bounding_box.bottom_right().unwrap().x,
+                "{text}"
+            );
+        }
+    }
+}
+
+#[test]
+fn baselines() {
+    let position = Point::new(10, 20);
+    let height = SEGMENT_STYLE.line_height() as i32;
+    for (baseline, top) in [
+        (Baseline::Top, 20),
+        (Baseline::Middle, 20 - (height - 1) / 2),
+        (Baseline::Bottom, 20 - (height - 1)),
+        // no descent, the bottom row is the baseline
+        (Baseline::Alphabetic, 20 - (height - 1)),
+    ] {
+        let drawn = ink(&draw("8", SEGMENT_STYLE, position, baseline)).unwrap();
+        assert_eq!(drawn.top_left.y, top, "{baseline:?}");
+        assert_eq!(drawn.size.height as i32, height, "{baseline:?}");
+    }
+}
+
+#[test]
+fn missing_characters_are_left_out() {
+    let with = draw("7x2", SEGMENT_STYLE, Point::new(5, 5), Baseline::Top);
+    let without = draw("72", SEGMENT_STYLE, Point::new(5, 5), Baseline::Top);
+    assert!(with.buffer() == without.buffer());
+}
+
+#[test]
+fn aligned_text() {
+    let size = Size::new(100, 40);
+    let style = LARGE_SEGMENT_STYLE;
+    for text in ["1", "-40", "127"] {
+        let width = style
+            .measure_string(text, Point::zero(), Baseline::Top)
+            .bounding_box
+            .size
+            .width as i32;
+        // 32 rows centred in 40
+        assert_eq!(
+            align_text(text, &style, Alignment::Left, size),
+            Point::new(0, 4)
+        );
+        assert_eq!(
+            align_text(text, &style, Alignment::Center, size),
+            Point::new((100 - width) / 2, 4)
+        );
+
+        let right = align_text(text, &style, Alignment::Right, size);
+        let drawn = ink(&draw(text, style, right, Baseline::Top)).unwrap();
+        assert_eq!(drawn.bottom_right().unwrap().x, 99, "{text}");
+    }
+}
+
+#[test]
+fn font_renders() {
+    // a line each, two lines of the large font don't fit on the panel
+    for (name, style) in [("seg7_13", SEGMENT_STYLE), ("seg7_32", LARGE_SEGMENT_STYLE)] {
+        for (line, text) in ["0123456789", "-12.5°C 7:41"].into_iter().enumerate() {
+            let target = draw(text, style, Point::new(2, 2), Baseline::Top);
+            assert_snapshot(&format!("font_{name}_{line}"), &target);
+        }
+    }
+
+    // all of printable ASCII, a line of each
+    let lines = [
+        " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMN",
+        "OPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~°",
+    ];
+    for (name, style) in [("text_13", LABEL_STYLE), ("text_13b", VALUE_STYLE)] {
+        let mut target = new_target();
+        for (line, text) in lines.into_iter().enumerate() {
+            let position = Point::new(2, 2 + 16 * line as i32);
+            _ = Text::with_baseline(text, position, style, Baseline::Top).draw(&mut target);
+        }
+        assert_snapshot(&format!("font_{name}"), &target);
+    }
+}
@@ -0,0 +1,178 @@
+use z31_hvac_host_tests::framediff::*;
+
+/// Same as the VFD: 16 bytes a column, 7 of them on the panel
+const LAYOUT: FrameLayout = FrameLayout {
+    column_bytes: 16,
+    visible_bytes: 7,
+};
+const COLUMNS: usize = 256;
+
+fn frame() -> Vec<u8> {
+    vec![0; COLUMNS * LAYOUT.column_bytes]
+}
+
+fn set(frame: &mut [u8], column: usize, byte: usize, value: u8) {
+    frame[column * LAYOUT.column_bytes + byte] = value;
+}
+
+fn spans(front: &[u8], back: &[u8]) -> Vec<Span> {
+    changed_spans(LAYOUT, front, back).collect()
+}
+
+/// Write every span into `gram` the way the partial write does
+fn apply(gram: &mut [u8], back: &[u8]) {
+    let front = gram.to_vec();
+    let mut out = vec![0; COLUMNS * LAYOUT.visible_bytes];
+    for span in changed_spans(LAYOUT, &front, back) {
+        let data = span.gather(LAYOUT, back, &mut out);
+        assert_eq!(data.len(), span.len());
+        let rows = span.bytes.len();
+        for (i, column) in data.chunks(rows).enumerate() {
+            let start = (span.columns.start + i) * LAYOUT.column_bytes + span.bytes.start;
+            gram[start..start + rows].copy_from_slice(column);
+        }
+    }
+}
+
+#[test]
+fn same_frames_send_nothing() {
+    let mut front = frame();
+    set(&mut front, 10, 3, 0x5A);
+    assert_eq!(spans(&front, &front.clone()), []);
+}
+
+#[test]
+fn single_byte_change() {
+    let front = frame();
+    let mut back = frame();
+    set(&mut back, 42, 5, 0x10);
+
+    let found = spans(&front, &back);
+    assert_eq!(
+        found,
+        [Span {
+            columns: 42..43,
+            bytes: 5..6
+        }]
+    );
+    let span = &found[0];
+    assert_eq!(
+        (span.x(), span.y(), span.width(), span.height()),
+        (42, 40, 1, 8)
+    );
+}
+
+#[test]
+fn neighbouring_columns_join() {
+    let front = frame();
+    let mut back = frame();
+    set(&mut back, 100, 1, 0xFF);
+    set(&mut back, 101, 4, 0xFF);
+    set(&mut back, 102, 2, 0xFF);
+    // a column apart starts a new span
+    set(&mut back, 104, 0, 0x01);
+
+    assert_eq!(
+        spans(&front, &back),
+        [
+            Span {
+                columns: 100..103,
+                bytes: 1..5
+            },
+            Span {
+                columns: 104..105,
+                bytes: 0..1
+            },
+        ]
+    );
+}
+
+#[test]
+fn edges_of_the_frame() {
+    let front = frame();
+    let mut back = frame();
+    set(&mut back, 0, 0, 0x80);
+    set(&mut back, COLUMNS - 1, LAYOUT.visible_bytes - 1, 0x01);
+
+    assert_eq!(
+        spans(&front, &back),
+        [
+            Span {
+                columns: 0..1,
+                bytes: 0..1
+            },
+            Span {
+                columns: COLUMNS - 1..COLUMNS,
+                bytes: 6..7
+            },
+        ]
+    );
+}
+
+#[test]
+fn hidden_rows_are_ignored() {
+    let front = frame();
+    let mut back = frame();
+    set(&mut back, 7, LAYOUT.visible_bytes, 0xFF);
+    set(&mut back, 7, LAYOUT.column_bytes - 1, 0xFF);
+    assert_eq!(spans(&front, &back), []);
+}
+
+#[test]
+fn full_change_is_one_span() {
+    let front = frame();
+    let back = vec![0xFF; COLUMNS * LAYOUT.column_bytes];
+
+    let found = spans(&front, &back);
+    assert_eq!(
+        found,
+        [Span {
+            columns: 0..COLUMNS,
+            bytes: 0..LAYOUT.visible_bytes
+        }]
+    );
+    assert_eq!(found[0].len(), COLUMNS * LAYOUT.visible_bytes);
+}
+
+#[test]
+fn gather_packs_columns() {
+    let mut back = frame();
+    for column in 20..23 {
+        for byte in 0..LAYOUT.column_bytes {
+            set(&mut back, column, byte, (column * 16 + byte) as u8);
+        }
+    }
+    let span = Span {
+        columns: 20..23,
+        bytes: 2..4,
+    };
+    let mut out = [0; 16];
+    assert_eq!(
+        span.gather(LAYOUT, &back, &mut out),
+        [0x42, 0x43, 0x52, 0x53, 0x62, 0x63]
+    );
+}
+
+/// Frames made up of a moving bar and some scattered bytes, each sent on
+/// top of the last
+#[test]
+fn applying_spans_reproduces_frames() {
+    let mut gram = frame();
+    let mut seed = 0x1234_5678u32;
+    for step in 0..50 {
+        let mut back = frame();
+        for column in step * 3..step * 3 + 20 {
+            set(&mut back, column, step % LAYOUT.visible_bytes, 0xF0);
+        }
+        for _ in 0..step % 7 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let column = (seed >> 8) as usize % COLUMNS;
+            let byte = (seed >> 20) as usize % LAYOUT.visible_bytes;
+            set(&mut back, column, byte, seed as u8 | 1);
+        }
+
+        apply(&mut gram, &back);
+        assert!(gram == back, "frame {step} not reproduced");
+        assert_eq!(spans(&gram, &back), []);
+    }
+}
@@ -0,0 +1,74 @@
+use z31_hvac_host_tests::power::*;
+
+fn manager() -> PowerManager {
+    PowerManager::new(PowerConfig::default(), PowerState::Awake)
+}
+
+#[test]
+fn key_on_stays_awake() {
+    let mut power = manager();
+    for now in (0..10_000).step_by(100) {
+        assert_eq!(power.update(true, now), None);
+    }
+    assert_eq!(power.state(), PowerState::Awake);
+}
+
+#[test]
+fn key_off_sleeps_after_delay() {
+    let mut power = manager();
+    let config = PowerConfig::default();
+
+    assert_eq!(power.update(false, 1000), None);
+    assert_eq!(power.update(false, 1000 + config.sleep_after - 1), None);
+    assert_eq!(
+        power.update(false, 1000 + config.sleep_after),
+        Some(PowerState::Asleep)
+    );
+    // only reported once
+    assert_eq!(power.update(false, 1000 + 2 * config.sleep_after), None);
+    assert_eq!(power.state(), PowerState::Asleep);
+}
+
+#[test]
+fn cranking_dropout_is_ignored() {
+    let mut power = manager();
+
+    // accessory feed cut for two seconds while the starter runs
+    for now in (1000..3000).step_by(10) {
+        assert_eq!(power.update(false, now), None);
+    }
+    assert_eq!(power.update(true, 3000), None);
+    // the next drop starts its own timer
+    assert_eq!(power.update(false, 3500), None);
+    assert_eq!(power.update(false, 5000), None);
+    assert_eq!(power.state(), PowerState::Awake);
+}
+
+#[test]
+fn wake_is_debounced() {
+    let mut power = PowerManager::new(PowerConfig::default(), PowerState::Asleep);
+    let config = PowerConfig::default();
+
+    // contact bounce while turning the key
+    assert_eq!(power.update(true, 100), None);
+    assert_eq!(power.update(false, 110), None);
+    assert_eq!(power.update(true, 120), None);
+    assert_eq!(power.update(true, 120 + config.wake_after - 1), None);
+    assert_eq!(
+        power.update(true, 120 + config.wake_after),
+        Some(PowerState::Awake)
+    );
+}
+
+#[test]
+fn clock_wrap() {
+    let mut power = manager();
+    let config = PowerConfig::default();
+    let start = u32::MAX - 100;
+
+    assert_eq!(power.update(false, start), None);
+    assert_eq!(
+        power.update(false, start.wrapping_add(config.sleep_after)),
+        Some(PowerState::Asleep)
+    );
+}
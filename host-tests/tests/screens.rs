@@ -0,0 +1,197 @@
+use eei_vfd::graphics::Display as _;
+use z31_hvac_host_tests::buttons::{Button, ButtonEvent, ButtonSet};
+use z31_hvac_host_tests::climatecontrol::{ClimateControlBacker, ClimateControlMode};
+use z31_hvac_host_tests::diagnostics::{Diagnostics, Fault, Reading, Sensor};
+use z31_hvac_host_tests::screens::*;
+use z31_hvac_host_tests::settings::{Config, DIAGNOSTICS_CHORD, MENU_CHORD};
+use z31_hvac_host_tests::snapshot::{Target, assert_snapshot, new_target};
+use z31_hvac_host_tests::vfdgraphics::Graphics;
+
+/// 1:02:03 after boot
+const NOW: u64 = 3_723_000;
+
+/// How often a held button repeats
+const REPEAT: u64 = 100;
+
+fn backend() -> ClimateControlBacker {
+    let mut backend = ClimateControlBacker::new();
+    backend.set_mode(ClimateControlMode::FaceFeet);
+    backend.set_ac_toggle();
+    backend.set_fan_speed(3);
+    backend.set_set_temp(72);
+    backend.set_ambient_temp(-5);
+    backend
+}
+
+fn render(screens: &mut ScreenManager, now: u64) -> Target {
+    render_with(screens, now, Config::default())
+}
+
+fn render_with(screens: &mut ScreenManager, now: u64, config: Config) -> Target {
+    let graphics = Graphics::load();
+    let backend = backend();
+    let diagnostics = Diagnostics::new();
+    diagnostics.record(
+        Sensor::Onboard,
+        Reading {
+            raw: 880,
+            filtered: 876,
+            ohms: None,
+            tenths: 812,
+            fault: Fault::None,
+        },
+    );
+    let ctx = PageContext {
+        graphics: &graphics,
+        backend: &backend,
+        diagnostics: &diagnostics,
+        config,
+        now,
+    };
+    let mut target = new_target();
+    screens.draw(&ctx, &mut target);
+    target
+}
+
+fn showing(page: PageId) -> ScreenManager {
+    let mut screens = ScreenManager::new();
+    screens.goto_with(page, Transition::Cut);
+    screens
+}
+
+/// Hold `button` from `from` until `until`, repeating like the button reader
+fn hold(screens: &mut ScreenManager, button: Button, from: u64, until: u64) {
+    for now in (from..=until).step_by(REPEAT as usize) {
+        screens.handle_event(ButtonEvent::Held(button.into()), now);
+        screens.handle_event(ButtonEvent::Press(button), now);
+    }
+}
+
+fn let_go(screens: &mut ScreenManager, now: u64) {
+    screens.handle_event(ButtonEvent::Held(ButtonSet::empty()), now);
+}
+
+#[test]
+fn pages_render() {
+    for (name, page) in [
+        ("page_climate", PageId::Climate),
+        ("page_sensors", PageId::Sensors),
+        ("page_diagnostics", PageId::Diagnostics),
+        ("page_settings", PageId::Settings),
+    ] {
+        let mut screens = showing(page);
+        screens.climate_mut().set_fan_gauge(16);
+        screens.climate_mut().set_temp_gauge(19);
+        let target = render(&mut screens, NOW);
+        assert_snapshot(name, &target);
+    }
+
+    // twelve minutes and three seconds since Auto reset the trip
+    let mut screens = showing(PageId::TripClock);
+    screens.handle_button(Button::Auto, NOW - 723_000);
+    let target = render(&mut screens, NOW);
+    assert_snapshot("page_trip_clock", &target);
+}
+
+#[test]
+fn readouts_follow_the_config() {
+    // 72 °F set is 22 °C, -5 °F outside is -21 °C and reads -23 after the offset
+    let config = Config {
+        celsius: true,
+        ambient_offset: -2,
+        ..Config::default()
+    };
+    let mut screens = showing(PageId::Sensors);
+    let target = render_with(&mut screens, NOW, config);
+    assert_snapshot("page_sensors_celsius", &target);
+}
+
+#[test]
+fn holding_off_leaves_the_climate_page() {
+    let mut screens = ScreenManager::new();
+    hold(&mut screens, Button::Off, 0, PAGE_HOLD - REPEAT);
+    assert_eq!(screens.current(), PageId::Climate);
+    assert!(screens.handle_button(Button::Off, PAGE_HOLD));
+    assert_eq!(screens.current(), PageId::Sensors);
+
+    // still held, Off on the sensors page would go straight back
+    hold(&mut screens, Button::Off, PAGE_HOLD + REPEAT, 3 * PAGE_HOLD);
+    assert_eq!(screens.current(), PageId::Sensors);
+
+    // a fresh press does, however soon
+    let_go(&mut screens, 3 * PAGE_HOLD);
+    assert!(screens.handle_button(Button::Off, 3 * PAGE_HOLD + 1));
+    assert_eq!(screens.current(), PageId::Climate);
+}
+
+#[test]
+fn tapping_off_stays_on_the_climate_page() {
+    let mut screens = ScreenManager::new();
+    for now in (0..3 * PAGE_HOLD).step_by(REPEAT as usize) {
+        hold(&mut screens, Button::Off, now, now);
+        let_go(&mut screens, now);
+    }
+    assert_eq!(screens.current(), PageId::Climate);
+}
+
+#[test]
+fn other_buttons_restart_the_off_hold() {
+    let mut screens = ScreenManager::new();
+    hold(&mut screens, Button::Off, 0, PAGE_HOLD - REPEAT);
+    screens.handle_button(Button::TempUp, PAGE_HOLD);
+    hold(&mut screens, Button::Off, PAGE_HOLD + REPEAT, 2 * PAGE_HOLD);
+    assert_eq!(screens.current(), PageId::Climate);
+    screens.handle_button(Button::Off, 2 * PAGE_HOLD + REPEAT);
+    assert_eq!(screens.current(), PageId::Sensors);
+}
+
+#[test]
+fn info_pages_go_round() {
+    let mut screens = showing(PageId::Sensors);
+    screens.handle_button(Button::TempDown, 0);
+    assert_eq!(screens.current(), PageId::Climate);
+
+    let mut screens = showing(PageId::TripClock);
+    screens.handle_button(Button::TempUp, 0);
+    assert_eq!(screens.current(), PageId::Climate);
+
+    let mut screens = showing(PageId::Sensors);
+    screens.handle_button(Button::TempUp, 0);
+    assert_eq!(screens.current(), PageId::Settings);
+    // the repeat doesn't reach the menu, the next press does
+    assert!(!screens.handle_button(Button::TempUp, REPEAT));
+    let_go(&mut screens, REPEAT);
+    assert!(screens.handle_button(Button::TempUp, 2 * REPEAT));
+    assert_eq!(screens.current(), PageId::Settings);
+
+    let mut screens = showing(PageId::Diagnostics);
+    screens.handle_button(Button::Off, 0);
+    assert_eq!(screens.current(), PageId::Climate);
+}
+
+#[test]
+fn chords_open_their_pages() {
+    let mut screens = ScreenManager::new();
+    assert!(screens.handle_event(ButtonEvent::Chord(MENU_CHORD), 0));
+    assert_eq!(screens.current(), PageId::Settings);
+    assert!(!screens.handle_event(ButtonEvent::Chord(MENU_CHORD), 0));
+    assert!(screens.handle_event(ButtonEvent::Chord(DIAGNOSTICS_CHORD), 0));
+    assert_eq!(screens.current(), PageId::Diagnostics);
+}
+
+#[test]
+fn transition_runs_from_its_first_frame() {
+    let mut screens = ScreenManager::new();
+    screens.goto(PageId::Sensors);
+    let duration = u64::from(Transition::SlideLeft.duration());
+
+    // however long it waited to be drawn
+    render(&mut screens, NOW);
+    assert!(screens.in_transition());
+    render(&mut screens, NOW + duration - 1);
+    assert!(screens.in_transition());
+    let target = render(&mut screens, NOW + duration);
+    assert!(!screens.in_transition());
+    let still = render(&mut showing(PageId::Sensors), NOW);
+    assert!(target.buffer() == still.buffer());
+}
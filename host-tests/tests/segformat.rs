@@ -0,0 +1,125 @@
+use z31_hvac_host_tests::segformat::*;
+
+const FORMATS: [SegFormat; 4] = [
+    SegFormat {
+        blank_leading: true,
+        decimal: false,
+        overflow: Overflow::Dashes,
+    },
+    SegFormat {
+        blank_leading: false,
+        decimal: false,
+        overflow: Overflow::Dashes,
+    },
+    SegFormat {
+        blank_leading: true,
+        decimal: false,
+        overflow: Overflow::HiLo,
+    },
+    SegFormat {
+        blank_leading: false,
+        decimal: false,
+        overflow: Overflow::HiLo,
+    },
+];
+
+fn digit(glyph: Glyph) -> Option<i32> {
+    match glyph {
+        Glyph::Blank => Some(0),
+        Glyph::Digit(d) if d < 10 => Some(d.into()),
+        _ => None,
+    }
+}
+
+/// The number a readout shows, `None` for the overflow glyphs
+fn decode(readout: Readout) -> Option<i32> {
+    let tens = digit(readout.tens)?;
+    let ones = match readout.ones {
+        Glyph::Digit(d) if d < 10 => i32::from(d),
+        _ => return None,
+    };
+    let n = i32::from(readout.hund) * 100 + tens * 10 + ones;
+    Some(if readout.neg { -n } else { n })
+}
+
+fn overflow(format: &SegFormat, value: i32) -> Readout {
+    match (format.overflow, value > MAX_VALUE) {
+        (Overflow::Dashes, _) => Readout::new(false, false, Glyph::Dash, Glyph::Dash),
+        (Overflow::HiLo, true) => Readout::new(false, false, Glyph::H, Glyph::I),
+        (Overflow::HiLo, false) => Readout::new(false, false, Glyph::L, Glyph::O),
+    }
+}
+
+fn assert_shows(format: &SegFormat, readout: Readout, value: i32) {
+    if !(MIN_VALUE..=MAX_VALUE).contains(&value) {
+        assert_eq!(readout, overflow(format, value), "{value} with {format:?}");
+        return;
+    }
+    assert_eq!(decode(readout), Some(value), "{value} with {format:?}");
+    // no "-0"
+    assert_eq!(readout.neg, value < 0, "{value}");
+    let leading = value.abs() < 10;
+    assert_eq!(
+        readout.tens == Glyph::Blank,
+        leading && format.blank_leading,
+        "{value} with {format:?}"
+    );
+}
+
+#[test]
+fn every_i16_formats() {
+    for format in &FORMATS {
+        for value in i16::MIN..=i16::MAX {
+            assert_shows(format, format.format(value), value.into());
+        }
+    }
+}
+
+#[test]
+fn every_i16_in_tenths_rounds() {
+    for format in &FORMATS {
+        for tenths in i16::MIN..=i16::MAX {
+            let tenths = i32::from(tenths);
+            // half away from zero
+            let rounded = tenths.signum() * ((tenths.abs() + 5) / 10);
+            assert_shows(format, format.format_tenths(tenths as i16), rounded);
+        }
+    }
+}
+
+#[test]
+fn every_i16_in_tenths_with_decimal() {
+    for format in &FORMATS {
+        let format = SegFormat {
+            decimal: true,
+            ..*format
+        };
+        for tenths in i16::MIN..=i16::MAX {
+            let readout = format.format_tenths(tenths);
+            let tenths = i32::from(tenths);
+            if (MIN_VALUE..=MAX_VALUE).contains(&tenths) {
+                // the tenths take the ones digit, the whole part always shows
+                assert_eq!(decode(readout), Some(tenths), "{tenths}");
+                assert_ne!(readout.tens, Glyph::Blank, "{tenths}");
+                assert_eq!(readout.neg, tenths < 0, "{tenths}");
+            } else {
+                let rounded = tenths.signum() * ((tenths.abs() + 5) / 10);
+                assert_shows(&format, readout, rounded);
+            }
+        }
+    }
+}
+
+#[test]
+fn digits_look_different() {
+    let segments: Vec<u8> = (0..10).map(|d| Glyph::Digit(d).segments()).collect();
+    for (d, &lit) in segments.iter().enumerate() {
+        assert_ne!(lit, 0, "{d}");
+        assert_eq!(
+            segments.iter().filter(|&&other| other == lit).count(),
+            1,
+            "{d}"
+        );
+    }
+    assert_eq!(Glyph::Digit(10).segments(), 0);
+}
@@ -0,0 +1,370 @@
+use z31_hvac_host_tests::buttons::{Button, ButtonEvent, ButtonIter, ButtonSet};
+use z31_hvac_host_tests::climatecontrol::ClimateControlBacker;
+use z31_hvac_host_tests::screens::{PAGE_HOLD, PageId, ScreenManager};
+use z31_hvac_host_tests::settings::*;
+
+/// How often the button reader rescans a held button
+const REPEAT: u64 = 100;
+
+/// The button reader's side of the panel: the gate, the screens behind it
+/// and the climate actions that got through
+struct Panel {
+    gate: ChordGate,
+    screens: ScreenManager,
+    now: u64,
+    actions: Vec<Button>,
+}
+
+impl Panel {
+    fn new() -> Self {
+        Panel {
+            gate: ChordGate::new(),
+            screens: ScreenManager::new(),
+            now: 0,
+            actions: Vec::new(),
+        }
+    }
+
+    /// What `BUTTONS_CAPTURED` would say, without sharing it between tests
+    fn captured(&self) -> bool {
+        self.screens.current() != PageId::Climate
+    }
+
+    fn forward(&mut self, gated: Gated) {
+        if let Some(event) = gated.event {
+            self.screens.handle_event(event, self.now);
+        }
+        let actions = ButtonIter::new().filter(|&button| gated.actions.contains(button.into()));
+        self.actions.extend(actions);
+    }
+
+    /// Hold `buttons` down for `repeats` rescans. Like the button reader,
+    /// only the first one found down repeats.
+    fn hold(&mut self, buttons: ButtonSet, repeats: u64) {
+        let first = ButtonIter::new()
+            .find(|&button| buttons.contains(button.into()))
+            .unwrap();
+        for _ in 0..repeats {
+            self.now += REPEAT;
+            let gated = self.gate.scan(buttons, self.captured());
+            self.forward(gated);
+            let gated = self.gate.press(first, self.captured());
+            self.forward(gated);
+        }
+    }
+
+    fn release(&mut self) {
+        self.now += REPEAT;
+        let gated = self.gate.scan(ButtonSet::empty(), self.captured());
+        self.forward(gated);
+    }
+
+    fn tap(&mut self, button: Button) {
+        self.hold(button.into(), 1);
+        self.release();
+    }
+
+    fn config(&self) -> Config {
+        *self.screens.config()
+    }
+}
+
+#[test]
+fn menu_chord_leaves_the_climate_control_alone() {
+    let mut panel = Panel::new();
+    panel.hold(MENU_CHORD, MENU_CHORD_HOLD.into());
+    assert_eq!(panel.screens.current(), PageId::Settings);
+
+    // Off would leave the menu straight away if its repeats got through
+    panel.hold(MENU_CHORD, 30);
+    assert_eq!(panel.screens.current(), PageId::Settings);
+    panel.hold(ButtonSet::OFF, 5);
+    assert_eq!(panel.screens.current(), PageId::Settings);
+    panel.release();
+    assert_eq!(panel.actions, []);
+
+    // down to CELSIUS and flip it, then out
+    panel.tap(Button::TempDown);
+    panel.tap(Button::Auto);
+    assert!(panel.config().celsius);
+    panel.tap(Button::Off);
+    assert_eq!(panel.screens.current(), PageId::Climate);
+    assert_eq!(panel.actions, []);
+
+    panel.tap(Button::Auto);
+    assert_eq!(panel.actions, [Button::Auto]);
+}
+
+#[test]
+fn swallowed_chord_reports_the_release() {
+    let mut gate = ChordGate::new();
+    for _ in 1..MENU_CHORD_HOLD {
+        gate.scan(MENU_CHORD, false);
+    }
+    assert_eq!(
+        gate.scan(MENU_CHORD, false).event,
+        Some(ButtonEvent::Chord(MENU_CHORD))
+    );
+    assert_eq!(gate.press(Button::Off, false), Gated::default());
+    assert_eq!(gate.scan(ButtonSet::OFF, false), Gated::default());
+    assert_eq!(
+        gate.scan(ButtonSet::empty(), false),
+        Gated {
+            event: Some(ButtonEvent::Held(ButtonSet::empty())),
+            actions: ButtonSet::empty(),
+        }
+    );
+}
+
+#[test]
+fn chord_goes_off_again_after_letting_go() {
+    let mut panel = Panel::new();
+    panel.hold(MENU_CHORD, MENU_CHORD_HOLD.into());
+    panel.release();
+    panel.tap(Button::Off);
+    assert_eq!(panel.screens.current(), PageId::Climate);
+
+    panel.hold(MENU_CHORD, MENU_CHORD_HOLD.into());
+    assert_eq!(panel.screens.current(), PageId::Settings);
+}
+
+#[test]
+fn diagnostics_chord_swallows_until_let_go() {
+    let mut panel = Panel::new();
+    panel.hold(DIAGNOSTICS_CHORD, MENU_CHORD_HOLD.into());
+    assert_eq!(panel.screens.current(), PageId::Diagnostics);
+    assert_eq!(panel.actions, []);
+
+    // TempUp alone would be acted on, and Off would leave the page
+    panel.hold(ButtonSet::TEMP_UP, 5);
+    panel.hold(ButtonSet::TEMP_UP | ButtonSet::OFF, 5);
+    assert_eq!(panel.screens.current(), PageId::Diagnostics);
+    panel.release();
+    panel.tap(Button::Off);
+    assert_eq!(panel.screens.current(), PageId::Climate);
+    assert_eq!(panel.actions, []);
+
+    // on their own they act once each, when let go
+    panel.hold(ButtonSet::RECIRC, 5);
+    assert_eq!(panel.actions, []);
+    panel.release();
+    assert_eq!(panel.actions, [Button::Recirc]);
+}
+
+#[test]
+fn chord_buttons_act_once_when_let_go() {
+    let mut panel = Panel::new();
+    panel.hold(ButtonSet::AUTO, 5);
+    assert_eq!(panel.actions, []);
+    panel.release();
+    assert_eq!(panel.actions, [Button::Auto]);
+
+    // anything pressed alongside waits too
+    let mut gate = ChordGate::new();
+    assert_eq!(gate.scan(ButtonSet::OFF, false).actions, ButtonSet::empty());
+    assert_eq!(gate.press(Button::Off, false).actions, ButtonSet::empty());
+    assert_eq!(
+        gate.press(Button::FanHigh, false).actions,
+        ButtonSet::empty()
+    );
+    assert_eq!(
+        gate.press(Button::FanHigh, false).actions,
+        ButtonSet::empty()
+    );
+    assert_eq!(
+        gate.scan(ButtonSet::FAN_HIGH, false).actions,
+        ButtonSet::OFF | ButtonSet::FAN_HIGH
+    );
+    assert_eq!(
+        gate.press(Button::FanHigh, false).actions,
+        ButtonSet::FAN_HIGH
+    );
+}
+
+#[test]
+fn other_buttons_act_straight_away() {
+    let mut panel = Panel::new();
+    panel.hold(ButtonSet::TEMP_UP, 3);
+    assert_eq!(panel.actions, [Button::TempUp; 3]);
+}
+
+#[test]
+fn held_off_changes_page_instead_of_stopping_the_fan() {
+    let mut panel = Panel::new();
+    panel.hold(ButtonSet::OFF, PAGE_HOLD / REPEAT + 1);
+    assert_eq!(panel.screens.current(), PageId::Sensors);
+    panel.release();
+    assert_eq!(panel.actions, []);
+
+    panel.tap(Button::Off);
+    assert_eq!(panel.screens.current(), PageId::Climate);
+    panel.tap(Button::Off);
+    assert_eq!(panel.actions, [Button::Off]);
+}
+
+#[test]
+fn captured_presses_never_act() {
+    let mut gate = ChordGate::new();
+    gate.scan(ButtonSet::TEMP_UP, true);
+    assert_eq!(gate.press(Button::TempUp, true).actions, ButtonSet::empty());
+
+    // captured while waiting, the page change used it up
+    gate.scan(ButtonSet::OFF, false);
+    gate.press(Button::Off, false);
+    assert_eq!(
+        gate.scan(ButtonSet::empty(), true).actions,
+        ButtonSet::empty()
+    );
+    assert_eq!(
+        gate.scan(ButtonSet::empty(), false).actions,
+        ButtonSet::empty()
+    );
+}
+
+#[test]
+fn blanked_presses_never_act() {
+    let mut panel = Panel::new();
+    panel.gate.set_blanked(true);
+    panel.hold(ButtonSet::TEMP_UP, 3);
+    panel.tap(Button::Auto);
+    panel.hold(ButtonSet::OFF, 2);
+    assert_eq!(panel.actions, []);
+
+    // nor is Off when it is let go after waking up
+    panel.gate.set_blanked(false);
+    panel.release();
+    assert_eq!(panel.actions, []);
+    panel.tap(Button::TempUp);
+    assert_eq!(panel.actions, [Button::TempUp]);
+
+    // the screens still see the buttons
+    panel.gate.set_blanked(true);
+    panel.hold(MENU_CHORD, MENU_CHORD_HOLD.into());
+    assert_eq!(panel.screens.current(), PageId::Settings);
+}
+
+#[test]
+fn menu_edits_and_cancels() {
+    let mut menu = Menu::new(SETTINGS_MENU);
+    let mut config = Config::default();
+
+    assert_eq!(
+        menu.handle_button(Button::Auto, &mut config),
+        MenuEvent::Redraw
+    );
+    for _ in 0..3 {
+        menu.handle_button(Button::TempUp, &mut config);
+    }
+    assert_eq!(config.brightness_offset, 96);
+    menu.handle_button(Button::Auto, &mut config);
+    assert!(!menu.editing());
+
+    // Off puts back the value from before editing
+    menu.handle_button(Button::Auto, &mut config);
+    for _ in 0..100 {
+        menu.handle_button(Button::TempDown, &mut config);
+    }
+    assert_eq!(config.brightness_offset, -512);
+    assert_eq!(
+        menu.handle_button(Button::Off, &mut config),
+        MenuEvent::Edited
+    );
+    assert_eq!(config.brightness_offset, 96);
+    assert_eq!(
+        menu.handle_button(Button::Off, &mut config),
+        MenuEvent::Exit
+    );
+
+    // round to the last item, a list of names that wraps
+    menu.handle_button(Button::TempUp, &mut config);
+    menu.handle_button(Button::Auto, &mut config);
+    assert_eq!(menu.items()[menu.selected()].format(&config), "NORMAL");
+    menu.handle_button(Button::TempUp, &mut config);
+    assert_eq!(config.auto_aggressiveness, Aggressiveness::High);
+    menu.handle_button(Button::TempUp, &mut config);
+    assert_eq!(config.auto_aggressiveness, Aggressiveness::Low);
+    assert_eq!(
+        menu.handle_button(Button::Off, &mut config),
+        MenuEvent::Edited
+    );
+    assert_eq!(config.auto_aggressiveness, Aggressiveness::Normal);
+}
+
+#[test]
+fn auto_mode_follows_its_aggressiveness() {
+    let mut panel = Panel::new();
+    let mut backend = ClimateControlBacker::new();
+    backend.set_set_temp(72);
+    backend.set_ambient_temp(69);
+    backend.set_auto_toggle();
+    assert!(backend.auto() && backend.ac_toggle());
+
+    // 3 degrees off is still a gentle breeze
+    let mut speeds = Vec::new();
+    backend.run_auto(panel.config().auto_aggressiveness);
+    speeds.push(backend.fan_speed());
+
+    // into the menu, up to AUTO MODE and one down to LOW
+    panel.hold(MENU_CHORD, MENU_CHORD_HOLD.into());
+    panel.release();
+    panel.tap(Button::TempUp);
+    panel.tap(Button::Auto);
+    panel.tap(Button::TempDown);
+    panel.tap(Button::Auto);
+    assert_eq!(panel.config().auto_aggressiveness, Aggressiveness::Low);
+    backend.run_auto(panel.config().auto_aggressiveness);
+    speeds.push(backend.fan_speed());
+
+    // and round to HIGH
+    panel.tap(Button::Auto);
+    panel.tap(Button::TempDown);
+    panel.tap(Button::Auto);
+    backend.run_auto(panel.config().auto_aggressiveness);
+    speeds.push(backend.fan_speed());
+    assert_eq!(speeds, [50, 0, 100]);
+
+    // a speed picked by hand ends it
+    backend.set_fan_speed(50);
+    backend.run_auto(Aggressiveness::High);
+    assert!(!backend.auto());
+    assert_eq!(backend.fan_speed(), 50);
+}
+
+#[test]
+fn menu_only_has_settings_that_are_used() {
+    let labels: Vec<_> = SETTINGS_MENU.iter().map(|item| item.label).collect();
+    assert_eq!(labels, ["BRIGHTNESS", "CELSIUS", "AMB OFFSET", "AUTO MODE"]);
+}
+
+#[test]
+fn temperatures_in_the_unit_shown() {
+    let fahrenheit = Config::default();
+    let celsius = Config {
+        celsius: true,
+        ..Config::default()
+    };
+    for f in i8::MIN..=i8::MAX {
+        assert_eq!(fahrenheit.tenths(f), i16::from(f) * 10);
+        let exact = (f64::from(f) - 32.0) * 50.0 / 9.0;
+        assert_eq!(celsius.tenths(f), exact.round() as i16, "{f}");
+    }
+    assert_eq!(celsius.tenths(32), 0);
+    assert_eq!(celsius.tenths(-40), -400);
+    assert_eq!(celsius.tenths(72), 222);
+
+    let offset = Config {
+        ambient_offset: -3,
+        ..celsius
+    };
+    assert_eq!(offset.ambient_tenths(50), 70);
+    assert_eq!(offset.tenths(50), 100);
+}
+
+#[test]
+fn tenths_round_to_whole_degrees() {
+    assert_eq!(degrees(0), 0);
+    assert_eq!(degrees(222), 22);
+    assert_eq!(degrees(225), 23);
+    assert_eq!(degrees(-225), -23);
+    assert_eq!(degrees(-224), -22);
+}
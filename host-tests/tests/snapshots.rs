@@ -0,0 +1,138 @@
+use embedded_graphics::prelude::*;
+use z31_hvac_host_tests::climatecontrol::ClimateControlMode;
+use z31_hvac_host_tests::snapshot::{Target, assert_snapshot, lit, new_target};
+use z31_hvac_host_tests::vfdgraphics::{Graphics, climate};
+use z31_hvac_host_tests::widgets::Widget;
+
+fn draw_mode(mode: &ClimateControlMode, target: &mut Target) {
+    let (state, defrost) = climate::mode_state(mode);
+    climate::MODE.draw(state, target);
+    if defrost {
+        climate::DEFROST.draw(0, target);
+    }
+}
+
+fn render(draw: impl FnOnce(&Graphics, &mut Target)) -> Target {
+    let graphics = Graphics::load();
+    let mut target = new_target();
+    draw(&graphics, &mut target);
+    target
+}
+
+#[test]
+fn boot_image() {
+    let target = render(|g, t| g.draw_boot_image(t));
+    assert_snapshot("boot_image", &target);
+}
+
+#[test]
+fn background() {
+    let target = render(|g, t| g.draw_background(t));
+    assert_snapshot("background", &target);
+}
+
+#[test]
+fn climate_control_modes() {
+    for (name, mode) in [
+        ("mode_face", ClimateControlMode::Face),
+        ("mode_feet", ClimateControlMode::Feet),
+        ("mode_face_feet", ClimateControlMode::FaceFeet),
+        ("mode_feet_def", ClimateControlMode::FeetDef),
+        ("mode_def", ClimateControlMode::Def),
+    ] {
+        let target = render(|_, t| draw_mode(&mode, t));
+        assert_snapshot(name, &target);
+    }
+}
+
+#[test]
+fn toggles() {
+    for (name, on) in [("ac_on", true), ("ac_off", false)] {
+        let target = render(|_, t| climate::AC_TOGGLE.draw(on, t));
+        assert_snapshot(name, &target);
+    }
+    for (name, on) in [("recirc_on", true), ("recirc_off", false)] {
+        let target = render(|_, t| climate::RECIRC_TOGGLE.draw(on, t));
+        assert_snapshot(name, &target);
+    }
+}
+
+#[test]
+fn temperatures() {
+    for temp in [72, 5, -5, -40, 127] {
+        let target = render(|_, t| climate::INTERNAL_TEMP.draw(temp, t));
+        assert_snapshot(&format!("internal_temp_{temp}"), &target);
+
+        let target = render(|_, t| climate::AMBIENT_TEMP.draw(temp, t));
+        assert_snapshot(&format!("ambient_temp_{temp}"), &target);
+    }
+}
+
+#[test]
+fn gauges() {
+    // ends of the ranges
+    for value in [0, 32] {
+        let target = render(|_, t| climate::FAN_GAUGE.draw(value, t));
+        assert_snapshot(&format!("fan_gauge_{value}"), &target);
+    }
+    for value in [0, 36] {
+        let target = render(|_, t| climate::TEMP_GAUGE.draw(value, t));
+        assert_snapshot(&format!("temp_gauge_{value}"), &target);
+    }
+    // out of range, no pointer
+    let target = render(|_, t| climate::FAN_GAUGE.draw(33, t));
+    assert_snapshot("blank", &target);
+}
+
+#[test]
+fn menu_rows() {
+    let target = render(|g, t| {
+        g.draw_menu_row(0, "BRIGHTNESS", "255", false, false, t);
+        g.draw_menu_row(1, "CELSIUS", "OFF", true, false, t);
+        g.draw_menu_row(2, "AUTO MODE", "NORMAL", true, true, t);
+    });
+    assert_snapshot("menu_rows", &target);
+}
+
+#[test]
+fn climate_screen() {
+    let target = render(|g, t| {
+        g.draw_background(t);
+        draw_mode(&ClimateControlMode::FaceFeet, t);
+        climate::AC_TOGGLE.draw(true, t);
+        climate::RECIRC_TOGGLE.draw(false, t);
+        climate::FAN_GAUGE.draw(16, t);
+        climate::TEMP_GAUGE.draw(19, t);
+        climate::INTERNAL_TEMP.draw(72, t);
+        climate::AMBIENT_TEMP.draw(-5, t);
+    });
+    assert_snapshot("climate_screen", &target);
+
+    // every widget changed something on top of the background
+    let background = render(|g, t| g.draw_background(t));
+    for area in [
+        climate::MODE.area(),
+        climate::AC_TOGGLE.area(),
+        climate::FAN_GAUGE.area(),
+        climate::TEMP_GAUGE.area(),
+        climate::INTERNAL_TEMP.area(),
+        climate::AMBIENT_TEMP.area(),
+    ] {
+        let drawn = area
+            .points()
+            .any(|point| lit(&target, point) != lit(&background, point));
+        assert!(drawn, "nothing drawn in {area:?}");
+    }
+}
+
+#[test]
+fn screensaver() {
+    let target = render(|g, t| g.draw_screensaver(Point::new(100, 20), t));
+    assert_snapshot("screensaver", &target);
+}
+
+#[test]
+fn shifted_background() {
+    let target = render(|g, t| g.draw_background(&mut t.translated(Point::new(-1, 1))));
+    assert_snapshot("background_shifted", &target);
+}
@@ -0,0 +1,171 @@
+use eei_vfd::graphics::Display as _;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+use z31_hvac_host_tests::snapshot::{Target, assert_snapshot, lit, new_target};
+use z31_hvac_host_tests::vfdgraphics::{LABEL_STYLE, VALUE_STYLE, assets};
+use z31_hvac_host_tests::widgets::*;
+
+const fn area(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+    Rectangle::new(Point::new(x, y), Size::new(width, height))
+}
+
+/// Draw `widget` with each of `values` on a fresh target, checking that it
+/// stays inside its area
+fn render<W: Widget>(widget: &W, values: &[W::Value]) -> Target {
+    let mut target = new_target();
+    for &value in values {
+        widget.draw(value, &mut target);
+    }
+    let inside = widget.area();
+    let outside = target
+        .bounding_box()
+        .points()
+        .filter(|point| !inside.contains(*point))
+        .find(|point| lit(&target, *point));
+    assert_eq!(outside, None, "drawn outside of {inside:?}");
+    target
+}
+
+const VERTICAL_GAUGE: PointerGauge = PointerGauge::new(area(10, 2, 8, 44), Axis::Vertical, -20, 20)
+    .with_scale(GaugeScale {
+        divisions: 4,
+        length: 3,
+    });
+const HORIZONTAL_GAUGE: PointerGauge =
+    PointerGauge::new(area(30, 10, 100, 9), Axis::Horizontal, 0, 100)
+        .with_scale(GaugeScale {
+            divisions: 10,
+            length: 2,
+        })
+        .with_pointer(3);
+
+#[test]
+fn pointer_gauges() {
+    for value in [-20, 0, 7, 20] {
+        let target = render(&VERTICAL_GAUGE, &[value]);
+        assert_snapshot(&format!("widget_vertical_gauge_{value}"), &target);
+    }
+    for value in [0, 33, 100] {
+        let target = render(&HORIZONTAL_GAUGE, &[value]);
+        assert_snapshot(&format!("widget_horizontal_gauge_{value}"), &target);
+    }
+}
+
+#[test]
+fn gauge_out_of_range_only_draws_the_scale() {
+    let low = render(&VERTICAL_GAUGE, &[-21]);
+    assert!(low.buffer() == render(&VERTICAL_GAUGE, &[21]).buffer());
+    assert!(low.buffer() != new_target().buffer());
+
+    // the pointer only adds to the scale
+    let pointer = render(&VERTICAL_GAUGE, &[0]);
+    for (scale, both) in low.buffer().iter().zip(pointer.buffer()) {
+        assert_eq!(scale & !both, 0);
+    }
+    assert!(low.buffer() != pointer.buffer());
+}
+
+#[test]
+fn gauge_too_small_for_its_pointer() {
+    // 5 pixels hold a pointer 2 either side of its tip, but leave it no travel
+    for length in 0..=5 {
+        let vertical = PointerGauge::new(area(10, 2, 8, length), Axis::Vertical, 0, 10).with_scale(
+            GaugeScale {
+                divisions: 2,
+                length: 3,
+            },
+        );
+        let target = render(&vertical, &[5]);
+        let scale = render(&vertical, &[11]);
+        assert!(target.buffer() == scale.buffer(), "{length}");
+
+        let horizontal = PointerGauge::new(area(30, 10, length, 9), Axis::Horizontal, 0, 10);
+        let target = render(&horizontal, &[5]);
+        assert!(target.buffer() == new_target().buffer(), "{length}");
+    }
+
+    // one more and it can sit at either end
+    let gauge = PointerGauge::new(area(30, 10, 6, 9), Axis::Horizontal, 0, 10);
+    let target = render(&gauge, &[0]);
+    assert!(target.buffer() != render(&gauge, &[10]).buffer());
+}
+
+#[test]
+fn bar_graphs() {
+    let vertical = BarGraph::new(area(4, 4, 12, 40), Axis::Vertical, 0, 10).outlined();
+    let horizontal = BarGraph::new(area(40, 20, 120, 6), Axis::Horizontal, 50, 150);
+    for (name, value) in [("empty", 0), ("half", 5), ("full", 10)] {
+        let target = render(&vertical, &[value]);
+        assert_snapshot(&format!("widget_bar_vertical_{name}"), &target);
+    }
+    for (name, value) in [("empty", 50), ("quarter", 75), ("full", 150)] {
+        let target = render(&horizontal, &[value]);
+        assert_snapshot(&format!("widget_bar_horizontal_{name}"), &target);
+    }
+}
+
+#[test]
+fn bar_graph_clamps() {
+    let bar = BarGraph::new(area(4, 4, 12, 40), Axis::Vertical, 0, 10);
+    assert!(render(&bar, &[-5]).buffer() == render(&bar, &[0]).buffer());
+    assert!(render(&bar, &[99]).buffer() == render(&bar, &[10]).buffer());
+}
+
+#[test]
+fn numeric_fields() {
+    let field = area(60, 4, 64, 15);
+    for (name, alignment) in [
+        ("left", Alignment::Left),
+        ("center", Alignment::Center),
+        ("right", Alignment::Right),
+    ] {
+        let widget = NumericField::new(field, VALUE_STYLE)
+            .with_alignment(alignment)
+            .with_units("F");
+        for value in [7, -12, 1234] {
+            let target = render(&widget, &[value]);
+            assert_snapshot(&format!("widget_numeric_{name}_{value}"), &target);
+        }
+    }
+}
+
+#[test]
+fn hanging_sign_keeps_digits_in_place() {
+    let widget = NumericField::new(area(60, 4, 64, 15), LABEL_STYLE).with_hanging_sign();
+    let positive = render(&widget, &[42]);
+    let negative = render(&widget, &[-42]);
+    // the same digits, the sign only adds pixels
+    for (p, n) in positive.buffer().iter().zip(negative.buffer()) {
+        assert_eq!(p & !n, 0);
+    }
+    assert!(positive.buffer() != negative.buffer());
+}
+
+#[test]
+fn toggle_labels() {
+    let toggle = ToggleLabel::new(
+        area(80, 30, 40, 16),
+        ("AUTO", VALUE_STYLE),
+        ("MAN", LABEL_STYLE),
+    );
+    for on in [true, false] {
+        let target = render(&toggle, &[on]);
+        assert_snapshot(&format!("widget_toggle_{on}"), &target);
+    }
+}
+
+#[test]
+fn icon_states() {
+    static STATES: [&z31_hvac_host_tests::vfdgraphics::PackedImage; 2] =
+        [&assets::FACE, &assets::DEFSYMBOL];
+    let icon = Icon::new(Point::new(10, 1), &STATES);
+    assert_eq!(icon.area(), area(10, 1, 39, 45));
+
+    for state in 0..2 {
+        let target = render(&icon, &[state]);
+        assert_snapshot(&format!("widget_icon_{state}"), &target);
+    }
+    // no picture for the state
+    assert!(render(&icon, &[2]).buffer() == new_target().buffer());
+}
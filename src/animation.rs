@@ -0,0 +1,451 @@
+//! Animations on the VFD: the boot sequence and changes of page.
+//!
+//! A [`Sequence`] is a list of [`Cue`]s, each showing some [`Content`] for a
+//! while and moving it with a [`Motion`]: wiping it on, sliding it about or
+//! just holding it still. How far a wipe has got or where a slide is comes
+//! from a [`Track`] of keyframes, so a bar can sweep up and back down again
+//! with a single cue. The sequence has a brightness track as well, for
+//! fading the whole display in and out.
+//!
+//! Everything is worked out from the time since the start, never from the
+//! number of frames drawn, so a frame that takes too long to send only
+//! drops frames instead of slowing the animation down. [`FramePacer`] picks
+//! which frames to draw.
+//!
+//! Going from one page to another is a [`Transition`] between two
+//! [`Scene`]s, drawn by [`draw_transition`].
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics_transform::ViewportExt;
+
+use crate::vfdgraphics::{BinaryTarget, PackedImage};
+
+/// Milliseconds between frames, 50 a second
+pub const FRAME_TIME: u32 = 20;
+
+/// How far through something, from [`Progress::ZERO`] to [`Progress::FULL`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Progress(u16);
+
+impl Progress {
+    pub const ZERO: Progress = Progress(0);
+    pub const FULL: Progress = Progress(1000);
+
+    /// Thousandths of the way, anything over 1000 is [`Progress::FULL`]
+    pub const fn per_mille(per_mille: u16) -> Self {
+        if per_mille > 1000 {
+            Progress::FULL
+        } else {
+            Progress(per_mille)
+        }
+    }
+
+    /// `elapsed` milliseconds into something lasting `duration`
+    pub fn through(elapsed: u32, duration: u32) -> Self {
+        if elapsed >= duration {
+            return Progress::FULL;
+        }
+        Progress((u64::from(elapsed) * 1000 / u64::from(duration)) as u16)
+    }
+
+    pub fn get(self) -> u16 {
+        self.0
+    }
+
+    pub fn is_done(self) -> bool {
+        self == Progress::FULL
+    }
+
+    /// The same share of `amount`
+    pub fn of(self, amount: i32) -> i32 {
+        (i64::from(amount) * i64::from(self.0) / 1000) as i32
+    }
+
+    pub fn ease(self, easing: Easing) -> Self {
+        let p = u32::from(self.0);
+        let eased = match easing {
+            Easing::Linear => p,
+            Easing::In => p * p / 1000,
+            Easing::Out => 1000 - (1000 - p) * (1000 - p) / 1000,
+            Easing::InOut if p < 500 => 2 * p * p / 1000,
+            Easing::InOut => 1000 - 2 * (1000 - p) * (1000 - p) / 1000,
+        };
+        Progress(eased as u16)
+    }
+}
+
+/// How a change speeds up and slows down
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    /// The same speed all the way
+    #[default]
+    Linear,
+    /// Starting slowly
+    In,
+    /// Slowing down at the end
+    Out,
+    /// Starting slowly and slowing down at the end
+    InOut,
+}
+
+/// A value at a point in time
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Keyframe {
+    /// Milliseconds from the start of the cue
+    pub at: u32,
+    pub value: i32,
+}
+
+/// A value changing over time, going from keyframe to keyframe.
+///
+/// Before the first keyframe the value is the first one's, after the last
+/// it stays at the last one's.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Track {
+    keys: &'static [Keyframe],
+    easing: Easing,
+}
+
+impl Track {
+    /// `keys` must be in order of time
+    pub const fn new(keys: &'static [Keyframe], easing: Easing) -> Self {
+        Track { keys, easing }
+    }
+
+    /// The value at `t` milliseconds, 0 without any keyframes
+    pub fn sample(&self, t: u32) -> i32 {
+        let next = self.keys.iter().position(|key| key.at > t);
+        let (from, to) = match next {
+            None => return self.keys.last().map_or(0, |key| key.value),
+            Some(0) => return self.keys[0].value,
+            Some(next) => (self.keys[next - 1], self.keys[next]),
+        };
+        let progress = Progress::through(t - from.at, to.at - from.at).ease(self.easing);
+        from.value + progress.of(to.value - from.value)
+    }
+
+    /// The value at `t` as a share, keyframes are in thousandths
+    fn progress(&self, t: u32) -> Progress {
+        Progress::per_mille(self.sample(t).clamp(0, 1000) as u16)
+    }
+}
+
+/// Which way a wipe uncovers what's underneath
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+    TopToBottom,
+    BottomToTop,
+}
+
+impl Direction {
+    /// The part of `area` a wipe `progress` of the way through has uncovered
+    pub fn reveal(self, area: Rectangle, progress: Progress) -> Rectangle {
+        let Size { width, height } = area.size;
+        let (across, down) = (
+            progress.of(width as i32) as u32,
+            progress.of(height as i32) as u32,
+        );
+        let (offset, size) = match self {
+            Direction::LeftToRight => (Point::zero(), Size::new(across, height)),
+            Direction::RightToLeft => (
+                Point::new((width - across) as i32, 0),
+                Size::new(across, height),
+            ),
+            Direction::TopToBottom => (Point::zero(), Size::new(width, down)),
+            Direction::BottomToTop => (
+                Point::new(0, (height - down) as i32),
+                Size::new(width, down),
+            ),
+        };
+        Rectangle::new(area.top_left + offset, size)
+    }
+}
+
+/// What a [`Cue`] shows
+#[derive(Copy, Clone)]
+pub enum Content {
+    /// An image at its own offset
+    Image(&'static PackedImage),
+    /// Images one after the other, each for `frame_time` milliseconds, then
+    /// round again
+    Sprite {
+        frames: &'static [&'static PackedImage],
+        frame_time: u32,
+    },
+    /// `count` bars across `area`, each taller than the last, like the bar
+    /// graph tachometer of the digital dash
+    Bars { area: Rectangle, count: u32 },
+}
+
+impl Content {
+    fn sprite_frame(
+        frames: &'static [&'static PackedImage],
+        frame_time: u32,
+        t: u32,
+    ) -> Option<&'static PackedImage> {
+        let frame = t / frame_time.max(1);
+        frames.get(frame as usize % frames.len().max(1)).copied()
+    }
+
+    /// Where the content is `t` milliseconds into its cue
+    pub fn area(&self, t: u32) -> Rectangle {
+        let image = match *self {
+            Content::Image(image) => image,
+            Content::Sprite { frames, frame_time } => {
+                match Content::sprite_frame(frames, frame_time, t) {
+                    Some(image) => image,
+                    None => return Rectangle::zero(),
+                }
+            }
+            Content::Bars { area, .. } => return area,
+        };
+        Rectangle::new(image.offset(), image.size())
+    }
+
+    fn draw<D: BinaryTarget>(&self, t: u32, display: &mut D) {
+        match *self {
+            Content::Image(image) => _ = image.draw(display),
+            Content::Sprite { frames, frame_time } => {
+                if let Some(image) = Content::sprite_frame(frames, frame_time, t) {
+                    _ = image.draw(display);
+                }
+            }
+            Content::Bars { area, count } => {
+                let count = count.max(1);
+                let pitch = area.size.width / count;
+                // a gap between bars, unless they're too thin for one
+                let width = if pitch > 2 { pitch - 2 } else { pitch };
+                for bar in 0..count {
+                    let height = area.size.height * (bar + 1) / count;
+                    let top_left = area.top_left
+                        + Point::new((bar * pitch) as i32, (area.size.height - height) as i32);
+                    let bar = Rectangle::new(top_left, Size::new(width, height));
+                    _ = display.fill_solid(&bar, BinaryColor::On);
+                }
+            }
+        }
+    }
+}
+
+/// How a [`Cue`] moves
+#[derive(Copy, Clone, Debug)]
+pub enum Motion {
+    /// Stays where it is
+    Still,
+    /// Uncovered in `direction`, `shown` is the share uncovered in
+    /// thousandths
+    Wipe { direction: Direction, shown: Track },
+    /// Moved by `x` and `y` pixels
+    Slide { x: Track, y: Track },
+}
+
+/// Some content shown for part of a [`Sequence`]
+#[derive(Copy, Clone)]
+pub struct Cue {
+    /// First millisecond of the sequence the content is shown
+    pub start: u32,
+    /// Last millisecond it's shown
+    pub end: u32,
+    pub content: Content,
+    /// Keyframe times are from `start`
+    pub motion: Motion,
+}
+
+impl Cue {
+    fn draw<D: BinaryTarget>(&self, t: u32, display: &mut D) {
+        match self.motion {
+            Motion::Still => self.content.draw(t, display),
+            Motion::Wipe { direction, shown } => {
+                let area = direction.reveal(self.content.area(t), shown.progress(t));
+                if !area.is_zero_sized() {
+                    self.content.draw(t, &mut display.clip(area));
+                }
+            }
+            Motion::Slide { x, y } => {
+                let offset = Point::new(x.sample(t), y.sample(t));
+                self.content.draw(t, &mut display.translate(offset));
+            }
+        }
+    }
+}
+
+/// A keyframed animation, see the [module docs](self)
+#[derive(Copy, Clone)]
+pub struct Sequence {
+    /// Milliseconds from the first frame to the last
+    pub length: u32,
+    /// Drawn in order, later cues on top of earlier ones
+    pub cues: &'static [Cue],
+    /// Share of the display's brightness, in thousandths
+    pub brightness: Track,
+}
+
+impl Sequence {
+    /// Draw the frame `t` milliseconds in, clearing the display first
+    pub fn draw<D: BinaryTarget>(&self, t: u32, display: &mut D) {
+        _ = display.clear(BinaryColor::Off);
+        for cue in self.cues {
+            if (cue.start..=cue.end).contains(&t) {
+                cue.draw(t - cue.start, display);
+            }
+        }
+    }
+
+    /// How bright the display should be `t` milliseconds in
+    pub fn brightness(&self, t: u32) -> Progress {
+        self.brightness.progress(t)
+    }
+}
+
+/// When to draw the next frame, from [`FramePacer::advance`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NextFrame {
+    /// Milliseconds from the start of the animation
+    pub at: u32,
+    /// The last frame ran over and frames were dropped, the next one is due
+    /// straight away
+    pub late: bool,
+}
+
+/// Keeps an animation to time when frames take too long.
+///
+/// Frames are drawn for times a whole number of frame times from the start,
+/// one each tick. When drawing and sending a frame runs past the start of
+/// the next one's slot, the slots it ran into are dropped and the next frame
+/// is for the time it's now got to.
+#[derive(Clone, Debug)]
+pub struct FramePacer {
+    frame_time: u32,
+    /// Time of the frame last drawn
+    at: u32,
+    dropped: u32,
+}
+
+impl FramePacer {
+    pub const fn new(frame_time: u32) -> Self {
+        FramePacer {
+            frame_time,
+            at: 0,
+            dropped: 0,
+        }
+    }
+
+    /// The frame to draw now that the last one is out, `now` milliseconds
+    /// from the start
+    pub fn advance(&mut self, now: u32) -> NextFrame {
+        let frame_time = self.frame_time.max(1);
+        let next = self.at + frame_time;
+        if now <= next {
+            self.at = next;
+            return NextFrame {
+                at: next,
+                late: false,
+            };
+        }
+        let at = now.div_ceil(frame_time) * frame_time;
+        self.dropped += (at - next) / frame_time;
+        self.at = at;
+        NextFrame { at, late: true }
+    }
+
+    /// Frames skipped so far for running over
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+/// Something drawn whole onto a cleared display, e.g. a page
+pub trait Scene {
+    fn draw<D: BinaryTarget>(&self, display: &mut D);
+}
+
+/// How to get from one page to the next
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Transition {
+    /// Swap straight to the new page
+    #[default]
+    Cut,
+    /// Push the old page out to the left
+    SlideLeft,
+    /// Push the old page out to the right
+    SlideRight,
+    /// Draw the new page over the old one from left to right, behind a lit
+    /// edge
+    Wipe,
+    /// Dim the old page out and the new one in
+    Fade,
+}
+
+impl Transition {
+    /// Milliseconds from start to finish
+    pub const fn duration(self) -> u32 {
+        match self {
+            Transition::Cut => 0,
+            Transition::SlideLeft | Transition::SlideRight => 160,
+            Transition::Wipe => 240,
+            Transition::Fade => 320,
+        }
+    }
+}
+
+/// Draw `from` turning into `to`, `progress` of the way through
+/// `transition`, on a cleared display.
+///
+/// Returns how bright the display should be as a share of its brightness,
+/// which is only ever less than full while fading.
+pub fn draw_transition<F: Scene, T: Scene, D: BinaryTarget>(
+    transition: Transition,
+    progress: Progress,
+    from: &F,
+    to: &T,
+    display: &mut D,
+) -> Progress {
+    let size = display.bounding_box().size;
+    let screen = Rectangle::new(Point::zero(), size);
+    match transition {
+        Transition::Cut => to.draw(display),
+        Transition::SlideLeft | Transition::SlideRight => {
+            let dir = if transition == Transition::SlideLeft {
+                -1
+            } else {
+                1
+            };
+            let width = size.width as i32;
+            let shift = progress.ease(Easing::InOut).of(width) * dir;
+            // each page in a screen sized viewport, so neither draws over the other
+            let from_area = Rectangle::new(Point::new(shift, 0), size);
+            from.draw(&mut display.viewport(from_area));
+            let to_area = Rectangle::new(Point::new(shift - dir * width, 0), size);
+            to.draw(&mut display.viewport(to_area));
+        }
+        Transition::Wipe => {
+            let shown = Direction::LeftToRight.reveal(screen, progress);
+            from.draw(display);
+            _ = display.fill_solid(&shown, BinaryColor::Off);
+            to.draw(&mut display.clip(shown));
+            if shown.size.width > 0 && shown.size.width < size.width {
+                let edge = Rectangle::new(
+                    Point::new(shown.size.width as i32, 0),
+                    Size::new(1, size.height),
+                );
+                _ = display.fill_solid(&edge, BinaryColor::On);
+            }
+        }
+        Transition::Fade => {
+            // out by half way, then back in on the new page
+            let half = Progress::FULL.get() / 2;
+            return if progress.get() < half {
+                from.draw(display);
+                Progress::per_mille(Progress::FULL.get() - progress.get() * 2)
+            } else {
+                to.draw(display);
+                Progress::per_mille((progress.get() - half) * 2)
+            };
+        }
+    }
+    Progress::FULL
+}
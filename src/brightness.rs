@@ -0,0 +1,220 @@
+//! VFD brightness that follows the car's lighting.
+//!
+//! Two inputs decide how bright the display should be:
+//! - the dash illumination (dimmer) wire, which only carries a voltage with
+//!   the lights on and gets lower as the dimmer knob is turned down
+//! - a photodiode looking at the cabin
+//!
+//! In daylight the level follows the photodiode. With the lights on in the
+//! dark it follows the dimmer knob on a curve that spends most of its travel
+//! at the low end, where the eye is most sensitive. The user offset from the
+//! settings menu is added on top, and the display fades towards the result
+//! instead of jumping.
+
+/// Highest level `EEIDisplay::set_brightness` accepts
+pub const MAX_LEVEL: u16 = 1023;
+/// Never go fully dark, the display would look dead
+pub const MIN_LEVEL: u16 = 8;
+
+/// Full scale of the ADC readings
+pub const ADC_MAX: u16 = 4095;
+/// Dimmer readings above this mean the lights are on
+pub const LIGHTS_ON_THRESHOLD: u16 = 400;
+/// Photodiode readings above this are daylight, even with the lights on
+pub const DAYLIGHT_THRESHOLD: u16 = 2000;
+
+/// Level range used in daylight
+pub const DAY_MIN: u16 = 384;
+pub const DAY_MAX: u16 = MAX_LEVEL;
+/// Level range covered by the dimmer knob at night
+pub const NIGHT_MIN: u16 = 16;
+pub const NIGHT_MAX: u16 = 512;
+
+/// Each fade step covers 1/FADE_DIVISOR of the remaining distance
+pub const FADE_DIVISOR: u16 = 8;
+/// Weight of a new photodiode sample in the running average, out of 16
+const AMBIENT_WEIGHT: u32 = 4;
+
+/// Raw readings from the light inputs
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LightInputs {
+    /// Dash illumination wire, 0 to [`ADC_MAX`], 0 with the lights off
+    pub dimmer: u16,
+    /// Photodiode, 0 (dark) to [`ADC_MAX`] (bright)
+    pub ambient: u16,
+}
+
+impl LightInputs {
+    pub fn lights_on(&self) -> bool {
+        self.dimmer > LIGHTS_ON_THRESHOLD
+    }
+
+    /// Lights on and dark outside
+    pub fn night(&self) -> bool {
+        self.lights_on() && self.ambient <= DAYLIGHT_THRESHOLD
+    }
+}
+
+/// Anything whose brightness can be set, in the 10-bit VFD range
+pub trait Dimmable {
+    type Error;
+
+    fn set_brightness(&mut self, level: u16) -> Result<(), Self::Error>;
+}
+
+/// Daylight level for a photodiode reading, linear from [`DAY_MIN`] to [`DAY_MAX`]
+pub fn day_curve(ambient: u16) -> u16 {
+    let ambient = u32::from(ambient.min(ADC_MAX));
+    let span = u32::from(DAY_MAX - DAY_MIN);
+    DAY_MIN + (span * ambient / u32::from(ADC_MAX)) as u16
+}
+
+/// Night level for a dimmer reading.
+///
+/// Quadratic from [`NIGHT_MIN`] at the threshold to [`NIGHT_MAX`] at full
+/// scale, so the bottom half of the knob covers the bottom quarter of levels.
+pub fn night_curve(dimmer: u16) -> u16 {
+    let range = u64::from(ADC_MAX - LIGHTS_ON_THRESHOLD);
+    let t = u64::from(dimmer.clamp(LIGHTS_ON_THRESHOLD, ADC_MAX) - LIGHTS_ON_THRESHOLD);
+    let span = u64::from(NIGHT_MAX - NIGHT_MIN);
+    NIGHT_MIN + (span * t * t / (range * range)) as u16
+}
+
+/// Level to aim for given the inputs and the user offset
+pub fn target_level(inputs: LightInputs, offset: i16) -> u16 {
+    let base = if inputs.night() {
+        night_curve(inputs.dimmer)
+    } else {
+        day_curve(inputs.ambient)
+    };
+    (i32::from(base) + i32::from(offset)).clamp(MIN_LEVEL.into(), MAX_LEVEL.into()) as u16
+}
+
+pub struct BrightnessController {
+    level: u16,
+    target: u16,
+    offset: i16,
+    /// Cap on the target, for dimming when idle
+    limit: Option<u16>,
+    /// Target worked out from the inputs, before the limit
+    wanted: u16,
+    /// Running average of the photodiode, `None` until the first reading
+    ambient: Option<u16>,
+}
+
+impl BrightnessController {
+    /// Start at `level` with nothing to fade to
+    pub fn new(level: u16) -> Self {
+        let level = level.min(MAX_LEVEL);
+        BrightnessController {
+            level,
+            target: level,
+            offset: 0,
+            limit: None,
+            wanted: level,
+            ambient: None,
+        }
+    }
+
+    /// Level last handed to the display
+    pub fn level(&self) -> u16 {
+        self.level
+    }
+
+    pub fn target(&self) -> u16 {
+        self.target
+    }
+
+    pub fn offset(&self) -> i16 {
+        self.offset
+    }
+
+    /// User adjustment added to whatever the inputs call for.
+    ///
+    /// Takes effect on the next [`update`](Self::update).
+    pub fn set_offset(&mut self, offset: i16) {
+        self.offset = offset;
+    }
+
+    /// Fade to a fixed level, ignoring the inputs until the next update
+    pub fn set_target(&mut self, level: u16) {
+        self.wanted = level.min(MAX_LEVEL);
+        self.apply_limit();
+    }
+
+    /// Jump straight to `level` without fading
+    pub fn jump_to(&mut self, level: u16) {
+        self.set_target(level);
+        self.level = self.target;
+    }
+
+    pub fn limit(&self) -> Option<u16> {
+        self.limit
+    }
+
+    /// Keep the target at or below `limit`, which may go under
+    /// [`MIN_LEVEL`] down to fully dark. `None` lifts the limit.
+    pub fn set_limit(&mut self, limit: Option<u16>) {
+        self.limit = limit;
+        self.apply_limit();
+    }
+
+    fn apply_limit(&mut self) {
+        self.target = match self.limit {
+            Some(limit) => self.wanted.min(limit),
+            None => self.wanted,
+        };
+    }
+
+    /// Work out a new target from fresh readings.
+    ///
+    /// The photodiode is averaged over several readings so passing shadows
+    /// don't make the display flicker.
+    pub fn update(&mut self, inputs: LightInputs) {
+        let ambient = match self.ambient {
+            None => inputs.ambient,
+            Some(avg) => {
+                ((u32::from(avg) * (16 - AMBIENT_WEIGHT)
+                    + u32::from(inputs.ambient) * AMBIENT_WEIGHT)
+                    / 16) as u16
+            }
+        };
+        self.ambient = Some(ambient);
+
+        self.wanted = target_level(
+            LightInputs {
+                dimmer: inputs.dimmer,
+                ambient,
+            },
+            self.offset,
+        );
+        self.apply_limit();
+    }
+
+    /// Move one fade step towards the target.
+    ///
+    /// Returns the new level if it changed.
+    pub fn tick(&mut self) -> Option<u16> {
+        if self.level == self.target {
+            return None;
+        }
+        let diff = self.level.abs_diff(self.target);
+        let step = (diff / FADE_DIVISOR).max(1);
+        self.level = if self.level < self.target {
+            self.level + step
+        } else {
+            self.level - step
+        };
+        Some(self.level)
+    }
+
+    /// [`tick`](Self::tick) and send any change to the display.
+    ///
+    /// Returns true while still fading.
+    pub fn apply<D: Dimmable>(&mut self, display: &mut D) -> Result<bool, D::Error> {
+        if let Some(level) = self.tick() {
+            display.set_brightness(level)?;
+        }
+        Ok(self.level != self.target)
+    }
+}
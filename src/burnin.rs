@@ -0,0 +1,168 @@
+//! Burn-in protection for the VFD.
+//!
+//! The climate page is mostly the same static background, which would
+//! slowly wear its outline into the phosphor. Three things spread the wear:
+//! - the whole layout is shifted by a pixel or so every few minutes
+//! - after a while without button presses the display dims, and later
+//!   switches to a screen saver that keeps moving
+//! - with the car's lights off and nobody touching anything, the display is
+//!   blanked completely
+//!
+//! Times are plain seconds from any monotonic clock, so this can be driven
+//! from `embassy_time::Instant` on the car and from made up numbers in tests.
+
+use embedded_graphics::prelude::{Point, Size};
+
+/// Offsets the layout walks through, one step per shift period.
+///
+/// A small loop around the home position, so nothing moves by more than one
+/// pixel and no step is a big jump from the last.
+pub const SHIFT_PATTERN: [Point; 8] = [
+    Point::new(0, 0),
+    Point::new(1, 0),
+    Point::new(1, 1),
+    Point::new(0, 1),
+    Point::new(-1, 1),
+    Point::new(-1, 0),
+    Point::new(-1, -1),
+    Point::new(0, -1),
+];
+
+/// How far the screen has gone towards sleep
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IdleState {
+    /// Normal display
+    Active,
+    /// Normal display at reduced brightness
+    Dimmed,
+    /// Moving screen saver instead of the pages
+    Saver,
+    /// Nothing lit at all
+    Blank,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BurnInConfig {
+    /// Seconds between layout shifts, 0 disables shifting
+    pub shift_period: u32,
+    /// Idle seconds before dimming
+    pub dim_after: u32,
+    /// Idle seconds before the screen saver
+    pub saver_after: u32,
+    /// Idle seconds before blanking, only while the car's lights are off
+    pub blank_after: u32,
+    /// Brightness cap while dimmed or showing the screen saver
+    pub dim_level: u16,
+}
+
+impl Default for BurnInConfig {
+    fn default() -> Self {
+        BurnInConfig {
+            shift_period: 120,
+            dim_after: 5 * 60,
+            saver_after: 15 * 60,
+            blank_after: 10 * 60,
+            dim_level: 64,
+        }
+    }
+}
+
+pub struct BurnInGuard {
+    config: BurnInConfig,
+    last_input: u32,
+    state: IdleState,
+}
+
+impl BurnInGuard {
+    pub fn new(config: BurnInConfig, now: u32) -> Self {
+        BurnInGuard {
+            config,
+            last_input: now,
+            state: IdleState::Active,
+        }
+    }
+
+    pub fn config(&self) -> &BurnInConfig {
+        &self.config
+    }
+
+    pub fn state(&self) -> IdleState {
+        self.state
+    }
+
+    /// Seconds since the last input
+    pub fn idle_for(&self, now: u32) -> u32 {
+        now.saturating_sub(self.last_input)
+    }
+
+    /// Someone pressed a button, wake up fully.
+    ///
+    /// Returns true if the screen had been dimmed or replaced and needs
+    /// redrawing.
+    pub fn input(&mut self, now: u32) -> bool {
+        self.last_input = now;
+        let woke = self.state != IdleState::Active;
+        self.state = IdleState::Active;
+        woke
+    }
+
+    /// Work out the idle state for `now`.
+    ///
+    /// Returns the new state if it changed.
+    pub fn update(&mut self, now: u32, lights_on: bool) -> Option<IdleState> {
+        let idle = self.idle_for(now);
+        let config = &self.config;
+        let state = if !lights_on && idle >= config.blank_after {
+            IdleState::Blank
+        } else if idle >= config.saver_after {
+            IdleState::Saver
+        } else if idle >= config.dim_after {
+            IdleState::Dimmed
+        } else {
+            IdleState::Active
+        };
+
+        if state == self.state {
+            return None;
+        }
+        self.state = state;
+        Some(state)
+    }
+
+    /// Highest brightness allowed in the current state, `None` for no limit
+    pub fn brightness_limit(&self) -> Option<u16> {
+        match self.state {
+            IdleState::Active => None,
+            IdleState::Dimmed | IdleState::Saver => Some(self.config.dim_level),
+            IdleState::Blank => Some(0),
+        }
+    }
+
+    /// Offset to draw the layout at, at time `now`
+    pub fn shift(&self, now: u32) -> Point {
+        if self.config.shift_period == 0 {
+            return Point::zero();
+        }
+        let step = now / self.config.shift_period;
+        SHIFT_PATTERN[step as usize % SHIFT_PATTERN.len()]
+    }
+
+    /// Where to put an item of `size` on the screen saver at time `now`.
+    ///
+    /// The item bounces around the `area` at one pixel per second across and
+    /// a little slower down, so it crosses every part of the screen.
+    pub fn saver_position(&self, now: u32, size: Size, area: Size) -> Point {
+        let x = bounce(now, area.width.saturating_sub(size.width));
+        let y = bounce(now / 3, area.height.saturating_sub(size.height));
+        Point::new(x as i32, y as i32)
+    }
+}
+
+/// Triangle wave from 0 up to `max` and back down, one step per `t`
+fn bounce(t: u32, max: u32) -> u32 {
+    if max == 0 {
+        return 0;
+    }
+    let phase = t % (2 * max);
+    if phase <= max { phase } else { 2 * max - phase }
+}
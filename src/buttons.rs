@@ -0,0 +1,103 @@
+//! The climate panel's buttons and the events they make.
+//!
+//! Kept apart from [`crate::digidisplay`], which scans them, so the screens
+//! and the settings menu don't depend on the hardware.
+
+use core::sync::atomic::AtomicBool;
+
+use bitflags::bitflags;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Button {
+    Auto,
+    Demist,
+    TempUp,
+    Off,
+    FanLo,
+    FanHigh,
+    Recirc,
+    TempDown,
+}
+
+bitflags! {
+    /// Set of buttons held down at the same time
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct ButtonSet: u8 {
+        const AUTO = 0x01;
+        const DEMIST = 0x02;
+        const TEMP_UP = 0x04;
+        const OFF = 0x08;
+        const FAN_LO = 0x10;
+        const FAN_HIGH = 0x20;
+        const RECIRC = 0x40;
+        const TEMP_DOWN = 0x80;
+    }
+}
+
+impl From<Button> for ButtonSet {
+    fn from(button: Button) -> Self {
+        match button {
+            Button::Auto => ButtonSet::AUTO,
+            Button::Demist => ButtonSet::DEMIST,
+            Button::TempUp => ButtonSet::TEMP_UP,
+            Button::Off => ButtonSet::OFF,
+            Button::FanLo => ButtonSet::FAN_LO,
+            Button::FanHigh => ButtonSet::FAN_HIGH,
+            Button::Recirc => ButtonSet::RECIRC,
+            Button::TempDown => ButtonSet::TEMP_DOWN,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ButtonEvent {
+    /// A button was pressed, or is repeating while held
+    Press(Button),
+    /// Everything held down, sent on each repeat while a button is held and
+    /// once more when it is let go
+    Held(ButtonSet),
+    /// A chord was held for long enough, nothing more comes until every
+    /// button has been let go
+    Chord(ButtonSet),
+}
+
+/// While set, button presses only go to the screens and leave the climate control alone
+pub static BUTTONS_CAPTURED: AtomicBool = AtomicBool::new(false);
+
+/// Every button, in [`ButtonSet`] bit order
+pub struct ButtonIter {
+    next: Option<Button>,
+}
+
+impl ButtonIter {
+    pub fn new() -> Self {
+        ButtonIter {
+            next: Some(Button::Auto),
+        }
+    }
+}
+
+impl Default for ButtonIter {
+    fn default() -> Self {
+        ButtonIter::new()
+    }
+}
+
+impl Iterator for ButtonIter {
+    type Item = Button;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let curr = self.next.take()?;
+        self.next = match curr {
+            Button::Auto => Some(Button::Demist),
+            Button::Demist => Some(Button::TempUp),
+            Button::TempUp => Some(Button::Off),
+            Button::Off => Some(Button::FanLo),
+            Button::FanLo => Some(Button::FanHigh),
+            Button::FanHigh => Some(Button::Recirc),
+            Button::Recirc => Some(Button::TempDown),
+            Button::TempDown => None,
+        };
+        Some(curr)
+    }
+}
@@ -1,4 +1,6 @@
-#[derive(Default)]
+use crate::settings::Aggressiveness;
+
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
 pub enum ClimateControlMode {
     Face,
     Feet,
@@ -7,7 +9,7 @@ pub enum ClimateControlMode {
     #[default]
     Def,
 }
-#[derive(Default)]
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
 pub struct ClimateControlBacker {
     mode: ClimateControlMode,
     ac_toggle: bool,
@@ -16,6 +18,7 @@ pub struct ClimateControlBacker {
     ambient_temp: i8,
     set_temp: i8,
     displaymode: bool,
+    auto: bool,
 }
 
 #[allow(unused)]
@@ -28,6 +31,7 @@ impl ClimateControlBacker {
         let mut ambient_temp: i8 = 50;
         let mut set_temp: i8 = 50;
         let mut displaymode: bool = false;
+        let mut auto = false;
         let cc = ClimateControlBacker {
             mode,
             ac_toggle,
@@ -36,6 +40,7 @@ impl ClimateControlBacker {
             ambient_temp,
             set_temp,
             displaymode,
+            auto,
         };
         cc
     }
@@ -74,14 +79,46 @@ impl ClimateControlBacker {
         self.recirc_toggle = !self.recirc_toggle;
     }
 
-    pub fn fan_speed(&mut self) -> u8 {
+    pub fn fan_speed(&self) -> u8 {
         self.fan_speed
     }
 
+    /// A fan speed picked by hand, which ends auto mode
     pub fn set_fan_speed(&mut self, fan_speed: u8) {
+        self.auto = false;
         self.fan_speed = fan_speed;
     }
 
+    pub fn auto(&self) -> bool {
+        self.auto
+    }
+
+    /// Auto mode runs the AC along with the fan
+    pub fn set_auto_toggle(&mut self) {
+        self.auto = !self.auto;
+        self.ac_toggle = self.auto;
+    }
+
+    /// In auto mode, pick the fan speed for how far the cabin is from the
+    /// set temperature
+    pub fn run_auto(&mut self, aggressiveness: Aggressiveness) {
+        if !self.auto {
+            return;
+        }
+        // degrees off the set temperature for each step up in fan speed
+        let step = match aggressiveness {
+            Aggressiveness::Low => 4,
+            Aggressiveness::Normal => 2,
+            Aggressiveness::High => 1,
+        };
+        let gap = (i16::from(self.set_temp) - i16::from(self.ambient_temp)).unsigned_abs();
+        self.fan_speed = match gap / step {
+            0 => 0,
+            1 => 50,
+            _ => 100,
+        };
+    }
+
     pub fn ambient_temp(&self) -> i8 {
         self.ambient_temp
     }
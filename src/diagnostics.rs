@@ -0,0 +1,529 @@
+//! What the diagnostics page shows: every sensor as the ADC sees it, the
+//! button matrix and the level of every output pin.
+//!
+//! The sensor code, the button reader and the LED writer each record what
+//! they last saw into [`DIAGNOSTICS`], and the page draws a [`Snapshot`] of
+//! it a few times a second. Everything is kept in atomics so recording
+//! never waits on the VFD, a snapshot taken half way through a recording
+//! can mix two readings of a sensor, which doesn't matter for looking at.
+//!
+//! The page is split into [`Section`]s, one screenful each, because a row
+//! per sensor, button and output doesn't fit in 48 pixels.
+
+use alloc::format;
+use alloc::string::String;
+use core::sync::atomic::{AtomicI32, AtomicU8, AtomicU16, AtomicU32, Ordering};
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::mono_font::ascii::FONT_5X8;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+
+use crate::climatecontrol::{ClimateControlBacker, ClimateControlMode};
+use crate::vfdgraphics::BinaryTarget;
+
+/// Full scale of the 12 bit ADC
+pub const ADC_MAX: u16 = 4095;
+
+/// Counts from either rail a divider has to be within to count as open or
+/// shorted
+pub const RAIL_MARGIN: u16 = 16;
+
+/// Milliseconds between redraws of the diagnostics page, so values change
+/// without pressing anything
+pub const REFRESH_INTERVAL: u64 = 250;
+
+/// Readings the onboard sensor can't give, -40 to 125 °C in tenths of a °F
+const ONBOARD_RANGE: (i32, i32) = (-400, 2570);
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Fault {
+    #[default]
+    None,
+    /// Reading at the top rail, the sensor is unplugged or a wire is broken
+    Open,
+    /// Reading at the bottom rail
+    Short,
+    /// Outside of what the sensor can measure
+    Range,
+}
+
+impl Fault {
+    pub fn label(self) -> &'static str {
+        match self {
+            Fault::None => "OK",
+            Fault::Open => "OPEN",
+            Fault::Short => "SHORT",
+            Fault::Range => "RANGE",
+        }
+    }
+
+    fn from_bits(bits: u8) -> Self {
+        match bits {
+            1 => Fault::Open,
+            2 => Fault::Short,
+            3 => Fault::Range,
+            _ => Fault::None,
+        }
+    }
+
+    fn bits(self) -> u8 {
+        match self {
+            Fault::None => 0,
+            Fault::Open => 1,
+            Fault::Short => 2,
+            Fault::Range => 3,
+        }
+    }
+}
+
+/// Fault of a sensor at the bottom of a divider with a pull-up, going by its
+/// averaged reading
+pub fn divider_fault(filtered: u16) -> Fault {
+    if filtered >= ADC_MAX - RAIL_MARGIN {
+        Fault::Open
+    } else if filtered <= RAIL_MARGIN {
+        Fault::Short
+    } else {
+        Fault::None
+    }
+}
+
+/// Fault of a value that should be between `min` and `max`, inclusive
+pub fn range_fault(value: i32, (min, max): (i32, i32)) -> Fault {
+    if (min..=max).contains(&value) {
+        Fault::None
+    } else {
+        Fault::Range
+    }
+}
+
+/// Fault of the onboard temperature sensor, `tenths` of a °F
+pub fn onboard_fault(tenths: i32) -> Fault {
+    range_fault(tenths, ONBOARD_RANGE)
+}
+
+/// Share of full scale, in tenths of a percent
+pub fn percent_tenths(filtered: u16) -> i32 {
+    (filtered as i32 * 1000 + ADC_MAX as i32 / 2) / ADC_MAX as i32
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sensor {
+    /// The cabin thermistor
+    Ambient,
+    /// The RP2350's own temperature sensor
+    Onboard,
+    /// The dash illumination wire
+    Dimmer,
+    /// The cabin photodiode
+    Photodiode,
+}
+
+impl Sensor {
+    pub const ALL: [Sensor; 4] = [
+        Sensor::Ambient,
+        Sensor::Onboard,
+        Sensor::Dimmer,
+        Sensor::Photodiode,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Sensor::Ambient => "AMBIENT",
+            Sensor::Onboard => "ONBOARD",
+            Sensor::Dimmer => "DIMMER",
+            Sensor::Photodiode => "PHOTO",
+        }
+    }
+
+    /// Unit of [`Reading::tenths`]
+    pub fn unit(self) -> &'static str {
+        match self {
+            Sensor::Ambient | Sensor::Onboard => "F",
+            Sensor::Dimmer | Sensor::Photodiode => "%",
+        }
+    }
+}
+
+/// One measurement of a sensor
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reading {
+    /// The last ADC sample, in counts
+    pub raw: u16,
+    /// The average of all the samples taken, in counts
+    pub filtered: u16,
+    /// Resistance worked out from `filtered`, for the sensors that have one
+    pub ohms: Option<u32>,
+    /// The value in the sensor's [`Sensor::unit`], in tenths
+    pub tenths: i32,
+    pub fault: Fault,
+}
+
+/// Outputs driven by [`crate::digidisplay::DigiDisplay`], the LEDs in the
+/// buttons are all lit low
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Output {
+    DemistLed,
+    AcLed,
+    EconLed,
+    DefrostLed,
+    FanHighLed,
+    FanLowLed,
+    RecircLed,
+}
+
+impl Output {
+    pub const ALL: [Output; 7] = [
+        Output::DemistLed,
+        Output::AcLed,
+        Output::EconLed,
+        Output::DefrostLed,
+        Output::FanHighLed,
+        Output::FanLowLed,
+        Output::RecircLed,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Output::DemistLed => "DEMIST",
+            Output::AcLed => "AC",
+            Output::EconLed => "ECON",
+            Output::DefrostLed => "DEFROST",
+            Output::FanHighLed => "FAN HI",
+            Output::FanLowLed => "FAN LO",
+            Output::RecircLed => "RECIRC",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Names of the buttons in [`crate::buttons::ButtonSet`] bit order
+pub const BUTTONS: [&str; 8] = [
+    "AUTO", "DEMIST", "TEMP UP", "OFF", "FAN LO", "FAN HI", "RECIRC", "TEMP DN",
+];
+
+/// Flags in [`SensorSlot::state`] next to the fault
+const RECORDED: u8 = 0x80;
+const HAS_OHMS: u8 = 0x40;
+
+/// A [`Reading`] in atomics
+struct SensorSlot {
+    raw: AtomicU16,
+    filtered: AtomicU16,
+    ohms: AtomicU32,
+    tenths: AtomicI32,
+    /// [`RECORDED`] once there is a reading, [`HAS_OHMS`] and the fault
+    state: AtomicU8,
+}
+
+impl SensorSlot {
+    const fn new() -> Self {
+        SensorSlot {
+            raw: AtomicU16::new(0),
+            filtered: AtomicU16::new(0),
+            ohms: AtomicU32::new(0),
+            tenths: AtomicI32::new(0),
+            state: AtomicU8::new(0),
+        }
+    }
+
+    fn store(&self, reading: Reading) {
+        self.raw.store(reading.raw, Ordering::Relaxed);
+        self.filtered.store(reading.filtered, Ordering::Relaxed);
+        self.ohms
+            .store(reading.ohms.unwrap_or(0), Ordering::Relaxed);
+        self.tenths.store(reading.tenths, Ordering::Relaxed);
+        let ohms = if reading.ohms.is_some() { HAS_OHMS } else { 0 };
+        self.state
+            .store(RECORDED | ohms | reading.fault.bits(), Ordering::Relaxed);
+    }
+
+    fn load(&self) -> Option<Reading> {
+        let state = self.state.load(Ordering::Relaxed);
+        if state & RECORDED == 0 {
+            return None;
+        }
+        Some(Reading {
+            raw: self.raw.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+            ohms: (state & HAS_OHMS != 0).then(|| self.ohms.load(Ordering::Relaxed)),
+            tenths: self.tenths.load(Ordering::Relaxed),
+            fault: Fault::from_bits(state & !(RECORDED | HAS_OHMS)),
+        })
+    }
+}
+
+/// The latest of everything the diagnostics page shows, see [`DIAGNOSTICS`]
+pub struct Diagnostics {
+    sensors: [SensorSlot; Sensor::ALL.len()],
+    buttons: AtomicU8,
+    outputs: AtomicU8,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Diagnostics::new()
+    }
+}
+
+impl Diagnostics {
+    pub const fn new() -> Self {
+        Diagnostics {
+            sensors: [const { SensorSlot::new() }; Sensor::ALL.len()],
+            buttons: AtomicU8::new(0),
+            outputs: AtomicU8::new(0),
+        }
+    }
+
+    pub fn record(&self, sensor: Sensor, reading: Reading) {
+        self.sensors[sensor as usize].store(reading);
+    }
+
+    /// Buttons down as [`crate::buttons::ButtonSet`] bits
+    pub fn set_buttons(&self, bits: u8) {
+        self.buttons.store(bits, Ordering::Relaxed);
+    }
+
+    /// Level the pin of `output` is driven to
+    pub fn set_output(&self, output: Output, high: bool) {
+        if high {
+            self.outputs.fetch_or(output.bit(), Ordering::Relaxed);
+        } else {
+            self.outputs.fetch_and(!output.bit(), Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            sensors: Sensor::ALL.map(|sensor| self.sensors[sensor as usize].load()),
+            buttons: self.buttons.load(Ordering::Relaxed),
+            outputs: self.outputs.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Where everything records what it saw for the diagnostics page
+pub static DIAGNOSTICS: Diagnostics = Diagnostics::new();
+
+/// [`Diagnostics`] at one moment
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// In [`Sensor::ALL`] order, `None` until a sensor is first read
+    pub sensors: [Option<Reading>; Sensor::ALL.len()],
+    /// Buttons down, in [`crate::buttons::ButtonSet`] bits
+    pub buttons: u8,
+    /// Output pins driven high, a bit for each [`Output`]
+    pub outputs: u8,
+}
+
+impl Snapshot {
+    pub fn reading(&self, sensor: Sensor) -> Option<Reading> {
+        self.sensors[sensor as usize]
+    }
+
+    /// Whether the button `index` in [`BUTTONS`] is down
+    pub fn pressed(&self, index: usize) -> bool {
+        self.buttons & (1 << index) != 0
+    }
+
+    pub fn output_high(&self, output: Output) -> bool {
+        self.outputs & output.bit() != 0
+    }
+}
+
+/// One screenful of the diagnostics page
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Section {
+    #[default]
+    Sensors,
+    Buttons,
+    Outputs,
+    /// What the climate control has been told to do
+    Control,
+}
+
+impl Section {
+    pub const ALL: [Section; 4] = [
+        Section::Sensors,
+        Section::Buttons,
+        Section::Outputs,
+        Section::Control,
+    ];
+
+    pub fn next(self) -> Section {
+        Section::ALL[(self as usize + 1) % Section::ALL.len()]
+    }
+
+    pub fn prev(self) -> Section {
+        Section::ALL[(self as usize + Section::ALL.len() - 1) % Section::ALL.len()]
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Section::Sensors => "SENSORS",
+            Section::Buttons => "BUTTONS",
+            Section::Outputs => "OUTPUTS",
+            Section::Control => "CONTROL",
+        }
+    }
+}
+
+/// Small enough for a header and four rows
+const STYLE: MonoTextStyle<'static, BinaryColor> = MonoTextStyle::new(&FONT_5X8, BinaryColor::On);
+
+const ROW_HEIGHT: i32 = 9;
+
+/// Sensor table columns, the widths the values are padded to
+const SENSOR_COLUMNS: (usize, usize, usize, usize, usize) = (8, 5, 5, 7, 7);
+
+/// `tenths` as a decimal with one place
+pub fn format_tenths(tenths: i32) -> String {
+    let sign = if tenths < 0 { "-" } else { "" };
+    let tenths = tenths.unsigned_abs();
+    format!("{sign}{}.{}", tenths / 10, tenths % 10)
+}
+
+/// Resistance in four characters or so, kilohms from 10k up
+pub fn format_ohms(ohms: u32) -> String {
+    if ohms < 10_000 {
+        format!("{ohms}")
+    } else if ohms < 1_000_000 {
+        format!("{}.{}K", ohms / 1000, ohms / 100 % 10)
+    } else {
+        String::from(">1M")
+    }
+}
+
+fn mode_name(mode: &ClimateControlMode) -> &'static str {
+    match mode {
+        ClimateControlMode::Face => "FACE",
+        ClimateControlMode::Feet => "FEET",
+        ClimateControlMode::FaceFeet => "FACE+FEET",
+        ClimateControlMode::FeetDef => "FEET+DEF",
+        ClimateControlMode::Def => "DEF",
+    }
+}
+
+/// `text` with its top left corner in `row` and `x` along
+fn draw_text<D: BinaryTarget>(text: &str, x: i32, row: i32, display: &mut D) {
+    _ = Text::with_baseline(text, Point::new(x, row * ROW_HEIGHT), STYLE, Baseline::Top)
+        .draw(display);
+}
+
+fn sensor_row(
+    name: &str,
+    raw: &str,
+    filtered: &str,
+    ohms: &str,
+    value: &str,
+    fault: &str,
+) -> String {
+    let (n, r, f, o, v) = SENSOR_COLUMNS;
+    format!("{name:<n$}{raw:>r$}{filtered:>f$}{ohms:>o$}{value:>v$}  {fault}")
+}
+
+fn draw_sensors<D: BinaryTarget>(snapshot: &Snapshot, display: &mut D) {
+    let header = sensor_row("", "RAW", "AVG", "OHMS", "VALUE", "FAULT");
+    draw_text(&header, 0, 0, display);
+    for (row, sensor) in (1..).zip(Sensor::ALL) {
+        let line = match snapshot.reading(sensor) {
+            Some(reading) => sensor_row(
+                sensor.label(),
+                &format!("{}", reading.raw),
+                &format!("{}", reading.filtered),
+                &reading.ohms.map_or(String::from("-"), format_ohms),
+                &format!("{}{}", format_tenths(reading.tenths), sensor.unit()),
+                reading.fault.label(),
+            ),
+            None => sensor_row(sensor.label(), "-", "-", "-", "-", "NO DATA"),
+        };
+        draw_text(&line, 0, row, display);
+    }
+}
+
+/// Two columns of four, with a box that fills in while lit or pressed
+fn draw_lamps<D: BinaryTarget>(lamps: impl Iterator<Item = (&'static str, bool)>, display: &mut D) {
+    for (i, (label, lit)) in lamps.enumerate() {
+        let (column, row) = (i as i32 / 4, i as i32 % 4 + 1);
+        let x = column * 128;
+        let style = if lit {
+            PrimitiveStyle::with_fill(BinaryColor::On)
+        } else {
+            PrimitiveStyle::with_stroke(BinaryColor::On, 1)
+        };
+        _ = Rectangle::new(Point::new(x, row * ROW_HEIGHT), Size::new(7, 7))
+            .into_styled(style)
+            .draw(display);
+        draw_text(label, x + 11, row, display);
+    }
+}
+
+fn draw_buttons<D: BinaryTarget>(snapshot: &Snapshot, display: &mut D) {
+    let buttons = BUTTONS
+        .iter()
+        .enumerate()
+        .map(|(i, &label)| (label, snapshot.pressed(i)));
+    draw_lamps(buttons, display);
+}
+
+fn draw_outputs<D: BinaryTarget>(snapshot: &Snapshot, display: &mut D) {
+    draw_lamps(
+        Output::ALL
+            .into_iter()
+            .map(|output| (output.label(), !snapshot.output_high(output))),
+        display,
+    );
+    // the pin levels, since the boxes show what the LEDs do
+    for (i, output) in Output::ALL.into_iter().enumerate() {
+        let (column, row) = (i as i32 / 4, i as i32 % 4 + 1);
+        let level = if snapshot.output_high(output) {
+            "HIGH"
+        } else {
+            "LOW"
+        };
+        draw_text(level, column * 128 + 60, row, display);
+    }
+}
+
+fn draw_control<D: BinaryTarget>(backend: &ClimateControlBacker, display: &mut D) {
+    let rows = [
+        format!("MODE   {}", mode_name(backend.mode())),
+        format!("FAN    {}", backend.fan_speed()),
+        format!("AC     {}", u8::from(backend.ac_toggle())),
+        format!("RECIRC {}", u8::from(backend.recirc_toggle())),
+    ];
+    for (row, text) in (1..).zip(rows) {
+        draw_text(&text, 0, row, display);
+    }
+    draw_text(&format!("SET  {}", backend.set_temp()), 128, 1, display);
+    draw_text(&format!("AMB  {}", backend.ambient_temp()), 128, 2, display);
+}
+
+/// Draw one section of the diagnostics page, its title over the names on
+/// the left and its number in the top right corner
+pub fn draw_section<D: BinaryTarget>(
+    section: Section,
+    snapshot: &Snapshot,
+    backend: &ClimateControlBacker,
+    display: &mut D,
+) {
+    draw_text(section.title(), 0, 0, display);
+    let number = format!("{}/{}", section as usize + 1, Section::ALL.len());
+    let right = TextStyleBuilder::new()
+        .alignment(Alignment::Right)
+        .baseline(Baseline::Top)
+        .build();
+    _ = Text::with_text_style(&number, Point::new(255, 0), STYLE, right).draw(display);
+
+    match section {
+        Section::Sensors => draw_sensors(snapshot, display),
+        Section::Buttons => draw_buttons(snapshot, display),
+        Section::Outputs => draw_outputs(snapshot, display),
+        Section::Control => draw_control(backend, display),
+    }
+}
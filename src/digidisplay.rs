@@ -1,3 +1,5 @@
+use core::sync::atomic::Ordering;
+
 use bitflags::bitflags;
 use embassy_rp::{
     gpio::{Flex, Input, Level, Output, Pull},
@@ -6,13 +8,18 @@ use embassy_rp::{
 };
 use embassy_time::{Duration, Timer, block_for};
 
+pub use crate::buttons::{BUTTONS_CAPTURED, Button, ButtonEvent, ButtonIter, ButtonSet};
 use crate::{
     climatecontrol::{ClimateControlBacker, ClimateControlMode},
-    map_u8,
+    diagnostics::{self, DIAGNOSTICS},
+    map_i32,
+    segformat::{Glyph, Readout, SegFormat},
+    settings::{ChordGate, Config, Gated},
 };
 
 bitflags! {
     //      Statically driven side of display(through driver IC over I2C)
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub struct SegDisplayBits: u32{
         // ──     General indicators     ───────────────────────────────────────
         const FRESH_AIR = 0x0000_0001;
@@ -40,6 +47,7 @@ bitflags! {
     }
 
     //      Serially controlled side of display
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub struct SerialDisplayBits: u64 {
         // ── Ambient “second” digit (ones) ──────────────────────────────────────
         const AMB2_TL = 0x0000_0000_0000_0001; // top-left
@@ -479,40 +487,58 @@ impl SerialDisplayBits {
         }
     }
 
-    pub fn setup_amb(input: i8) -> SerialDisplayBits {
-        let mut base = SerialDisplayBits::EMPTY;
-        let mut n = input;
-        if n < 0 {
-            n = n * -1;
-            base = base | SerialDisplayBits::amb_neg(true);
-        }
-        if n >= 100 {
-            n = n - 100;
-            base = base | SerialDisplayBits::amb_hund(true);
-        }
-        let tens = n / 10;
-        let ones = n % 10;
-        base = base
-            | SerialDisplayBits::amb_first(tens.try_into().unwrap())
-            | SerialDisplayBits::amb_second(ones.try_into().unwrap());
-        return base;
+    /// Ambient tens segments in `Glyph::segments` bit order
+    const AMB1_SEGS: [SerialDisplayBits; 7] = [
+        SerialDisplayBits::AMB1_T,
+        SerialDisplayBits::AMB1_TR,
+        SerialDisplayBits::AMB1_BR,
+        SerialDisplayBits::AMB1_B,
+        SerialDisplayBits::AMB1_BL,
+        SerialDisplayBits::AMB1_TL,
+        SerialDisplayBits::AMB1_M,
+    ];
+    /// Ambient ones segments in `Glyph::segments` bit order
+    const AMB2_SEGS: [SerialDisplayBits; 7] = [
+        SerialDisplayBits::AMB2_T,
+        SerialDisplayBits::AMB2_TR,
+        SerialDisplayBits::AMB2_BR,
+        SerialDisplayBits::AMB2_B,
+        SerialDisplayBits::AMB2_BL,
+        SerialDisplayBits::AMB2_TL,
+        SerialDisplayBits::AMB2_M,
+    ];
+    /// Set tens segments in `Glyph::segments` bit order
+    const SET1_SEGS: [SerialDisplayBits; 7] = [
+        SerialDisplayBits::SET1_T,
+        SerialDisplayBits::SET1_TR,
+        SerialDisplayBits::SET1_BR,
+        SerialDisplayBits::SET1_B,
+        SerialDisplayBits::SET1_BL,
+        SerialDisplayBits::SET1_TL,
+        SerialDisplayBits::SET1_M,
+    ];
+
+    fn glyph(glyph: Glyph, segs: &[SerialDisplayBits; 7]) -> SerialDisplayBits {
+        let mask = glyph.segments();
+        segs.iter()
+            .enumerate()
+            .filter(|(i, _)| mask & (1 << i) != 0)
+            .fold(SerialDisplayBits::EMPTY, |acc, (_, seg)| acc | *seg)
     }
 
-    pub fn setup_set(input: i8) -> (SerialDisplayBits, i8) {
-        let mut base = SerialDisplayBits::EMPTY;
-        let mut n = input;
-        if n < 0 {
-            n = n * -1;
-            base = base | SerialDisplayBits::set_neg(true);
-        }
-        if n >= 100 {
-            n = n - 100;
-            base = base | SerialDisplayBits::set_hund(true);
-        }
-        let tens = n / 10;
-        let ones = n % 10;
-        base = base | SerialDisplayBits::amb_first(tens.try_into().unwrap());
-        return (base, ones);
+    pub fn setup_amb(readout: Readout) -> SerialDisplayBits {
+        SerialDisplayBits::amb_neg(readout.neg)
+            | SerialDisplayBits::amb_hund(readout.hund)
+            | SerialDisplayBits::glyph(readout.tens, &SerialDisplayBits::AMB1_SEGS)
+            | SerialDisplayBits::glyph(readout.ones, &SerialDisplayBits::AMB2_SEGS)
+    }
+
+    /// The set ones digit is driven from the seg side, so it comes back separately
+    pub fn setup_set(readout: Readout) -> (SerialDisplayBits, SegDisplayBits) {
+        let serial = SerialDisplayBits::set_neg(readout.neg)
+            | SerialDisplayBits::set_hund(readout.hund)
+            | SerialDisplayBits::glyph(readout.tens, &SerialDisplayBits::SET1_SEGS);
+        (serial, SegDisplayBits::set_glyph(readout.ones))
     }
 }
 
@@ -631,6 +657,26 @@ impl SegDisplayBits {
         }
     }
 
+    /// Set ones segments in `Glyph::segments` bit order
+    const SET2_SEGS: [SegDisplayBits; 7] = [
+        SegDisplayBits::SET2_T,
+        SegDisplayBits::SET2_TR,
+        SegDisplayBits::SET2_BR,
+        SegDisplayBits::SET2_B,
+        SegDisplayBits::SET2_BL,
+        SegDisplayBits::SET2_TL,
+        SegDisplayBits::SET2_M,
+    ];
+
+    pub fn set_glyph(glyph: Glyph) -> SegDisplayBits {
+        let mask = glyph.segments();
+        SegDisplayBits::SET2_SEGS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| mask & (1 << i) != 0)
+            .fold(SegDisplayBits::EMPTY, |acc, (_, seg)| acc | *seg)
+    }
+
     pub fn recirc(b: bool) -> SegDisplayBits {
         if b == true {
             return SegDisplayBits::RECIRC;
@@ -686,18 +732,6 @@ impl SegDisplayBits {
     }
 }
 
-#[derive(Copy, Clone)]
-pub enum Button {
-    Auto,
-    Demist,
-    TempUp,
-    Off,
-    FanLo,
-    FanHigh,
-    Recirc,
-    TempDown,
-}
-
 pub struct Buttons<'a> {
     pin1: Flex<'a>,
     pin2: Flex<'a>,
@@ -742,37 +776,6 @@ impl<'a> Buttons<'a> {
     }
 }
 
-pub struct ButtonIter {
-    next: Option<Button>,
-}
-
-impl ButtonIter {
-    pub fn new() -> Self {
-        ButtonIter {
-            next: Some(Button::Auto),
-        }
-    }
-}
-
-impl Iterator for ButtonIter {
-    type Item = Button;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let curr = self.next.take()?;
-        self.next = match curr {
-            Button::Auto => Some(Button::Demist),
-            Button::Demist => Some(Button::TempUp),
-            Button::TempUp => Some(Button::Off),
-            Button::Off => Some(Button::FanLo),
-            Button::FanLo => Some(Button::FanHigh),
-            Button::FanHigh => Some(Button::Recirc),
-            Button::Recirc => Some(Button::TempDown),
-            Button::TempDown => None,
-        };
-        Some(curr)
-    }
-}
-
 pub struct DigiDisplay<'a> {
     //i2c: I2c<'a, I2C1, Blocking>,
     serialclock: Output<'a>,
@@ -786,7 +789,13 @@ pub struct DigiDisplay<'a> {
     fanlow_led: Output<'a>,
     recirc_led: Output<'a>,
     buttons: Buttons<'a>,
+    pressed: ButtonSet,
+    gate: ChordGate,
     backend: ClimateControlBacker,
+    config: Config,
+    format: SegFormat,
+    /// Blanked with the ignition off
+    blanked: bool,
 }
 
 impl<'a> DigiDisplay<'a> {
@@ -828,90 +837,154 @@ impl<'a> DigiDisplay<'a> {
             fanlow_led,
             recirc_led,
             buttons,
+            pressed: ButtonSet::empty(),
+            gate: ChordGate::new(),
             backend,
+            config: Config::default(),
+            format: SegFormat::default(),
+            blanked: false,
         }
     }
 
-    pub async fn buttonreader(&mut self) {
+    /// Change how the ambient and set readouts are formatted
+    pub fn set_format(&mut self, format: SegFormat) {
+        self.format = format;
+    }
+
+    /// The climate control the buttons work
+    pub fn backend(&self) -> &ClimateControlBacker {
+        &self.backend
+    }
+
+    /// Take up the settings from the VFD's menu
+    pub fn set_config(&mut self, config: Config) {
+        self.backend.set_displaymode(config.celsius);
+        self.config = config;
+    }
+
+    /// Drive one button's row low and read its column
+    async fn sample(&mut self, button: Button) -> bool {
+        {
+            let (setpin, _) = self.buttons.get(button);
+            setpin.set_as_output();
+            setpin.set_low();
+        }
+        Timer::after(Duration::from_millis(1)).await;
+
+        let pressed = {
+            let (_, checkpin) = self.buttons.get(button);
+            !checkpin.is_high()
+        };
+
+        // restore pin to input + pull-up
+        {
+            let (setpin, _) = self.buttons.get(button);
+            setpin.set_as_input();
+            setpin.set_pull(Pull::Up);
+        }
+        pressed
+    }
+
+    /// Sample every button once, without debouncing
+    pub async fn scan(&mut self) -> ButtonSet {
+        let mut pressed = ButtonSet::empty();
+        for button in ButtonIter::new() {
+            pressed.set(button.into(), self.sample(button).await);
+        }
+        pressed
+    }
+
+    /// Read the buttons once round, handing each [`ButtonEvent`] to
+    /// `on_event` as it happens
+    pub async fn buttonreader(&mut self, mut on_event: impl FnMut(ButtonEvent)) {
         let mut iter = ButtonIter::new();
 
         while let Some(button) = iter.next() {
-            {
-                let (setpin, _) = self.buttons.get(button);
-                setpin.set_as_output();
-                setpin.set_low();
-            }
-            Timer::after(Duration::from_millis(1)).await;
-
-            let pressed = {
-                let (_, checkpin) = self.buttons.get(button);
-                !checkpin.is_high()
-            };
-
-            if pressed {
-                // short debounce delay
-                Timer::after(Duration::from_millis(10)).await;
-
-                // second sample
-                let still_pressed = {
-                    let (_, checkpin) = self.buttons.get(button);
-                    !checkpin.is_high()
-                };
-                match button {
-                    Button::Auto => self.backend.set_ac_toggle(),
-                    Button::Demist => self.backend.next_mode(),
-                    Button::TempUp => self.backend.set_set_temp(self.backend.set_temp() + 1),
-                    Button::Off => self.backend.set_fan_speed(0),
-                    Button::FanLo => self.backend.set_fan_speed(50),
-                    Button::FanHigh => self.backend.set_fan_speed(100),
-                    Button::Recirc => self.backend.set_recirc_toggle(),
-                    Button::TempDown => self.backend.set_set_temp(self.backend.set_temp() - 1),
+            let pressed = self.sample(button).await;
+
+            if !pressed {
+                if self.pressed.contains(button.into()) {
+                    self.scanned(self.pressed - button.into(), &mut on_event);
                 }
-                if still_pressed {
-                    Timer::after(Duration::from_millis(100)).await;
-
-                    let is_held = {
-                        let (_, checkpin) = self.buttons.get(button);
-                        !checkpin.is_high()
-                    };
-
-                    if is_held {
-                        loop {
-                            Timer::after(Duration::from_millis(100)).await;
-
-                            let still_pressed = {
-                                let (_, checkpin) = self.buttons.get(button);
-                                !checkpin.is_high()
-                            };
-                            if !still_pressed {
-                                break;
-                            }
-                            match button {
-                                Button::Auto => self.backend.set_ac_toggle(),
-                                Button::Demist => self.backend.next_mode(),
-                                Button::TempUp => {
-                                    self.backend.set_set_temp(self.backend.set_temp() + 1)
-                                }
-                                Button::Off => self.backend.set_fan_speed(0),
-                                Button::FanLo => self.backend.set_fan_speed(50),
-                                Button::FanHigh => self.backend.set_fan_speed(100),
-                                Button::Recirc => self.backend.set_recirc_toggle(),
-                                Button::TempDown => {
-                                    self.backend.set_set_temp(self.backend.set_temp() - 1)
-                                }
-                            }
-                            self.update_display().await
+                continue;
+            }
+
+            // short debounce delay
+            Timer::after(Duration::from_millis(10)).await;
+
+            // second sample
+            let still_pressed = self.sample(button).await;
+            let mut pressed = self.pressed;
+            pressed.set(button.into(), still_pressed);
+            self.scanned(pressed, &mut on_event);
+            self.press(button, &mut on_event);
+            if still_pressed {
+                Timer::after(Duration::from_millis(100)).await;
+
+                let is_held = self.sample(button).await;
+
+                if is_held {
+                    loop {
+                        Timer::after(Duration::from_millis(100)).await;
+
+                        // rescan everything so chords show up while a button is held
+                        let pressed = self.scan().await;
+                        self.scanned(pressed, &mut on_event);
+                        if !self.pressed.contains(button.into()) {
+                            break;
                         }
+                        self.press(button, &mut on_event);
+                        self.update_display().await
                     }
                 }
             }
+        }
+    }
 
-            // 4) restore pin to input + pull-up
-            {
-                let (setpin, _) = self.buttons.get(button);
-                setpin.set_as_input();
-                setpin.set_pull(Pull::Up);
-            }
+    /// Buttons that were down the last time they were scanned
+    pub fn pressed(&self) -> ButtonSet {
+        self.pressed
+    }
+
+    /// Take in the buttons now down
+    fn scanned(&mut self, pressed: ButtonSet, on_event: &mut impl FnMut(ButtonEvent)) {
+        self.pressed = pressed;
+        DIAGNOSTICS.set_buttons(pressed.bits());
+        let gated = self
+            .gate
+            .scan(pressed, BUTTONS_CAPTURED.load(Ordering::Relaxed));
+        self.forward(gated, on_event);
+    }
+
+    /// Handle one press (or hold repeat) of a button
+    fn press(&mut self, button: Button, on_event: &mut impl FnMut(ButtonEvent)) {
+        let gated = self
+            .gate
+            .press(button, BUTTONS_CAPTURED.load(Ordering::Relaxed));
+        self.forward(gated, on_event);
+    }
+
+    /// Hand on what got through the [`ChordGate`]
+    fn forward(&mut self, gated: Gated, on_event: &mut impl FnMut(ButtonEvent)) {
+        if let Some(event) = gated.event {
+            on_event(event);
+        }
+        for button in ButtonIter::new().filter(|&button| gated.actions.contains(button.into())) {
+            self.act(button);
+        }
+    }
+
+    /// Work the climate control for one press of `button`
+    fn act(&mut self, button: Button) {
+        match button {
+            Button::Auto => self.backend.set_auto_toggle(),
+            Button::Demist => self.backend.next_mode(),
+            Button::TempUp => self.backend.set_set_temp(self.backend.set_temp() + 1),
+            Button::Off => self.backend.set_fan_speed(0),
+            Button::FanLo => self.backend.set_fan_speed(50),
+            Button::FanHigh => self.backend.set_fan_speed(100),
+            Button::Recirc => self.backend.set_recirc_toggle(),
+            Button::TempDown => self.backend.set_set_temp(self.backend.set_temp() - 1),
         }
     }
 
@@ -968,6 +1041,22 @@ impl<'a> DigiDisplay<'a> {
             }
             _ => (),
         }
+        self.record_outputs();
+    }
+
+    /// Note the level of every LED pin for the diagnostics page
+    fn record_outputs(&self) {
+        for (output, pin) in [
+            (diagnostics::Output::DemistLed, &self.demist_led),
+            (diagnostics::Output::AcLed, &self.ac_led),
+            (diagnostics::Output::EconLed, &self.econ_led),
+            (diagnostics::Output::DefrostLed, &self.defrost_led),
+            (diagnostics::Output::FanHighLed, &self.fanhigh_led),
+            (diagnostics::Output::FanLowLed, &self.fanlow_led),
+            (diagnostics::Output::RecircLed, &self.recirc_led),
+        ] {
+            DIAGNOSTICS.set_output(output, pin.is_set_high());
+        }
     }
 
     async fn write_serial(&mut self, input: u128) {
@@ -991,16 +1080,51 @@ impl<'a> DigiDisplay<'a> {
         .unwrap();*/
     }
 
+    /// Turn off every segment and LED until the next [`DigiDisplay::unblank`]
+    pub async fn blank(&mut self) {
+        self.blanked = true;
+        self.gate.set_blanked(true);
+        self.write_serial(0).await;
+        self.write_ic(0);
+        // the LEDs are lit low
+        for led in [
+            &mut self.demist_led,
+            &mut self.ac_led,
+            &mut self.econ_led,
+            &mut self.defrost_led,
+            &mut self.fanhigh_led,
+            &mut self.fanlow_led,
+            &mut self.recirc_led,
+        ] {
+            led.set_high();
+        }
+        self.record_outputs();
+    }
+
+    /// Show the readouts and LEDs again after [`DigiDisplay::blank`]
+    pub async fn unblank(&mut self) {
+        self.blanked = false;
+        self.gate.set_blanked(false);
+        self.update_display().await;
+    }
+
     pub async fn update_display(&mut self) {
-        let mut serialdata = SerialDisplayBits::setup_amb(self.backend.ambient_temp());
+        if self.blanked {
+            return;
+        }
+        self.backend.run_auto(self.config.auto_aggressiveness);
+        let ambient = self.config.ambient_tenths(self.backend.ambient_temp());
+        let mut serialdata = SerialDisplayBits::setup_amb(self.format.format_tenths(ambient));
         let mut segdata = SegDisplayBits::mode(self.backend.mode())
             | SegDisplayBits::recirc(self.backend.recirc_toggle())
             | SegDisplayBits::ac_toggle(self.backend.ac_toggle())
             | SegDisplayBits::c_or_f(self.backend.displaymode());
-        let (serialset, segset) = SerialDisplayBits::setup_set(self.backend.set_temp());
-        let tempguage = map_u8(self.backend.set_temp().try_into().unwrap(), 60, 90, 0, 10);
+        let set = self.config.tenths(self.backend.set_temp());
+        let (serialset, segset) = SerialDisplayBits::setup_set(self.format.format_tenths(set));
+        // the gauge covers 60 to 90, anything past that pins it to an end
+        let tempguage = map_i32(self.backend.set_temp().clamp(60, 90).into(), 60, 90, 0, 10) as u8;
         serialdata = serialdata | serialset | SerialDisplayBits::gauge(tempguage);
-        segdata = segdata | SegDisplayBits::set_second(segset);
+        segdata = segdata | segset;
 
         self.write_serial(serialdata.bits().into()).await;
         self.write_ic(segdata.bits());
@@ -0,0 +1,214 @@
+//! Bitmap fonts converted from `assets/fonts/` at build time.
+//!
+//! The fonts are proportional: every glyph has its own width and advance, so
+//! a 1 takes up less room than an 8. A [`FontStyle`] draws them anywhere an
+//! e-g [`Text`](embedded_graphics::text::Text) goes, with the same
+//! baselines and alignments as the built in mono fonts, and only lights
+//! pixels, whatever is underneath shows through.
+//!
+//! [`align_text`] places text of any style in a box, which is what the
+//! widgets use to right align numbers or centre labels.
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::renderer::{TextMetrics, TextRenderer};
+use embedded_graphics::text::{Alignment, Baseline};
+
+/// One character of a [`BitmapFont`]
+#[derive(Debug)]
+pub struct Glyph {
+    ch: char,
+    /// How far along the next character starts
+    advance: i32,
+    /// Top left corner of the bitmap, from the origin on the baseline
+    offset: Point,
+    size: Size,
+    /// Rows top to bottom, most significant bit first, each padded to a
+    /// whole number of bytes
+    rows: &'static [u8],
+}
+
+impl Glyph {
+    pub const fn new(
+        ch: char,
+        advance: i32,
+        offset: Point,
+        size: Size,
+        rows: &'static [u8],
+    ) -> Self {
+        Glyph {
+            ch,
+            advance,
+            offset,
+            size,
+            rows,
+        }
+    }
+
+    pub fn advance(&self) -> i32 {
+        self.advance
+    }
+
+    /// The lit pixels of the glyph with its origin at `origin`
+    fn pixels(
+        &self,
+        origin: Point,
+        color: BinaryColor,
+    ) -> impl Iterator<Item = Pixel<BinaryColor>> + '_ {
+        let stride = self.size.width.div_ceil(8) as usize;
+        let top_left = origin + self.offset;
+        (0..self.size.height)
+            .flat_map(move |y| (0..self.size.width).map(move |x| (x, y)))
+            .filter(move |&(x, y)| {
+                self.rows[y as usize * stride + x as usize / 8] & (0x80 >> (x % 8)) != 0
+            })
+            .map(move |(x, y)| Pixel(top_left + Point::new(x as i32, y as i32), color))
+    }
+}
+
+/// A proportional font converted from a BDF file
+#[derive(Debug)]
+pub struct BitmapFont {
+    /// Rows from the top of the line down to the baseline, inclusive
+    ascent: i32,
+    /// Rows below the baseline
+    descent: i32,
+    /// Sorted by character
+    glyphs: &'static [Glyph],
+}
+
+impl BitmapFont {
+    pub const fn new(ascent: i32, descent: i32, glyphs: &'static [Glyph]) -> Self {
+        BitmapFont {
+            ascent,
+            descent,
+            glyphs,
+        }
+    }
+
+    /// The glyph for `ch`, if the font has one
+    pub fn glyph(&self, ch: char) -> Option<&Glyph> {
+        let index = self
+            .glyphs
+            .binary_search_by_key(&ch, |glyph| glyph.ch)
+            .ok()?;
+        Some(&self.glyphs[index])
+    }
+
+    pub fn line_height(&self) -> u32 {
+        (self.ascent + self.descent) as u32
+    }
+
+    /// How far below `baseline` of a line the font's baseline is
+    fn baseline_offset(&self, baseline: Baseline) -> i32 {
+        match baseline {
+            Baseline::Top => self.ascent - 1,
+            Baseline::Bottom => -self.descent,
+            Baseline::Middle => self.ascent - 1 - (self.line_height() as i32 - 1) / 2,
+            Baseline::Alphabetic => 0,
+        }
+    }
+}
+
+/// A [`BitmapFont`] in a colour, characters the font doesn't have are left out
+#[derive(Copy, Clone, Debug)]
+pub struct FontStyle {
+    pub font: &'static BitmapFont,
+    pub text_color: BinaryColor,
+}
+
+impl FontStyle {
+    pub const fn new(font: &'static BitmapFont, text_color: BinaryColor) -> Self {
+        FontStyle { font, text_color }
+    }
+
+    fn glyphs<'a>(&self, text: &'a str) -> impl Iterator<Item = &'static Glyph> + 'a {
+        let font = self.font;
+        text.chars().filter_map(move |ch| font.glyph(ch))
+    }
+}
+
+impl TextRenderer for FontStyle {
+    type Color = BinaryColor;
+
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut origin = position + Point::new(0, self.font.baseline_offset(baseline));
+        for glyph in self.glyphs(text) {
+            target.draw_iter(glyph.pixels(origin, self.text_color))?;
+            origin.x += glyph.advance;
+        }
+        Ok(Point::new(origin.x, position.y))
+    }
+
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        _baseline: Baseline,
+        _target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        // nothing to draw without a background
+        Ok(position + Point::new(width as i32, 0))
+    }
+
+    /// The bounding box runs from `position` to the right edge of the last
+    /// glyph, the space after it isn't part of the text
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let origin = position + Point::new(0, self.font.baseline_offset(baseline));
+        let mut x = origin.x;
+        let mut right = origin.x;
+        for glyph in self.glyphs(text) {
+            if glyph.size.width > 0 {
+                right = right.max(x + glyph.offset.x + glyph.size.width as i32);
+            }
+            x += glyph.advance;
+        }
+
+        let top = origin.y - (self.font.ascent - 1);
+        TextMetrics {
+            bounding_box: Rectangle::new(
+                Point::new(position.x, top),
+                Size::new((right - position.x) as u32, self.line_height()),
+            ),
+            next_position: Point::new(x, position.y),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        self.font.line_height()
+    }
+}
+
+/// Top left corner of `text` in `style`, aligned across an area of `size`
+/// and centred down it, for drawing with [`Baseline::Top`]
+pub fn align_text<S: TextRenderer>(
+    text: &str,
+    style: &S,
+    alignment: Alignment,
+    size: Size,
+) -> Point {
+    let metrics = style.measure_string(text, Point::zero(), Baseline::Top);
+    let free = size.width as i32 - metrics.bounding_box.size.width as i32;
+    let x = match alignment {
+        Alignment::Left => 0,
+        Alignment::Center => free / 2,
+        Alignment::Right => free,
+    };
+    let y = (size.height as i32 - style.line_height() as i32) / 2;
+    Point::new(x, y)
+}
+
+include!(concat!(env!("OUT_DIR"), "/fonts.rs"));
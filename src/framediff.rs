@@ -0,0 +1,138 @@
+//! Finding what changed between two VFD frames.
+//!
+//! Frames are in the layout of the VFD's display RAM: column after column,
+//! 8 rows to a byte. Only the bytes of a column the panel shows are compared,
+//! the rest of the display RAM is never looked at.
+//!
+//! Changed columns next to each other make up a [`Span`], which covers the
+//! byte rows that changed in any of its columns and goes out as one partial
+//! write of the display RAM. Redrawing a gauge pointer or a temperature then
+//! sends a few dozen bytes instead of the whole frame.
+
+use core::ops::Range;
+
+/// How a frame is laid out in memory
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameLayout {
+    /// Bytes from one column to the next
+    pub column_bytes: usize,
+    /// Bytes at the start of every column that are on the panel
+    pub visible_bytes: usize,
+}
+
+/// A block of the display RAM that changed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// Changed columns
+    pub columns: Range<usize>,
+    /// Bytes of those columns to send, 8 rows each
+    pub bytes: Range<usize>,
+}
+
+impl Span {
+    /// First column, for the partial write
+    pub fn x(&self) -> u32 {
+        self.columns.start as u32
+    }
+
+    /// First row, for the partial write
+    pub fn y(&self) -> u32 {
+        self.bytes.start as u32 * 8
+    }
+
+    pub fn width(&self) -> u32 {
+        self.columns.len() as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        self.bytes.len() as u32 * 8
+    }
+
+    /// Bytes it takes to send the span
+    pub fn len(&self) -> usize {
+        self.columns.len() * self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copy the span out of `frame` into the start of `out`, column after
+    /// column the way the partial write wants it.
+    ///
+    /// # Panics
+    ///
+    /// If `out` is shorter than [`Span::len`].
+    pub fn gather<'a>(&self, layout: FrameLayout, frame: &[u8], out: &'a mut [u8]) -> &'a mut [u8] {
+        let out = &mut out[..self.len()];
+        let columns = frame.chunks_exact(layout.column_bytes);
+        let columns = columns.skip(self.columns.start).take(self.columns.len());
+        for (dest, column) in out.chunks_exact_mut(self.bytes.len()).zip(columns) {
+            dest.copy_from_slice(&column[self.bytes.clone()]);
+        }
+        out
+    }
+}
+
+/// The [`Span`]s where `back` differs from `front`, left to right.
+///
+/// Both frames must have the same `layout`.
+pub fn changed_spans<'a>(layout: FrameLayout, front: &'a [u8], back: &'a [u8]) -> ChangedSpans<'a> {
+    assert_eq!(front.len(), back.len(), "frames of different sizes");
+    ChangedSpans {
+        layout,
+        front,
+        back,
+        column: 0,
+    }
+}
+
+/// Iterator returned by [`changed_spans`]
+pub struct ChangedSpans<'a> {
+    layout: FrameLayout,
+    front: &'a [u8],
+    back: &'a [u8],
+    /// First column not looked at yet
+    column: usize,
+}
+
+impl ChangedSpans<'_> {
+    fn columns(&self) -> usize {
+        self.front.len() / self.layout.column_bytes
+    }
+
+    /// The bytes that differ in `column`, from the first to the last
+    fn changed_bytes(&self, column: usize) -> Option<Range<usize>> {
+        let start = column * self.layout.column_bytes;
+        let visible = start..start + self.layout.visible_bytes;
+        let (front, back) = (&self.front[visible.clone()], &self.back[visible]);
+        let differs = |(f, b): (&u8, &u8)| f != b;
+        let first = front.iter().zip(back).position(differs)?;
+        let last = front.iter().zip(back).rposition(differs)?;
+        Some(first..last + 1)
+    }
+}
+
+impl Iterator for ChangedSpans<'_> {
+    type Item = Span;
+
+    fn next(&mut self) -> Option<Span> {
+        let columns = self.columns();
+        let (start, mut bytes) = (self.column..columns)
+            .find_map(|column| Some((column, self.changed_bytes(column)?)))?;
+
+        let mut end = start + 1;
+        while end < columns {
+            let Some(more) = self.changed_bytes(end) else {
+                break;
+            };
+            bytes = bytes.start.min(more.start)..bytes.end.max(more.end);
+            end += 1;
+        }
+        self.column = end;
+        Some(Span {
+            columns: start..end,
+            bytes,
+        })
+    }
+}
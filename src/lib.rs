@@ -2,11 +2,24 @@
 
 extern crate alloc;
 
+pub mod animation;
+pub mod brightness;
+pub mod burnin;
+pub mod buttons;
 pub mod climatecontrol;
+pub mod diagnostics;
 pub mod digidisplay;
+pub mod fonts;
+pub mod framediff;
+pub mod lightsensor;
+pub mod power;
+pub mod segformat;
+pub mod screens;
+pub mod settings;
 pub mod temp;
 pub mod vfddisplay;
 pub mod vfdgraphics;
+pub mod widgets;
 
 #[allow(unused)]
 pub(crate) fn map_u8(x: u8, in_min: u8, in_max: u8, out_min: u8, out_max: u8) -> u8 {
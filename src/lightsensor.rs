@@ -0,0 +1,52 @@
+use embassy_rp::adc::{Adc, Blocking, Channel};
+
+use crate::brightness::LightInputs;
+use crate::diagnostics::{DIAGNOSTICS, Fault, Reading, Sensor, percent_tenths};
+use crate::temp::average;
+
+/// ADC channels for the dash illumination wire and the cabin photodiode.
+///
+/// The illumination wire is 12V with the lights on, so it needs dividing
+/// down to 3.3V before it reaches the ADC pin. A PWM dimmer signal works too
+/// through an RC filter in front of the pin.
+pub struct LightSensor<'a> {
+    dimmer: Channel<'a>,
+    photodiode: Channel<'a>,
+}
+
+impl<'a> LightSensor<'a> {
+    pub fn new(dimmer: Channel<'a>, photodiode: Channel<'a>) -> Self {
+        LightSensor { dimmer, photodiode }
+    }
+
+    /// Both inputs, also recorded for the diagnostics page
+    pub fn read(&mut self, adc: &mut Adc<'a, Blocking>) -> LightInputs {
+        let mut samples = [0u16; 16];
+        for sample in &mut samples {
+            *sample = adc.blocking_read(&mut self.dimmer).unwrap_or(0);
+        }
+        let dimmer = average(&samples);
+        record(Sensor::Dimmer, samples[15], dimmer);
+        for sample in &mut samples {
+            *sample = adc.blocking_read(&mut self.photodiode).unwrap_or(0);
+        }
+        let ambient = average(&samples);
+        record(Sensor::Photodiode, samples[15], ambient);
+        LightInputs { dimmer, ambient }
+    }
+}
+
+/// Either input can sit at a rail for real, off or in the dark, so there's
+/// no telling a fault from the counts
+fn record(sensor: Sensor, raw: u16, filtered: u16) {
+    DIAGNOSTICS.record(
+        sensor,
+        Reading {
+            raw,
+            filtered,
+            ohms: None,
+            tenths: percent_tenths(filtered),
+            fault: Fault::None,
+        },
+    );
+}
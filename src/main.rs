@@ -20,9 +20,10 @@ use embassy_rp::pio::{InterruptHandler as PIOInt, Pio};
 use embassy_rp::spi;
 use embassy_rp::spi::Spi;
 use embassy_rp::{bind_interrupts, block, i2c};
-use embassy_time::{Duration, Ticker, Timer, block_for};
+use embassy_time::{Duration, Instant, Ticker, Timer, block_for};
 
 use embedded_alloc::Heap;
+use power::{PowerConfig, PowerManager, PowerState};
 use vfddisplay::Display;
 use {defmt_rtt as _, panic_probe as _};
 
@@ -71,6 +72,10 @@ async fn main(spawner: Spawner) {
     let serialdata = Output::new(p.PIN_29, Level::Low);
     //let i2c = i2c::I2c::new_blocking(p.I2C1, scl, sda, embassy_rp::i2c::Config::default());
 
+    // accessory feed from the ignition switch, through a divider
+    let ignition = Input::new(p.PIN_7, Pull::Down);
+    let mut power = PowerManager::new(PowerConfig::default(), PowerState::Awake);
+
     spawner.spawn(digidisplay::serialsyncer()).unwrap();
 
     let mut digidisp = DigiDisplay::new( serialclock, serialdata, demist, ac, econ, defrost, fanhigh, fanlo, recirc, pin1, pin2, pin3, pin4, pin5, pin6, backend);
@@ -94,7 +99,14 @@ async fn main(spawner: Spawner) {
                 data[i] = wheel((((i * 256) as u16 / NUM_LEDS as u16 + j as u16) & 255) as u8);
             }
             ws2812.write(&data).await;
-            digidisp.buttonreader().await;
+            let now = Instant::now().as_millis() as u32;
+            match power.update(ignition.is_high(), now) {
+                Some(PowerState::Asleep) => digidisp.blank().await,
+                Some(PowerState::Awake) => digidisp.unblank().await,
+                None => (),
+            }
+            // no VFD fitted yet to show the pages, so the buttons only work the climate control
+            digidisp.buttonreader(|_| ()).await;
             digidisp.update_display().await;
         }
     }
@@ -120,8 +132,31 @@ let spibus: Mutex<CriticalSectionRawMutex, RefCell<_>> = Mutex::new(RefCell::new
 
 let vfd_spi = SpiDeviceWithConfig::new(&spibus, cs, config1);
 
-let mut vfd = Display::new(vfd_spi, rst);
-
-vfd.set_brightness(128).unwrap();
-vfd.draw_boot_image().await;
-vfd.update_display();*/
+let mut vfd = Display::new(digidisp.backend(), vfd_spi, rst).unwrap();
+vfd.play_boot_animation().await.unwrap();
+vfd.update_display().unwrap();
+
+the dash lights and the photodiode for the brightness, on the ADC pins left
+over (Thermistor::new above takes the Adc for itself, so it would have to
+borrow it like LightSensor::read does):
+let mut adc = Adc::new_blocking(p.ADC, Config::default());
+let dimmer = Channel::new_pin(p.PIN_27, Pull::None);
+let photodiode = Channel::new_pin(p.PIN_28, Pull::None);
+let mut light = lightsensor::LightSensor::new(dimmer, photodiode);
+
+and next to the segment display in the main loop:
+Some(PowerState::Asleep) => vfd.sleep().unwrap(),
+Some(PowerState::Awake) => vfd.wake().unwrap(),
+
+and handing it the buttons in place of the empty handler:
+let mut redraw = false;
+digidisp.buttonreader(|event| redraw |= vfd.handle_event(event)).await;
+digidisp.set_config(*vfd.screens().config());
+redraw |= vfd.set_backend(digidisp.backend());
+// the Display's BrightnessController follows the light readings, with the
+// BRIGHTNESS setting on top, and fades a step at a time
+vfd.update_light(light.read(&mut adc));
+vfd.fade_brightness().unwrap();
+if redraw || vfd.refresh_due() {
+    vfd.update_display().unwrap();
+}*/
@@ -0,0 +1,85 @@
+//! Display power following the ignition.
+//!
+//! With the key off the VFD goes into deep sleep and the segment LCD and
+//! button LEDs go dark, so nothing is lit (or drawing current) in a parked
+//! car. Turning the key back brings everything back as it was.
+//!
+//! The accessory feed is noisy: it bounces when the key is turned and drops
+//! out completely while cranking. So the key has to be on for a short while
+//! before the displays wake, and off for a good while before they sleep.
+//!
+//! Times are milliseconds from any monotonic clock, like in [`crate::burnin`].
+
+/// Whether the displays should be on
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PowerState {
+    Awake,
+    Asleep,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PowerConfig {
+    /// Milliseconds the key has to be on before waking up
+    pub wake_after: u32,
+    /// Milliseconds the key has to be off before going to sleep, long enough
+    /// to ride out cranking
+    pub sleep_after: u32,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        PowerConfig {
+            wake_after: 50,
+            sleep_after: 3000,
+        }
+    }
+}
+
+/// Debounces the ignition input into a [`PowerState`]
+pub struct PowerManager {
+    config: PowerConfig,
+    state: PowerState,
+    /// When the input last disagreed with `state` after agreeing, if it
+    /// still does
+    changed_at: Option<u32>,
+}
+
+impl PowerManager {
+    /// Start in `state`, normally [`PowerState::Awake`] since the firmware
+    /// boots when the key is turned.
+    pub fn new(config: PowerConfig, state: PowerState) -> Self {
+        PowerManager {
+            config,
+            state,
+            changed_at: None,
+        }
+    }
+
+    pub fn state(&self) -> PowerState {
+        self.state
+    }
+
+    /// Feed the ignition input as read at `now`, call this regularly.
+    ///
+    /// Returns the new state if the displays should change over.
+    pub fn update(&mut self, key_on: bool, now: u32) -> Option<PowerState> {
+        let (wanted, delay) = if key_on {
+            (PowerState::Awake, self.config.wake_after)
+        } else {
+            (PowerState::Asleep, self.config.sleep_after)
+        };
+
+        if wanted == self.state {
+            self.changed_at = None;
+            return None;
+        }
+
+        let since = *self.changed_at.get_or_insert(now);
+        if now.wrapping_sub(since) < delay {
+            return None;
+        }
+        self.state = wanted;
+        self.changed_at = None;
+        Some(wanted)
+    }
+}
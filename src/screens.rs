@@ -0,0 +1,560 @@
+//! Pages shown on the VFD and the manager that switches between them.
+//!
+//! Every page draws itself onto any [`BinaryTarget`] and gets a look at
+//! button presses while it is on screen. The [`ScreenManager`] owns one of
+//! each page, forwards draws and button events to the current one and runs
+//! a [`Transition`] between pages when asked to change.
+//!
+//! Times are milliseconds since boot.
+
+use alloc::format;
+use core::sync::atomic::Ordering;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::{Baseline, Text};
+
+use crate::animation::{Progress, Scene, draw_transition};
+use crate::buttons::{BUTTONS_CAPTURED, Button, ButtonEvent, ButtonSet};
+use crate::climatecontrol::ClimateControlBacker;
+use crate::diagnostics::{self, Diagnostics, REFRESH_INTERVAL, Section};
+use crate::settings::{
+    Config, DIAGNOSTICS_CHORD, MENU_CHORD, Menu, MenuEvent, SETTINGS_MENU, degrees,
+};
+use crate::vfdgraphics::{BinaryTarget, Graphics, LARGE_SEGMENT_STYLE, climate};
+use crate::widgets::Widget;
+
+pub use crate::animation::Transition;
+
+/// Width of the visible VFD area in pixels
+pub const SCREEN_WIDTH: i32 = 256;
+
+/// How long to hold Off on the climate page to go on to the next page
+pub const PAGE_HOLD: u64 = 1000;
+
+/// Everything a page is allowed to look at while drawing
+pub struct PageContext<'a> {
+    pub graphics: &'a Graphics,
+    pub backend: &'a ClimateControlBacker,
+    pub diagnostics: &'a Diagnostics,
+    pub config: Config,
+    pub now: u64,
+}
+
+/// What a page wants to happen after handling a button
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PageAction {
+    /// Nothing changed
+    Stay,
+    /// The page changed its own state and needs drawing again
+    Redraw,
+    /// Switch to another page
+    Goto(PageId),
+}
+
+pub trait Page {
+    fn draw<D: BinaryTarget>(&self, ctx: &PageContext, display: &mut D);
+
+    fn handle_button(&mut self, _button: Button, _now: u64) -> PageAction {
+        PageAction::Stay
+    }
+
+    /// Milliseconds between redraws while nothing is happening, for pages
+    /// showing values that change on their own
+    fn refresh_interval(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// [`PageId::next`] and [`PageId::prev`] go round every page but
+/// diagnostics, which only [`DIAGNOSTICS_CHORD`] opens
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PageId {
+    #[default]
+    Climate,
+    Sensors,
+    Diagnostics,
+    Settings,
+    TripClock,
+}
+
+impl PageId {
+    pub fn next(self) -> PageId {
+        match self {
+            PageId::Climate => PageId::Sensors,
+            PageId::Sensors => PageId::Settings,
+            PageId::Settings => PageId::TripClock,
+            PageId::TripClock => PageId::Climate,
+            PageId::Diagnostics => PageId::Climate,
+        }
+    }
+
+    pub fn prev(self) -> PageId {
+        match self {
+            PageId::Climate => PageId::TripClock,
+            PageId::Sensors => PageId::Climate,
+            PageId::Settings => PageId::Sensors,
+            PageId::TripClock => PageId::Settings,
+            PageId::Diagnostics => PageId::Climate,
+        }
+    }
+}
+
+/// Buttons shared by all the info pages: TempUp/TempDown flip through the
+/// pages and Off goes back to the climate page.
+fn page_nav(current: PageId, button: Button) -> PageAction {
+    match button {
+        Button::TempUp => PageAction::Goto(current.next()),
+        Button::TempDown => PageAction::Goto(current.prev()),
+        Button::Off => PageAction::Goto(PageId::Climate),
+        _ => PageAction::Stay,
+    }
+}
+
+/// The normal climate control screen.
+///
+/// Every button works the climate control here. Holding Off for
+/// [`PAGE_HOLD`] goes on to the next page instead of turning the fan off.
+#[derive(Default)]
+pub struct ClimatePage {
+    temp_gauge: u8,
+    fan_gauge: u8,
+    /// When Off was pressed, until it's let go
+    off_since: Option<u64>,
+}
+
+impl ClimatePage {
+    /// Temperature gauge position, 0 (cold) to 36 (hot)
+    pub fn set_temp_gauge(&mut self, temp_gauge: u8) {
+        self.temp_gauge = temp_gauge;
+    }
+
+    /// Fan gauge position, 0 (low) to 32 (high)
+    pub fn set_fan_gauge(&mut self, fan_gauge: u8) {
+        self.fan_gauge = fan_gauge;
+    }
+
+    /// Buttons held down, from [`ButtonEvent::Held`]
+    fn held(&mut self, held: ButtonSet) {
+        if !held.contains(ButtonSet::OFF) {
+            self.off_since = None;
+        }
+    }
+}
+
+impl Page for ClimatePage {
+    fn draw<D: BinaryTarget>(&self, ctx: &PageContext, display: &mut D) {
+        let graphics = ctx.graphics;
+        let backend = ctx.backend;
+
+        graphics.draw_background(display);
+        let (mode, defrost) = climate::mode_state(backend.mode());
+        climate::MODE.draw(mode, display);
+        if defrost {
+            climate::DEFROST.draw(0, display);
+        }
+        climate::AC_TOGGLE.draw(backend.ac_toggle(), display);
+        climate::RECIRC_TOGGLE.draw(backend.recirc_toggle(), display);
+
+        // no pointer while out of range
+        climate::FAN_GAUGE.draw(self.fan_gauge.into(), display);
+        climate::TEMP_GAUGE.draw(self.temp_gauge.into(), display);
+
+        let set = ctx.config.tenths(backend.set_temp());
+        let ambient = ctx.config.ambient_tenths(backend.ambient_temp());
+        climate::INTERNAL_TEMP.draw(degrees(set).into(), display);
+        climate::AMBIENT_TEMP.draw(degrees(ambient).into(), display);
+    }
+
+    fn handle_button(&mut self, button: Button, now: u64) -> PageAction {
+        if button != Button::Off {
+            self.off_since = None;
+            return PageAction::Stay;
+        }
+        let since = *self.off_since.get_or_insert(now);
+        if now.saturating_sub(since) < PAGE_HOLD {
+            return PageAction::Stay;
+        }
+        self.off_since = None;
+        PageAction::Goto(PageId::Climate.next())
+    }
+}
+
+/// Temperatures in one list
+#[derive(Default)]
+pub struct SensorsPage;
+
+impl Page for SensorsPage {
+    fn draw<D: BinaryTarget>(&self, ctx: &PageContext, display: &mut D) {
+        let graphics = ctx.graphics;
+        let backend = ctx.backend;
+
+        graphics.draw_label("SENSORS", Point::new(2, 11), display);
+        graphics.draw_label("AMBIENT", Point::new(2, 27), display);
+        let ambient = ctx.config.ambient_tenths(backend.ambient_temp());
+        graphics.draw_value(
+            &format!("{}", degrees(ambient)),
+            Point::new(70, 27),
+            display,
+        );
+        graphics.draw_label("SET", Point::new(2, 43), display);
+        let set = ctx.config.tenths(backend.set_temp());
+        graphics.draw_value(&format!("{}", degrees(set)), Point::new(70, 43), display);
+    }
+
+    fn handle_button(&mut self, button: Button, _now: u64) -> PageAction {
+        page_nav(PageId::Sensors, button)
+    }
+}
+
+/// Raw sensor readings, the button matrix and the output pins, for finding
+/// out what's wrong in the car. TempUp/TempDown flip through the sections
+/// and Off goes back to the climate page, every other button just shows up
+/// as pressed.
+#[derive(Default)]
+pub struct DiagnosticsPage {
+    section: Section,
+}
+
+impl Page for DiagnosticsPage {
+    fn draw<D: BinaryTarget>(&self, ctx: &PageContext, display: &mut D) {
+        let snapshot = ctx.diagnostics.snapshot();
+        diagnostics::draw_section(self.section, &snapshot, ctx.backend, display);
+    }
+
+    fn handle_button(&mut self, button: Button, _now: u64) -> PageAction {
+        match button {
+            Button::TempUp => self.section = self.section.next(),
+            Button::TempDown => self.section = self.section.prev(),
+            Button::Off => return PageAction::Goto(PageId::Climate),
+            _ => (),
+        }
+        PageAction::Redraw
+    }
+
+    fn refresh_interval(&self) -> Option<u64> {
+        Some(REFRESH_INTERVAL)
+    }
+}
+
+/// The settings menu
+pub struct SettingsPage {
+    menu: Menu,
+    config: Config,
+}
+
+impl Default for SettingsPage {
+    fn default() -> Self {
+        SettingsPage {
+            menu: Menu::new(SETTINGS_MENU),
+            config: Config::default(),
+        }
+    }
+}
+
+impl SettingsPage {
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+impl Page for SettingsPage {
+    fn draw<D: BinaryTarget>(&self, ctx: &PageContext, display: &mut D) {
+        let items = self.menu.items();
+        let rows = items.len().min(3);
+        // keep the selection in the middle row where possible
+        let first = self
+            .menu
+            .selected()
+            .saturating_sub(1)
+            .min(items.len() - rows);
+
+        for (row, item) in items[first..first + rows].iter().enumerate() {
+            let selected = first + row == self.menu.selected();
+            ctx.graphics.draw_menu_row(
+                row as i32,
+                item.label,
+                &item.format(&self.config),
+                selected,
+                selected && self.menu.editing(),
+                display,
+            );
+        }
+    }
+
+    fn handle_button(&mut self, button: Button, _now: u64) -> PageAction {
+        match self.menu.handle_button(button, &mut self.config) {
+            MenuEvent::Stay => PageAction::Stay,
+            MenuEvent::Redraw | MenuEvent::Edited => PageAction::Redraw,
+            MenuEvent::Exit => {
+                self.menu.reset();
+                PageAction::Goto(PageId::Climate)
+            }
+        }
+    }
+}
+
+/// Time since the trip timer was last reset, Auto resets it
+#[derive(Default)]
+pub struct TripClockPage {
+    trip_start: u64,
+}
+
+impl Page for TripClockPage {
+    fn draw<D: BinaryTarget>(&self, ctx: &PageContext, display: &mut D) {
+        let graphics = ctx.graphics;
+        let trip = ctx.now.saturating_sub(self.trip_start) / 1000;
+        let up = ctx.now / 1000;
+
+        graphics.draw_label("TRIP", Point::new(2, 11), display);
+        _ = Text::with_baseline(
+            &format!("{:02}:{:02}:{:02}", trip / 3600, trip / 60 % 60, trip % 60),
+            Point::new(40, 8),
+            LARGE_SEGMENT_STYLE,
+            Baseline::Top,
+        )
+        .draw(display);
+        graphics.draw_label(
+            &format!("UP {:02}:{:02}", up / 3600, up / 60 % 60),
+            Point::new(190, 43),
+            display,
+        );
+    }
+
+    fn handle_button(&mut self, button: Button, now: u64) -> PageAction {
+        match button {
+            Button::Auto => {
+                self.trip_start = now;
+                PageAction::Redraw
+            }
+            _ => page_nav(PageId::TripClock, button),
+        }
+    }
+}
+
+struct ActiveTransition {
+    from: PageId,
+    transition: Transition,
+    /// When its first frame was drawn
+    started: Option<u64>,
+}
+
+/// One page drawn as a [`Scene`] of a transition
+struct PageScene<'a> {
+    screens: &'a ScreenManager,
+    page: PageId,
+    ctx: &'a PageContext<'a>,
+}
+
+impl Scene for PageScene<'_> {
+    fn draw<D: BinaryTarget>(&self, display: &mut D) {
+        self.screens.draw_page(self.page, self.ctx, display);
+    }
+}
+
+pub struct ScreenManager {
+    current: PageId,
+    active: Option<ActiveTransition>,
+    /// Share of the display's brightness the last frame wants
+    brightness: Progress,
+    /// The button that last changed page, its repeats don't go to the new
+    /// one until it's let go
+    page_press: Option<Button>,
+    climate: ClimatePage,
+    sensors: SensorsPage,
+    diagnostics: DiagnosticsPage,
+    settings: SettingsPage,
+    trip_clock: TripClockPage,
+}
+
+impl Default for ScreenManager {
+    fn default() -> Self {
+        ScreenManager::new()
+    }
+}
+
+impl ScreenManager {
+    pub fn new() -> Self {
+        ScreenManager {
+            current: PageId::Climate,
+            active: None,
+            brightness: Progress::FULL,
+            page_press: None,
+            climate: ClimatePage::default(),
+            sensors: SensorsPage,
+            diagnostics: DiagnosticsPage::default(),
+            settings: SettingsPage::default(),
+            trip_clock: TripClockPage::default(),
+        }
+    }
+
+    pub fn current(&self) -> PageId {
+        self.current
+    }
+
+    /// True while a transition still has frames left to draw
+    pub fn in_transition(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// How bright the display should be for the frame last drawn, as a
+    /// share of its brightness. Less than full only while fading between
+    /// pages.
+    pub fn brightness(&self) -> Progress {
+        self.brightness
+    }
+
+    pub fn climate_mut(&mut self) -> &mut ClimatePage {
+        &mut self.climate
+    }
+
+    /// Configuration as edited through the settings page
+    pub fn config(&self) -> &Config {
+        self.settings.config()
+    }
+
+    /// Switch pages, with a slide in the direction of travel
+    pub fn goto(&mut self, page: PageId) {
+        let transition = if page == self.current.next() {
+            Transition::SlideLeft
+        } else if page == self.current.prev() {
+            Transition::SlideRight
+        } else {
+            Transition::Cut
+        };
+        self.goto_with(page, transition);
+    }
+
+    pub fn goto_with(&mut self, page: PageId, transition: Transition) {
+        if page == self.current {
+            return;
+        }
+        self.active = match transition {
+            Transition::Cut => None,
+            _ => Some(ActiveTransition {
+                from: self.current,
+                transition,
+                started: None,
+            }),
+        };
+        self.current = page;
+        // only the climate page lets the buttons drive the climate control
+        BUTTONS_CAPTURED.store(page != PageId::Climate, Ordering::Relaxed);
+    }
+
+    /// Handle an event from [`crate::digidisplay::DigiDisplay::buttonreader`].
+    ///
+    /// [`MENU_CHORD`] opens the settings page from anywhere, and
+    /// [`DIAGNOSTICS_CHORD`] the diagnostics page.
+    /// Returns true if the screen needs drawing again.
+    pub fn handle_event(&mut self, event: ButtonEvent, now: u64) -> bool {
+        let chord = match event {
+            ButtonEvent::Press(button) => return self.handle_button(button, now),
+            ButtonEvent::Held(held) => {
+                self.page_press = self
+                    .page_press
+                    .filter(|&button| held.contains(button.into()));
+                self.climate.held(held);
+                return false;
+            }
+            ButtonEvent::Chord(chord) => chord,
+        };
+        let page = if chord == MENU_CHORD {
+            PageId::Settings
+        } else if chord == DIAGNOSTICS_CHORD {
+            PageId::Diagnostics
+        } else {
+            return false;
+        };
+        if self.current == page {
+            return false;
+        }
+        self.goto(page);
+        true
+    }
+
+    /// Hand a button press to the current page.
+    ///
+    /// While the button that changed page is still held, its repeats are
+    /// dropped rather than acted on by the new page too.
+    /// Returns true if the screen needs drawing again.
+    pub fn handle_button(&mut self, button: Button, now: u64) -> bool {
+        if self.page_press == Some(button) {
+            return false;
+        }
+        let action = match self.current {
+            PageId::Climate => self.climate.handle_button(button, now),
+            PageId::Sensors => self.sensors.handle_button(button, now),
+            PageId::Diagnostics => self.diagnostics.handle_button(button, now),
+            PageId::Settings => self.settings.handle_button(button, now),
+            PageId::TripClock => self.trip_clock.handle_button(button, now),
+        };
+        match action {
+            PageAction::Stay => false,
+            PageAction::Redraw => true,
+            PageAction::Goto(page) => {
+                self.page_press = Some(button);
+                self.goto(page);
+                true
+            }
+        }
+    }
+
+    /// Milliseconds between redraws the current page wants while nothing
+    /// else changes, if it wants any
+    pub fn refresh_interval(&self) -> Option<u64> {
+        match self.current {
+            PageId::Climate => self.climate.refresh_interval(),
+            PageId::Sensors => self.sensors.refresh_interval(),
+            PageId::Diagnostics => self.diagnostics.refresh_interval(),
+            PageId::Settings => self.settings.refresh_interval(),
+            PageId::TripClock => self.trip_clock.refresh_interval(),
+        }
+    }
+
+    fn draw_page<D: BinaryTarget>(&self, page: PageId, ctx: &PageContext, display: &mut D) {
+        match page {
+            PageId::Climate => self.climate.draw(ctx, display),
+            PageId::Sensors => self.sensors.draw(ctx, display),
+            PageId::Diagnostics => self.diagnostics.draw(ctx, display),
+            PageId::Settings => self.settings.draw(ctx, display),
+            PageId::TripClock => self.trip_clock.draw(ctx, display),
+        }
+    }
+
+    /// Draw the current page, or the frame of a running transition that's
+    /// due now.
+    ///
+    /// The display is cleared first.
+    pub fn draw<D: BinaryTarget>(&mut self, ctx: &PageContext, display: &mut D) {
+        _ = display.clear(BinaryColor::Off);
+        self.brightness = Progress::FULL;
+
+        let Some(active) = &mut self.active else {
+            self.draw_page(self.current, ctx, display);
+            return;
+        };
+        let started = *active.started.get_or_insert(ctx.now);
+        let elapsed = ctx.now.saturating_sub(started) as u32;
+        let progress = Progress::through(elapsed, active.transition.duration());
+        if progress.is_done() {
+            self.active = None;
+            self.draw_page(self.current, ctx, display);
+            return;
+        }
+
+        let (from, transition) = (active.from, active.transition);
+        let scene = |page| PageScene {
+            screens: self,
+            page,
+            ctx,
+        };
+        let brightness = draw_transition(
+            transition,
+            progress,
+            &scene(from),
+            &scene(self.current),
+            display,
+        );
+        self.brightness = brightness;
+    }
+}
@@ -0,0 +1,171 @@
+//! Number formatting for the two-and-a-half digit readouts on the segment LCD.
+//!
+//! Both the ambient and set readouts have the same layout: a "−" indicator,
+//! a "1" hundreds indicator and two seven segment digits, so anything from
+//! −99 to 199 can be shown. This module only decides *what* goes where; the
+//! mapping onto the actual segment bits lives in [`crate::digidisplay`].
+
+/// Segment masks in the order top, top-right, bottom-right, bottom,
+/// bottom-left, top-left, middle.
+pub const SEG_T: u8 = 0b000_0001;
+pub const SEG_TR: u8 = 0b000_0010;
+pub const SEG_BR: u8 = 0b000_0100;
+pub const SEG_B: u8 = 0b000_1000;
+pub const SEG_BL: u8 = 0b001_0000;
+pub const SEG_TL: u8 = 0b010_0000;
+pub const SEG_M: u8 = 0b100_0000;
+
+/// A single character on a seven segment digit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Glyph {
+    Blank,
+    /// Decimal digit, only 0–9 are valid
+    Digit(u8),
+    Dash,
+    H,
+    I,
+    L,
+    O,
+}
+
+impl Glyph {
+    /// Segments lit for this glyph, see the `SEG_*` constants.
+    pub fn segments(self) -> u8 {
+        match self {
+            Glyph::Blank => 0,
+            Glyph::Digit(0) | Glyph::O => SEG_T | SEG_TR | SEG_BR | SEG_B | SEG_BL | SEG_TL,
+            Glyph::Digit(1) | Glyph::I => SEG_TR | SEG_BR,
+            Glyph::Digit(2) => SEG_T | SEG_TR | SEG_M | SEG_BL | SEG_B,
+            Glyph::Digit(3) => SEG_T | SEG_TR | SEG_M | SEG_BR | SEG_B,
+            Glyph::Digit(4) => SEG_TL | SEG_M | SEG_TR | SEG_BR,
+            Glyph::Digit(5) => SEG_T | SEG_TL | SEG_M | SEG_BR | SEG_B,
+            Glyph::Digit(6) => SEG_T | SEG_TL | SEG_M | SEG_BR | SEG_B | SEG_BL,
+            Glyph::Digit(7) => SEG_T | SEG_TR | SEG_BR,
+            Glyph::Digit(8) => SEG_T | SEG_TR | SEG_BR | SEG_B | SEG_BL | SEG_TL | SEG_M,
+            Glyph::Digit(9) => SEG_T | SEG_TR | SEG_TL | SEG_M | SEG_BR | SEG_B,
+            Glyph::Digit(_) => 0,
+            Glyph::Dash => SEG_M,
+            Glyph::H => SEG_TL | SEG_TR | SEG_M | SEG_BL | SEG_BR,
+            Glyph::L => SEG_TL | SEG_BL | SEG_B,
+        }
+    }
+}
+
+/// Everything needed to light one readout.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Readout {
+    pub neg: bool,
+    pub hund: bool,
+    pub tens: Glyph,
+    pub ones: Glyph,
+}
+
+impl Readout {
+    pub const fn new(neg: bool, hund: bool, tens: Glyph, ones: Glyph) -> Self {
+        Readout {
+            neg,
+            hund,
+            tens,
+            ones,
+        }
+    }
+}
+
+/// What to show when a value doesn't fit in −99..=199
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// "--" for both directions
+    #[default]
+    Dashes,
+    /// "HI" above the range, "LO" below it
+    HiLo,
+}
+
+/// Lowest value that fits on a readout
+pub const MIN_VALUE: i32 = -99;
+/// Highest value that fits on a readout
+pub const MAX_VALUE: i32 = 199;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SegFormat {
+    /// Blank the tens digit instead of showing a leading zero
+    pub blank_leading: bool,
+    /// Show tenths in [`SegFormat::format_tenths`] when the value is small enough
+    pub decimal: bool,
+    pub overflow: Overflow,
+}
+
+impl Default for SegFormat {
+    fn default() -> Self {
+        SegFormat {
+            blank_leading: true,
+            decimal: false,
+            overflow: Overflow::default(),
+        }
+    }
+}
+
+impl SegFormat {
+    /// Format a whole number.
+    pub fn format(&self, value: i16) -> Readout {
+        self.format_i32(value.into())
+    }
+
+    /// Format a value given in tenths (e.g. 215 for 21.5°).
+    ///
+    /// With [`decimal`](Self::decimal) set, values from −9.9 to 19.9 keep their
+    /// tenths digit: the panel has no decimal point, so the tenths go in the
+    /// ones digit and the whole part moves up one place (19.5 shows as "1 9 5").
+    /// Anything else is rounded half away from zero to a whole number.
+    pub fn format_tenths(&self, tenths: i16) -> Readout {
+        let tenths = i32::from(tenths);
+        if self.decimal && (MIN_VALUE..=MAX_VALUE).contains(&tenths) {
+            let mut readout = self.format_i32(tenths);
+            // a leading zero is the whole part here, so it always shows
+            if readout.tens == Glyph::Blank {
+                readout.tens = Glyph::Digit(0);
+            }
+            return readout;
+        }
+        let rounded = if tenths < 0 {
+            (tenths - 5) / 10
+        } else {
+            (tenths + 5) / 10
+        };
+        self.format_i32(rounded)
+    }
+
+    fn format_i32(&self, value: i32) -> Readout {
+        if value > MAX_VALUE {
+            return self.overflow(true);
+        }
+        if value < MIN_VALUE {
+            return self.overflow(false);
+        }
+
+        let neg = value < 0;
+        let mut n = value.unsigned_abs();
+        let hund = n >= 100;
+        if hund {
+            n -= 100;
+        }
+        let tens = (n / 10) as u8;
+        let ones = (n % 10) as u8;
+
+        let tens = if tens == 0 && !hund && self.blank_leading {
+            Glyph::Blank
+        } else {
+            Glyph::Digit(tens)
+        };
+
+        Readout::new(neg, hund, tens, Glyph::Digit(ones))
+    }
+
+    fn overflow(&self, high: bool) -> Readout {
+        match (self.overflow, high) {
+            (Overflow::Dashes, _) => Readout::new(false, false, Glyph::Dash, Glyph::Dash),
+            (Overflow::HiLo, true) => Readout::new(false, false, Glyph::H, Glyph::I),
+            (Overflow::HiLo, false) => Readout::new(false, false, Glyph::L, Glyph::O),
+        }
+    }
+}
@@ -0,0 +1,444 @@
+//! User configuration and the menu used to change it from the VFD.
+//!
+//! The menu is a flat list of [`MenuItem`]s, each bound to one field of
+//! [`Config`] through a getter/setter pair. Items are typed (on/off, a
+//! number range or a list of named choices) so the same [`Menu`] logic can
+//! step any of them with the TempUp/TempDown buttons.
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::buttons::{Button, ButtonEvent, ButtonSet};
+
+/// How hard auto mode runs the fan to bring the cabin to the set temperature
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Aggressiveness {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Added to the automatic VFD brightness, out of 1023
+    pub brightness_offset: i16,
+    /// Show temperatures in Celsius instead of Fahrenheit
+    pub celsius: bool,
+    /// Added to the ambient sensor reading, in degrees of the unit shown
+    pub ambient_offset: i8,
+    /// How hard auto mode works, see
+    /// [`ClimateControlBacker::run_auto`](crate::climatecontrol::ClimateControlBacker::run_auto)
+    pub auto_aggressiveness: Aggressiveness,
+}
+
+impl Config {
+    /// A temperature in °F as tenths of a degree in the unit shown
+    pub fn tenths(&self, fahrenheit: i8) -> i16 {
+        let tenths = i16::from(fahrenheit) * 10;
+        if !self.celsius {
+            return tenths;
+        }
+        // (F - 32) * 5 / 9, rounded half away from zero
+        let twice = (tenths - 320) * 10;
+        (twice + 9 * twice.signum()) / 18
+    }
+
+    /// An ambient sensor reading in °F, with [`Config::ambient_offset`]
+    /// added, as [`Config::tenths`]
+    pub fn ambient_tenths(&self, fahrenheit: i8) -> i16 {
+        self.tenths(fahrenheit) + i16::from(self.ambient_offset) * 10
+    }
+}
+
+/// Tenths of a degree rounded half away from zero to whole degrees
+pub fn degrees(tenths: i16) -> i16 {
+    (tenths + 5 * tenths.signum()) / 10
+}
+
+/// The kind of value a [`MenuItem`] edits
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    /// 0 or 1, shown as OFF/ON
+    Bool,
+    /// A number from `min` to `max` inclusive, moved by `step`
+    Range { min: i32, max: i32, step: i32 },
+    /// An index into `names`, wrapping at either end
+    Enum { names: &'static [&'static str] },
+}
+
+pub struct MenuItem {
+    pub label: &'static str,
+    pub kind: ItemKind,
+    get: fn(&Config) -> i32,
+    set: fn(&mut Config, i32),
+}
+
+impl MenuItem {
+    pub const fn new(
+        label: &'static str,
+        kind: ItemKind,
+        get: fn(&Config) -> i32,
+        set: fn(&mut Config, i32),
+    ) -> Self {
+        MenuItem {
+            label,
+            kind,
+            get,
+            set,
+        }
+    }
+
+    pub fn value(&self, config: &Config) -> i32 {
+        (self.get)(config)
+    }
+
+    /// Store `value`, clamped or wrapped to what the item allows
+    pub fn set_value(&self, config: &mut Config, value: i32) {
+        let value = match self.kind {
+            ItemKind::Bool => value.clamp(0, 1),
+            ItemKind::Range { min, max, .. } => value.clamp(min, max),
+            ItemKind::Enum { names } => value.rem_euclid(names.len() as i32),
+        };
+        (self.set)(config, value);
+    }
+
+    /// Move the value one step up or down
+    pub fn step(&self, config: &mut Config, up: bool) {
+        let value = self.value(config);
+        let value = match self.kind {
+            ItemKind::Bool => 1 - value,
+            ItemKind::Range { step, .. } if up => value + step,
+            ItemKind::Range { step, .. } => value - step,
+            ItemKind::Enum { .. } if up => value + 1,
+            ItemKind::Enum { .. } => value - 1,
+        };
+        self.set_value(config, value);
+    }
+
+    /// The value as shown in the menu
+    pub fn format(&self, config: &Config) -> String {
+        let value = self.value(config);
+        match self.kind {
+            ItemKind::Bool if value != 0 => String::from("ON"),
+            ItemKind::Bool => String::from("OFF"),
+            ItemKind::Range { .. } => format!("{value}"),
+            ItemKind::Enum { names } => String::from(names[value as usize]),
+        }
+    }
+}
+
+pub const SETTINGS_MENU: &[MenuItem] = &[
+    MenuItem::new(
+        "BRIGHTNESS",
+        ItemKind::Range {
+            min: -512,
+            max: 512,
+            step: 32,
+        },
+        |c| c.brightness_offset.into(),
+        |c, v| c.brightness_offset = v as i16,
+    ),
+    MenuItem::new(
+        "CELSIUS",
+        ItemKind::Bool,
+        |c| c.celsius.into(),
+        |c, v| c.celsius = v != 0,
+    ),
+    MenuItem::new(
+        "AMB OFFSET",
+        ItemKind::Range {
+            min: -10,
+            max: 10,
+            step: 1,
+        },
+        |c| c.ambient_offset.into(),
+        |c, v| c.ambient_offset = v as i8,
+    ),
+    MenuItem::new(
+        "AUTO MODE",
+        ItemKind::Enum {
+            names: &["LOW", "NORMAL", "HIGH"],
+        },
+        |c| c.auto_aggressiveness as i32,
+        |c, v| {
+            c.auto_aggressiveness = match v {
+                0 => Aggressiveness::Low,
+                2 => Aggressiveness::High,
+                _ => Aggressiveness::Normal,
+            }
+        },
+    ),
+];
+
+/// What happened after the menu handled a button
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MenuEvent {
+    /// Button did nothing here
+    Stay,
+    /// The selection or edit state moved
+    Redraw,
+    /// A config value changed
+    Edited,
+    /// Leave the menu
+    Exit,
+}
+
+/// Scrolling list of items.
+///
+/// TempUp/TempDown move the selection, Auto starts editing the selected
+/// item (or flips it straight away if it is a [`ItemKind::Bool`]) and Off
+/// leaves the menu. While editing, TempUp/TempDown change the value, Auto
+/// keeps it and Off puts the old value back.
+pub struct Menu {
+    items: &'static [MenuItem],
+    selected: usize,
+    /// Value from before editing started, to restore on cancel
+    editing: Option<i32>,
+}
+
+impl Menu {
+    pub fn new(items: &'static [MenuItem]) -> Self {
+        Menu {
+            items,
+            selected: 0,
+            editing: None,
+        }
+    }
+
+    pub fn items(&self) -> &'static [MenuItem] {
+        self.items
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    /// Go back to the first item, not editing
+    pub fn reset(&mut self) {
+        self.selected = 0;
+        self.editing = None;
+    }
+
+    pub fn handle_button(&mut self, button: Button, config: &mut Config) -> MenuEvent {
+        let Some(item) = self.items.get(self.selected) else {
+            return MenuEvent::Exit;
+        };
+
+        match (self.editing, button) {
+            (Some(_), Button::TempUp) => {
+                item.step(config, true);
+                MenuEvent::Edited
+            }
+            (Some(_), Button::TempDown) => {
+                item.step(config, false);
+                MenuEvent::Edited
+            }
+            (Some(_), Button::Auto) => {
+                self.editing = None;
+                MenuEvent::Redraw
+            }
+            (Some(original), Button::Off) => {
+                item.set_value(config, original);
+                self.editing = None;
+                MenuEvent::Edited
+            }
+            (None, Button::TempUp) => {
+                self.selected = self.selected.checked_sub(1).unwrap_or(self.items.len() - 1);
+                MenuEvent::Redraw
+            }
+            (None, Button::TempDown) => {
+                self.selected = (self.selected + 1) % self.items.len();
+                MenuEvent::Redraw
+            }
+            (None, Button::Auto) if item.kind == ItemKind::Bool => {
+                item.step(config, true);
+                MenuEvent::Edited
+            }
+            (None, Button::Auto) => {
+                self.editing = Some(item.value(config));
+                MenuEvent::Redraw
+            }
+            (None, Button::Off) => MenuEvent::Exit,
+            _ => MenuEvent::Stay,
+        }
+    }
+}
+
+/// Buttons to hold together to open the settings menu
+pub const MENU_CHORD: ButtonSet = ButtonSet::AUTO.union(ButtonSet::OFF);
+/// Number of hold repeats (about 100ms each) before the chord counts
+pub const MENU_CHORD_HOLD: u8 = 10;
+
+/// Buttons to hold together to open the diagnostics page, for as long as
+/// [`MENU_CHORD_HOLD`]
+pub const DIAGNOSTICS_CHORD: ButtonSet = ButtonSet::DEMIST.union(ButtonSet::RECIRC);
+
+/// Every button that starts a chord
+const CHORD_BUTTONS: ButtonSet = MENU_CHORD.union(DIAGNOSTICS_CHORD);
+
+/// Spots a set of buttons held together for a while
+pub struct ChordDetector {
+    chord: ButtonSet,
+    hold: u8,
+    count: u8,
+}
+
+impl ChordDetector {
+    pub const fn new(chord: ButtonSet, hold: u8) -> Self {
+        ChordDetector {
+            chord,
+            hold,
+            count: 0,
+        }
+    }
+
+    /// Feed the currently held buttons, returns true once when the chord has
+    /// been held for long enough
+    pub fn update(&mut self, held: ButtonSet) -> bool {
+        if !held.contains(self.chord) {
+            self.count = 0;
+            return false;
+        }
+        self.count = self.count.saturating_add(1);
+        self.count == self.hold
+    }
+}
+
+/// What [`ChordGate`] lets through of a press or a scan
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Gated {
+    /// For the screens
+    pub event: Option<ButtonEvent>,
+    /// Buttons whose climate control action is due now
+    pub actions: ButtonSet,
+}
+
+/// Sits between the button reader and everything the buttons drive, so
+/// going for a chord doesn't work the climate control or the page on screen
+/// on the way.
+///
+/// While any chord's button is down the climate actions are held
+/// back, and happen once each when it's let go if no chord went off. Once a
+/// chord goes off nothing more gets through until every button is up, then
+/// a [`ButtonEvent::Held`] with nothing held says so.
+///
+/// While blanked (see [`ChordGate::set_blanked`]) the buttons still reach
+/// the screens, but never the climate control.
+pub struct ChordGate {
+    menu: ChordDetector,
+    diagnostics: ChordDetector,
+    held: ButtonSet,
+    /// A chord went off and not every button has been let go since
+    swallowing: bool,
+    /// Actions waiting for the chord buttons to be let go
+    pending: ButtonSet,
+    /// The segment display is blanked, see [`ChordGate::set_blanked`]
+    blanked: bool,
+}
+
+impl Default for ChordGate {
+    fn default() -> Self {
+        ChordGate::new()
+    }
+}
+
+impl ChordGate {
+    pub const fn new() -> Self {
+        ChordGate {
+            menu: ChordDetector::new(MENU_CHORD, MENU_CHORD_HOLD),
+            diagnostics: ChordDetector::new(DIAGNOSTICS_CHORD, MENU_CHORD_HOLD),
+            held: ButtonSet::empty(),
+            swallowing: false,
+            pending: ButtonSet::empty(),
+            blanked: false,
+        }
+    }
+
+    /// Follow the segment display being blanked while the car is off, so
+    /// nothing can change the climate control unseen
+    pub fn set_blanked(&mut self, blanked: bool) {
+        self.blanked = blanked;
+    }
+
+    /// A press, or hold repeat, of `button`. `captured` is
+    /// [`crate::buttons::BUTTONS_CAPTURED`] from before the press is handed
+    /// on, captured presses never work the climate control.
+    pub fn press(&mut self, button: Button, captured: bool) -> Gated {
+        if self.swallowing {
+            return Gated::default();
+        }
+        let event = Some(ButtonEvent::Press(button));
+        if captured || self.blanked {
+            return Gated {
+                event,
+                ..Gated::default()
+            };
+        }
+        if self.held.union(button.into()).intersects(CHORD_BUTTONS) {
+            self.pending |= button.into();
+            return Gated {
+                event,
+                ..Gated::default()
+            };
+        }
+        Gated {
+            event,
+            actions: button.into(),
+        }
+    }
+
+    /// Everything held down, on each repeat of a held button and whenever a
+    /// button is let go. Held back actions are dropped if the buttons got
+    /// captured while they waited.
+    pub fn scan(&mut self, held: ButtonSet, captured: bool) -> Gated {
+        self.held = held;
+        // keep counting while swallowing so a chord still held can't go off again
+        let menu = self.menu.update(held);
+        let diagnostics = self.diagnostics.update(held);
+        if self.swallowing {
+            self.swallowing = !held.is_empty();
+            return Gated {
+                event: (!self.swallowing).then_some(ButtonEvent::Held(held)),
+                ..Gated::default()
+            };
+        }
+
+        let chord = if menu {
+            MENU_CHORD
+        } else if diagnostics {
+            DIAGNOSTICS_CHORD
+        } else {
+            ButtonSet::empty()
+        };
+        if !chord.is_empty() {
+            self.swallowing = true;
+            self.pending = ButtonSet::empty();
+            return Gated {
+                event: Some(ButtonEvent::Chord(chord)),
+                ..Gated::default()
+            };
+        }
+
+        let event = Some(ButtonEvent::Held(held));
+        if held.intersects(CHORD_BUTTONS) {
+            return Gated {
+                event,
+                ..Gated::default()
+            };
+        }
+        let pending = core::mem::take(&mut self.pending);
+        Gated {
+            event,
+            actions: if captured || self.blanked {
+                ButtonSet::empty()
+            } else {
+                pending
+            },
+        }
+    }
+}
@@ -2,6 +2,8 @@ use libm::log;
 
 use embassy_rp::adc::{Adc, Blocking, Channel};
 
+use crate::diagnostics::{DIAGNOSTICS, Reading, Sensor, divider_fault, onboard_fault};
+
 pub fn average(numbers: &[u16]) -> u16 {
     let len = numbers.len();
     if len == 0 {
@@ -74,28 +76,60 @@ impl<'a> Thermistor<'a> {
         }
     }
 
-    pub fn measure_adcs(&mut self) -> [f64; 2] {
+    /// The last of 128 samples of each sensor and their average
+    fn sample(&mut self) -> [(u16, u16); 2] {
         let mut samples1: [u16; 128] = [0u16; 128];
         for sample in &mut samples1 {
             *sample = self.adc.blocking_read(&mut self.sensor1).unwrap();
         }
-        let average1: f64 = average(&samples1).into();
         let mut samples2: [u16; 128] = [0u16; 128];
         for sample in &mut samples2 {
             *sample = self.adc.blocking_read(&mut self.sensor2).unwrap();
         }
-        let average2: f64 = average(&samples2).into();
-        [average1, average2]
+        [
+            (samples1[127], average(&samples1)),
+            (samples2[127], average(&samples2)),
+        ]
+    }
+
+    pub fn measure_adcs(&mut self) -> [f64; 2] {
+        self.sample().map(|(_, average)| average.into())
     }
 
+    /// Both temperatures in °F, also recorded for the diagnostics page along
+    /// with the ADC counts and the thermistor's resistance
     pub fn measure_temp(&mut self) -> [f64; 2] {
-        let adc = self.measure_adcs();
-        let res1 = self.adc_to_resistance(adc[0]);
+        let [(raw1, average1), (raw2, average2)] = self.sample();
+        let res1 = self.adc_to_resistance(average1.into());
         let temp1 = self.calculate_temperature(res1);
         let tempc1 = kelvin_to_celsius(temp1);
         let tempf1 = celcius_to_fahrenheit(tempc1);
-        let res2 = onboard_temp_calculate(adc[1]);
+        let res2 = onboard_temp_calculate(average2.into());
         let tempf2 = celcius_to_fahrenheit(res2);
+
+        let tenths1 = (tempf1 * 10.0) as i32;
+        DIAGNOSTICS.record(
+            Sensor::Ambient,
+            Reading {
+                raw: raw1,
+                filtered: average1,
+                // saturates with the thermistor unplugged
+                ohms: Some(res1 as u32),
+                tenths: tenths1,
+                fault: divider_fault(average1),
+            },
+        );
+        let tenths2 = (tempf2 * 10.0) as i32;
+        DIAGNOSTICS.record(
+            Sensor::Onboard,
+            Reading {
+                raw: raw2,
+                filtered: average2,
+                ohms: None,
+                tenths: tenths2,
+                fault: onboard_fault(tenths2),
+            },
+        );
         [tempf1, tempf2]
     }
 
@@ -1,32 +1,29 @@
-use eei_vfd::{gp1287bi::VFD256x50, prelude::EEIDisplay};
-use embassy_embedded_hal::shared_bus::blocking::spi::SpiDeviceWithConfig;
+use core::convert::Infallible;
+
+use eei_vfd::{
+    gp1287bi::{Display256x50, GRAM_COLUMNS, GRAM_ROWS, HEIGHT, VFD256x50},
+    prelude::{Display as _, EEIDisplay},
+};
+use embassy_embedded_hal::shared_bus::{SpiDeviceError, blocking::spi::SpiDeviceWithConfig};
 use embassy_rp::{
     gpio::Output,
     peripherals::SPI0,
     spi::{Blocking, Spi},
 };
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use embassy_time::{Delay, Duration, Ticker};
-use embedded_graphics::{
-    framebuffer::{Framebuffer, buffer_size},
-    pixelcolor::{
-        BinaryColor,
-        raw::{LittleEndian, RawU1},
-    },
-};
-use embedded_graphics_transform::Transpose;
+use embassy_time::{Delay, Duration, Instant, Ticker};
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+use embedded_graphics_transform::{Orient, Orientation};
 
+use crate::animation::{FRAME_TIME, FramePacer, Progress, Sequence};
+use crate::brightness::{BrightnessController, Dimmable, LightInputs};
+use crate::burnin::{BurnInConfig, BurnInGuard, IdleState};
 use crate::climatecontrol::ClimateControlBacker;
-use crate::{map_i32, vfdgraphics::Graphics};
-
-pub type InternalFrameBuffer = Framebuffer<
-    BinaryColor,
-    RawU1,
-    LittleEndian,
-    128,
-    256,
-    { buffer_size::<BinaryColor>(128, 256) },
->;
+use crate::diagnostics::DIAGNOSTICS;
+use crate::digidisplay::ButtonEvent;
+use crate::framediff::{FrameLayout, changed_spans};
+use crate::screens::{PageContext, SCREEN_WIDTH, ScreenManager};
+use crate::vfdgraphics::{Graphics, boot};
 
 pub type VFD<'a> = VFD256x50<
     SpiDeviceWithConfig<'a, CriticalSectionRawMutex, Spi<'a, SPI0, Blocking>, Output<'a>>,
@@ -34,18 +31,78 @@ pub type VFD<'a> = VFD256x50<
     Delay,
 >;
 
+/// Anything that can go wrong talking to the VFD
+pub type VfdError =
+    eei_vfd::error::Error<SpiDeviceError<embassy_rp::spi::Error, Infallible>, Infallible>;
+
+impl Dimmable for VFD<'_> {
+    type Error = VfdError;
+
+    fn set_brightness(&mut self, level: u16) -> Result<(), Self::Error> {
+        EEIDisplay::set_brightness(self, level.into())
+    }
+}
+
+/// Which way up the VFD is mounted in the dash, e.g. [`Orientation::Rotate180`]
+/// with the connector at the top
+pub const VFD_ORIENTATION: Orientation = Orientation::Rotate0;
+
+const _: () = assert!(
+    !VFD_ORIENTATION.is_transposing(),
+    "the pages are laid out for a wide panel"
+);
+
+/// Frames as the VFD's display RAM has them, only the rows on the panel
+/// count when diffing
+const FRAME_LAYOUT: FrameLayout = FrameLayout {
+    column_bytes: (GRAM_ROWS / 8) as usize,
+    visible_bytes: HEIGHT.div_ceil(8) as usize,
+};
+
+/// Room for the biggest partial write, every visible byte of the frame
+const SPAN_BYTES: usize = GRAM_COLUMNS as usize * FRAME_LAYOUT.visible_bytes;
+
 pub struct Display<'a> {
     vfd: VFD<'a>,
-    framebuffer: Transpose<InternalFrameBuffer>,
+    /// The back buffer, where the next frame is drawn
+    framebuffer: Orient<Display256x50>,
+    /// The front buffer, what the VFD's display RAM holds
+    front: Display256x50,
+    /// False when the display RAM may not match `front`, after a reset or
+    /// a failed write
+    in_sync: bool,
+    /// Changed spans are gathered here to be sent
+    span_buffer: [u8; SPAN_BYTES],
     graphics: Graphics,
-    temp_gauge: u8,
-    fan_gauge: u8,
-    backend: &'a ClimateControlBacker,
+    screens: ScreenManager,
+    brightness: BrightnessController,
+    burnin: BurnInGuard,
+    /// Dash lights as of the last [`Display::update_light`]
+    lights_on: bool,
+    /// Layout offset the frame on the VFD was drawn with
+    shift: Point,
+    /// In deep sleep with the ignition off
+    asleep: bool,
+    /// The VFD is dimmed below the brightness controller's level for a
+    /// fade between pages
+    transition_dimmed: bool,
+    /// When the current page was last drawn
+    drawn_at: Instant,
+    /// The climate control as of the last [`Display::set_backend`]
+    backend: ClimateControlBacker,
+}
+
+/// Level the boot animation fades up to, before there are any light readings
+const BOOT_BRIGHTNESS: u16 = 255;
+
+/// Seconds since boot, for the burn-in timers
+fn now_secs() -> u32 {
+    Instant::now().as_secs() as u32
 }
 
 impl<'a> Display<'a> {
     pub fn new(
-        backend: &'a ClimateControlBacker,
+        backend: &ClimateControlBacker,
         spi_bus: SpiDeviceWithConfig<
             'a,
             CriticalSectionRawMutex,
@@ -53,91 +110,292 @@ impl<'a> Display<'a> {
             Output<'a>,
         >,
         reset: Output<'a>,
-    ) -> Self {
-        let mut vfd: VFD = EEIDisplay::new(spi_bus, reset, Delay).unwrap();
-        vfd.clear_frame().unwrap();
+    ) -> Result<Self, VfdError> {
+        let mut vfd: VFD = EEIDisplay::new(spi_bus, reset, Delay)?;
+        vfd.clear_frame()?;
 
-        let fb = InternalFrameBuffer::new();
-        let framebuffer = Transpose::new(fb);
+        let framebuffer = Orient::new(VFD_ORIENTATION, Display256x50::default());
         let graphics = Graphics::load();
-
-        let temp_gauge: u8 = 0;
-        let fan_gauge: u8 = 0;
+        let screens = ScreenManager::new();
 
         let d = Display {
             vfd,
             framebuffer,
+            // cleared to the background just now
+            front: Display256x50::default(),
+            in_sync: true,
+            span_buffer: [0; SPAN_BYTES],
             graphics,
-            temp_gauge,
-            fan_gauge,
-            backend,
+            screens,
+            brightness: BrightnessController::new(0),
+            burnin: BurnInGuard::new(BurnInConfig::default(), now_secs()),
+            lights_on: true,
+            shift: Point::zero(),
+            asleep: false,
+            transition_dimmed: false,
+            drawn_at: Instant::now(),
+            backend: *backend,
         };
-        d
+        Ok(d)
+    }
+
+    /// Play the boot animation, fading up to a fixed brightness until the
+    /// first light readings come in
+    pub async fn play_boot_animation(&mut self) -> Result<(), VfdError> {
+        self.brightness.set_target(BOOT_BRIGHTNESS);
+        self.play(&boot::SEQUENCE).await
+    }
+
+    /// Play `sequence` from start to finish, a frame every [`FRAME_TIME`].
+    ///
+    /// The sequence's brightness is a share of the brightness controller's
+    /// target, and it's left at the level of the last frame.
+    pub async fn play(&mut self, sequence: &Sequence) -> Result<(), VfdError> {
+        let full = i32::from(self.brightness.target());
+        let mut ticker = Ticker::every(Duration::from_millis(FRAME_TIME.into()));
+        let mut pacer = FramePacer::new(FRAME_TIME);
+        let start = Instant::now();
+        let mut at = 0;
+        let mut level = None;
+        loop {
+            sequence.draw(at, &mut self.framebuffer);
+            self.present()?;
+            let wanted = sequence.brightness(at).of(full) as u16;
+            if level != Some(wanted) {
+                Dimmable::set_brightness(&mut self.vfd, wanted)?;
+                level = Some(wanted);
+            }
+            if at >= sequence.length {
+                self.brightness.jump_to(wanted);
+                return Ok(());
+            }
+
+            let next = pacer.advance(start.elapsed().as_millis() as u32);
+            if next.late {
+                // on from here instead of catching up on the missed ticks
+                ticker.reset();
+            } else {
+                ticker.next().await;
+            }
+            at = next.at.min(sequence.length);
+        }
+    }
+
+    /// Draw the running page transition to the end, a frame every
+    /// [`FRAME_TIME`]. Returns straight away without one.
+    pub async fn run_transition(&mut self) -> Result<(), VfdError> {
+        let mut ticker = Ticker::every(Duration::from_millis(FRAME_TIME.into()));
+        let mut pacer = FramePacer::new(FRAME_TIME);
+        let start = Instant::now();
+        while self.screens.in_transition() {
+            self.update_display()?;
+            // the transition keeps its own time, this only paces the frames
+            if pacer.advance(start.elapsed().as_millis() as u32).late {
+                ticker.reset();
+            } else {
+                ticker.next().await;
+            }
+        }
+        Ok(())
     }
 
-    pub async fn draw_boot_image(&mut self) {
-        let mut ticker = Ticker::every(Duration::from_secs(1));
+    /// Feed new light readings to the brightness controller
+    pub fn update_light(&mut self, inputs: LightInputs) {
+        self.lights_on = inputs.lights_on();
+        self.brightness.update(inputs);
+    }
+
+    pub fn idle_state(&self) -> IdleState {
+        self.burnin.state()
+    }
+
+    /// Move the burn-in protection along, call this every second or so.
+    ///
+    /// Dims, blanks or shifts the layout as needed. Returns true if the
+    /// display needs updating: the screen saver wants a new frame each call.
+    pub fn update_idle(&mut self) -> bool {
+        let now = now_secs();
+        let changed = self.burnin.update(now, self.lights_on).is_some();
+        self.brightness.set_limit(self.burnin.brightness_limit());
+
+        match self.burnin.state() {
+            IdleState::Saver => true,
+            IdleState::Blank => changed,
+            IdleState::Active | IdleState::Dimmed => {
+                changed || self.burnin.shift(now) != self.shift
+            }
+        }
+    }
 
-        self.vfd.clear_frame().unwrap();
-        self.graphics.draw_boot_image(&mut self.framebuffer);
+    /// Take one fade step towards the wanted brightness, call this regularly
+    /// (every 20ms or so).
+    ///
+    /// Returns true while still fading.
+    pub fn fade_brightness(&mut self) -> Result<bool, VfdError> {
+        if self.asleep || self.transition_dimmed {
+            return Ok(false);
+        }
+        self.brightness.apply(&mut self.vfd)
+    }
 
-        self.vfd.update_frame(self.framebuffer.data()).unwrap();
-        self.vfd.set_brightness(128).unwrap();
-        ticker.next().await;
-        self.vfd.set_brightness(255).unwrap();
-        ticker.next().await;
+    pub fn asleep(&self) -> bool {
+        self.asleep
     }
 
-    // return mutable refrence to framebuffer to use outside this struct
-    pub fn use_frame_buffer(&mut self) -> &mut InternalFrameBuffer {
+    /// Put the VFD into deep sleep, for when the ignition goes off
+    pub fn sleep(&mut self) -> Result<(), VfdError> {
+        if self.asleep {
+            return Ok(());
+        }
+        self.vfd.sleep()?;
+        self.asleep = true;
+        Ok(())
+    }
+
+    /// Bring the VFD back from [`Display::sleep`] as it was.
+    ///
+    /// Waking resets the VFD, so the brightness and the frame are sent again.
+    /// The burn-in timers start over as if a button had been pressed.
+    pub fn wake(&mut self) -> Result<(), VfdError> {
+        if !self.asleep {
+            return Ok(());
+        }
+        self.vfd.wake_up()?;
+        self.asleep = false;
+        self.in_sync = false;
+        Dimmable::set_brightness(&mut self.vfd, self.brightness.level())?;
+        self.burnin.input(now_secs());
+        self.brightness.set_limit(self.burnin.brightness_limit());
+        self.update_display()
+    }
+
+    // return mutable refrence to the back buffer to use outside this struct
+    pub fn use_frame_buffer(&mut self) -> &mut Orient<Display256x50> {
         &mut self.framebuffer
     }
 
-    fn draw_background(&mut self) {
-        self.graphics.draw_background(&mut self.framebuffer);
+    pub fn screens(&self) -> &ScreenManager {
+        &self.screens
+    }
+
+    pub fn screens_mut(&mut self) -> &mut ScreenManager {
+        &mut self.screens
     }
 
-    fn draw_mode(&mut self) {
-        self.graphics
-            .draw_climate_control_mode(self.backend.mode(), &mut self.framebuffer);
-        self.graphics
-            .draw_ac_toggle(self.backend.ac_toggle(), &mut self.framebuffer);
-        self.graphics
-            .draw_recirc_toggle(self.backend.recirc_toggle(), &mut self.framebuffer);
+    /// Take up the climate control's state for the pages to show.
+    ///
+    /// Returns true if it changed and the display needs updating.
+    pub fn set_backend(&mut self, backend: &ClimateControlBacker) -> bool {
+        let changed = self.backend != *backend;
+        self.backend = *backend;
+        changed
+    }
+
+    /// Pass a button event on to the page on screen and apply any change
+    /// made in the settings menu.
+    ///
+    /// Returns true if the display needs updating.
+    pub fn handle_event(&mut self, event: ButtonEvent) -> bool {
+        if let ButtonEvent::Press(_) = event {
+            let woke = self.burnin.input(now_secs());
+            self.brightness.set_limit(self.burnin.brightness_limit());
+            if woke {
+                // the first press only wakes the screen up
+                return true;
+            }
+        }
+
+        let redraw = self.screens.handle_event(event, Instant::now().as_millis());
+        self.brightness
+            .set_offset(self.screens.config().brightness_offset);
+        redraw
     }
 
-    fn draw_temps(&mut self) {
-        self.graphics
-            .draw_internal_temp(self.backend.set_temp(), &mut self.framebuffer);
-        self.graphics
-            .draw_ambient_temp(self.backend.ambient_temp(), &mut self.framebuffer);
+    /// True once the page on screen has gone longer than it wants without
+    /// a redraw, for pages like diagnostics that show live values. Check it
+    /// every time round the main loop and call [`Display::update_display`]
+    /// when it says so.
+    pub fn refresh_due(&self) -> bool {
+        match self.screens.refresh_interval() {
+            Some(interval) => self.drawn_at.elapsed() >= Duration::from_millis(interval),
+            None => false,
+        }
+    }
+
+    pub fn update_display(&mut self) -> Result<(), VfdError> {
+        if self.asleep {
+            return Ok(());
+        }
+        let ctx = PageContext {
+            graphics: &self.graphics,
+            backend: &self.backend,
+            diagnostics: &DIAGNOSTICS,
+            config: *self.screens.config(),
+            now: Instant::now().as_millis(),
+        };
+        self.drawn_at = Instant::now();
+        let now = now_secs();
+        match self.burnin.state() {
+            IdleState::Active | IdleState::Dimmed => {
+                self.shift = self.burnin.shift(now);
+                self.screens
+                    .draw(&ctx, &mut self.framebuffer.translated(self.shift));
+                self.dim_for_transition()?;
+            }
+            IdleState::Saver => {
+                _ = self.framebuffer.clear(BinaryColor::Off);
+                let area = Size::new(SCREEN_WIDTH as u32, HEIGHT);
+                let position =
+                    self.burnin
+                        .saver_position(now, self.graphics.screensaver_size(), area);
+                self.graphics
+                    .draw_screensaver(position, &mut self.framebuffer);
+            }
+            IdleState::Blank => {
+                _ = self.framebuffer.clear(BinaryColor::Off);
+            }
+        }
+        self.present()
     }
 
-    fn draw_fan_gauge(&mut self) {
-        //5 HI 37 LO
-        if self.fan_gauge > 32 {
-            return;
+    /// Dim the display as far as a fade between pages wants, and back to
+    /// the brightness controller's level once it's over
+    fn dim_for_transition(&mut self) -> Result<(), VfdError> {
+        let share = self.screens.brightness();
+        let dimmed = share != Progress::FULL;
+        if dimmed || self.transition_dimmed {
+            let level = share.of(self.brightness.level().into()) as u16;
+            Dimmable::set_brightness(&mut self.vfd, level)?;
         }
-        let pos = map_i32(self.fan_gauge.into(), 0, 32, 37, 5) as i32;
-        self.graphics.draw_fan_gauge(pos, &mut self.framebuffer);
+        self.transition_dimmed = dimmed;
+        Ok(())
     }
 
-    fn draw_temp_gauge(&mut self) {
-        // 42 HOT 5 COLD
-        if self.temp_gauge > 36 {
-            return ();
+    /// Send the frame drawn into the back buffer, then swap the buffers.
+    ///
+    /// Only the spans that differ from the front buffer go to the VFD, unless
+    /// its display RAM is out of sync, then the whole frame does. Everything
+    /// drawing a frame clears the back buffer first, so what it held before
+    /// the swap doesn't matter.
+    fn present(&mut self) -> Result<(), VfdError> {
+        // stays false if a write fails half way
+        if core::mem::take(&mut self.in_sync) {
+            let back = self.framebuffer.buffer();
+            for span in changed_spans(FRAME_LAYOUT, self.front.buffer(), back) {
+                let data = span.gather(FRAME_LAYOUT, back, &mut self.span_buffer);
+                self.vfd.update_partial_frame(
+                    data,
+                    span.x(),
+                    span.y(),
+                    span.width(),
+                    span.height(),
+                )?;
+            }
+        } else {
+            self.vfd.update_frame(self.framebuffer.buffer())?;
         }
-        let pos = map_i32(self.temp_gauge.into(), 0, 36, 42, 5) as i32;
-        self.graphics.draw_temp_guage(pos, &mut self.framebuffer);
-    }
-
-    pub fn update_display(&mut self) {
-        self.vfd.clear_frame().unwrap();
-        self.draw_background();
-        self.draw_mode();
-        self.draw_fan_gauge();
-        self.draw_temp_gauge();
-        self.draw_temps();
-        self.vfd.update_frame(self.framebuffer.data()).unwrap();
+        core::mem::swap(&mut self.front, &mut *self.framebuffer);
+        self.in_sync = true;
+        Ok(())
     }
 }
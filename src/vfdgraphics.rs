@@ -1,30 +1,74 @@
-use crate::climatecontrol::ClimateControlMode;
 use alloc::format;
-use embedded_graphics::mono_font::MonoTextStyle;
-use embedded_graphics::mono_font::ascii::{FONT_7X13, FONT_8X13_BOLD};
 use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::*;
-use embedded_graphics::primitives::{PrimitiveStyle, Triangle};
-use embedded_graphics::text::Text;
-use tinybmp::Bmp;
-
-const FAIRLADYBMP: &'static [u8] = include_bytes!("../assets/fairlady.bmp");
-const CC_BACKGROUND: &'static [u8] = include_bytes!("../assets/ClimateControlBackground.bmp");
-const CC_FACE: &'static [u8] = include_bytes!("../assets/Face.bmp");
-const CC_FEET: &'static [u8] = include_bytes!("../assets/Feet.bmp");
-const CC_FACE_FEET: &'static [u8] = include_bytes!("../assets/FaceandFeet.bmp");
-const CC_DEF: &'static [u8] = include_bytes!("../assets/Def.bmp");
-const CC_DEFSYM: &'static [u8] = include_bytes!("../assets/DefSymbol.bmp");
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::{Alignment, Text};
+use embedded_graphics_transform::ViewportExt;
+
+use crate::fonts::{self, FontStyle};
+
+/// Height of one settings menu row, three rows fit on screen
+pub const MENU_ROW_HEIGHT: i32 = 16;
+
+/// Bold text, for values such as temperatures
+pub const VALUE_STYLE: FontStyle = FontStyle::new(&fonts::TEXT_13B, BinaryColor::On);
+/// Plain text, for labels
+pub const LABEL_STYLE: FontStyle = FontStyle::new(&fonts::TEXT_13, BinaryColor::On);
+/// Seven segment numerals the size of the bold text, for readings
+pub const SEGMENT_STYLE: FontStyle = FontStyle::new(&fonts::SEG7_13, BinaryColor::On);
+/// Seven segment numerals filling most of the screen's height
+pub const LARGE_SEGMENT_STYLE: FontStyle = FontStyle::new(&fonts::SEG7_32, BinaryColor::On);
+/// Plain text on a lit background
+const SELECTED_STYLE: FontStyle = FontStyle::new(&fonts::TEXT_13, BinaryColor::Off);
 
 pub trait BinaryTarget: DrawTarget<Color = BinaryColor> {}
 impl<T> BinaryTarget for T where T: DrawTarget<Color = BinaryColor> {}
 
-pub struct Image {
-    data: Bmp<'static, BinaryColor>,
+/// A 1bpp image packed at build time from a BMP in `assets/`.
+///
+/// Pixels are stored column by column, top to bottom and most significant
+/// bit first, so each column is `height / 8` bytes rounded up. That is how
+/// `Display256x50` lays out a column of the display RAM.
+pub struct PackedImage {
+    width: u32,
+    height: u32,
     offset: Point,
+    data: &'static [u8],
+}
+
+impl PackedImage {
+    pub const fn new(width: u32, height: u32, offset: Point, data: &'static [u8]) -> Self {
+        PackedImage {
+            width,
+            height,
+            offset,
+            data,
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    pub fn offset(&self) -> Point {
+        self.offset
+    }
+
+    pub fn data(&self) -> &'static [u8] {
+        self.data
+    }
+
+    fn column_bytes(&self) -> usize {
+        self.height.div_ceil(8) as usize
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> BinaryColor {
+        let byte = self.data[x as usize * self.column_bytes() + y as usize / 8];
+        (byte & (0x80 >> (y % 8)) != 0).into()
+    }
 }
 
-impl Drawable for Image {
+impl Drawable for PackedImage {
     type Color = BinaryColor;
     type Output = ();
 
@@ -32,70 +76,174 @@ impl Drawable for Image {
     where
         D: DrawTarget<Color = Self::Color>,
     {
-        self.data.draw(&mut target.translated(self.offset))
+        let area = Rectangle::new(self.offset, self.size());
+        let colors = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.pixel(x, y));
+        target.fill_contiguous(&area, colors)
     }
 }
 
-pub struct Graphics {
-    boot: Image,
-    background: Image,
-    cc_face: Image,
-    cc_feet: Image,
-    cc_face_feet: Image,
-    cc_def: Image,
-    cc_defsym: Image,
-    fill: PrimitiveStyle<BinaryColor>,
-    temp_font: MonoTextStyle<'static, BinaryColor>,
-    off_font: MonoTextStyle<'static, BinaryColor>,
+/// Images packed from `assets/` by the build script
+pub mod assets {
+    use super::PackedImage;
+    use embedded_graphics::prelude::Point;
+
+    include!(concat!(env!("OUT_DIR"), "/assets.rs"));
 }
 
-impl Graphics {
-    pub fn load() -> Self {
-        let boot = Image {
-            data: Bmp::from_slice(FAIRLADYBMP).unwrap(),
-            offset: Point::new(5, 14),
-        };
-        let background = Image {
-            data: Bmp::from_slice(CC_BACKGROUND).unwrap(),
-            offset: Point::new(0, 0),
-        };
-        let cc_face = Image {
-            data: Bmp::from_slice(CC_FACE).unwrap(),
-            offset: Point::new(135, 2),
-        };
-        let cc_feet = Image {
-            data: Bmp::from_slice(CC_FEET).unwrap(),
-            offset: Point::new(135, 2),
-        };
-        let cc_face_feet = Image {
-            data: Bmp::from_slice(CC_FACE_FEET).unwrap(),
-            offset: Point::new(135, 2),
-        };
-        let cc_def = Image {
-            data: Bmp::from_slice(CC_DEF).unwrap(),
-            offset: Point::new(135, 2),
-        };
-        let cc_defsym = Image {
-            data: Bmp::from_slice(CC_DEFSYM).unwrap(),
-            offset: Point::new(130, 2),
-        };
+/// The widgets of the climate page, laid out to go with its background
+pub mod climate {
+    use super::{LABEL_STYLE, SEGMENT_STYLE, VALUE_STYLE, assets};
+    use crate::climatecontrol::ClimateControlMode;
+    use crate::fonts::FontStyle;
+    use crate::widgets::{Axis, Icon, NumericField, PointerGauge, ToggleLabel};
+    use embedded_graphics::prelude::*;
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::text::Alignment;
+
+    pub const AC_TOGGLE: ToggleLabel = ToggleLabel::new(
+        Rectangle::new(Point::new(104, 8), Size::new(23, 15)),
+        ("ON", VALUE_STYLE),
+        ("OFF", LABEL_STYLE),
+    );
+    pub const RECIRC_TOGGLE: ToggleLabel = ToggleLabel::new(
+        Rectangle::new(Point::new(104, 33), Size::new(23, 15)),
+        ("ON", VALUE_STYLE),
+        ("OFF", LABEL_STYLE),
+    );
+
+    pub const INTERNAL_TEMP: NumericField<FontStyle> = NumericField::new(
+        Rectangle::new(Point::new(32, 2), Size::new(28, 13)),
+        SEGMENT_STYLE,
+    )
+    .with_alignment(Alignment::Right);
+    pub const AMBIENT_TEMP: NumericField<FontStyle> = NumericField::new(
+        Rectangle::new(Point::new(32, 27), Size::new(28, 13)),
+        SEGMENT_STYLE,
+    )
+    .with_alignment(Alignment::Right);
 
-        let temp_font = MonoTextStyle::new(&FONT_8X13_BOLD, BinaryColor::On);
-        let off_font = MonoTextStyle::new(&FONT_7X13, BinaryColor::On);
+    /// Fan speed, 0 (low) to 32 (high), against the scale in the background
+    pub const FAN_GAUGE: PointerGauge = PointerGauge::new(
+        Rectangle::new(Point::new(92, 3), Size::new(3, 37)),
+        Axis::Vertical,
+        0,
+        32,
+    );
+    /// Temperature, 0 (cold) to 36 (hot), against the scale in the background
+    pub const TEMP_GAUGE: PointerGauge = PointerGauge::new(
+        Rectangle::new(Point::new(21, 3), Size::new(3, 42)),
+        Axis::Vertical,
+        0,
+        36,
+    );
 
-        let fill = PrimitiveStyle::with_fill(BinaryColor::On);
+    /// Where the air goes, see [`mode_state`]
+    pub const MODE: Icon = Icon::new(
+        Point::new(135, 2),
+        &[
+            &assets::FACE,
+            &assets::FEET,
+            &assets::FACEANDFEET,
+            &assets::FEET,
+            &assets::DEF,
+        ],
+    );
+    /// On top of [`MODE`] in the modes that defrost
+    pub const DEFROST: Icon = Icon::new(Point::new(130, 2), &[&assets::DEFSYMBOL]);
 
+    /// [`MODE`]'s state for `mode`, and whether [`DEFROST`] is shown too
+    pub fn mode_state(mode: &ClimateControlMode) -> (usize, bool) {
+        match mode {
+            ClimateControlMode::Face => (0, false),
+            ClimateControlMode::Feet => (1, false),
+            ClimateControlMode::FaceFeet => (2, false),
+            ClimateControlMode::FeetDef => (3, true),
+            ClimateControlMode::Def => (4, true),
+        }
+    }
+}
+
+/// The boot animation, the digital dash's tachometer sweep and then the logo
+pub mod boot {
+    use super::assets;
+    use crate::animation::{Content, Cue, Direction, Easing, Keyframe, Motion, Sequence, Track};
+    use embedded_graphics::prelude::*;
+    use embedded_graphics::primitives::Rectangle;
+
+    const LENGTH: u32 = 2000;
+
+    /// The bar graph sweeping up to the red line and back
+    const SWEEP: Cue = Cue {
+        start: 0,
+        end: 900,
+        content: Content::Bars {
+            area: Rectangle::new(Point::new(2, 4), Size::new(252, 40)),
+            count: 28,
+        },
+        motion: Motion::Wipe {
+            direction: Direction::LeftToRight,
+            shown: Track::new(
+                &[
+                    Keyframe { at: 0, value: 0 },
+                    Keyframe {
+                        at: 450,
+                        value: 1000,
+                    },
+                    Keyframe { at: 900, value: 0 },
+                ],
+                Easing::InOut,
+            ),
+        },
+    };
+
+    /// The Fairlady logo following the sweep back down, then staying put
+    const LOGO: Cue = Cue {
+        start: 700,
+        end: LENGTH,
+        content: Content::Image(&assets::FAIRLADY),
+        motion: Motion::Wipe {
+            direction: Direction::LeftToRight,
+            shown: Track::new(
+                &[
+                    Keyframe { at: 0, value: 0 },
+                    Keyframe {
+                        at: 500,
+                        value: 1000,
+                    },
+                ],
+                Easing::Out,
+            ),
+        },
+    };
+
+    pub const SEQUENCE: Sequence = Sequence {
+        length: LENGTH,
+        cues: &[SWEEP, LOGO],
+        brightness: Track::new(
+            &[
+                Keyframe { at: 0, value: 0 },
+                Keyframe {
+                    at: 250,
+                    value: 1000,
+                },
+            ],
+            Easing::Out,
+        ),
+    };
+}
+
+pub struct Graphics {
+    boot: &'static PackedImage,
+    background: &'static PackedImage,
+}
+
+impl Graphics {
+    pub fn load() -> Self {
         Self {
-            boot,
-            background,
-            cc_face,
-            cc_feet,
-            cc_face_feet,
-            cc_def,
-            cc_defsym,
-            temp_font,
-            off_font,
-            fill,
+            boot: &assets::FAIRLADY,
+            background: &assets::CLIMATECONTROLBACKGROUND,
         }
     }
 
@@ -103,85 +251,67 @@ impl Graphics {
         _ = self.boot.draw(display)
     }
 
-    pub fn draw_background<D: BinaryTarget>(&self, display: &mut D) {
-        _ = self.background.draw(display)
+    /// Size of the item that moves around on the screen saver
+    pub fn screensaver_size(&self) -> Size {
+        self.boot.size()
     }
 
-    pub fn draw_climate_control_mode<D: BinaryTarget>(
-        &self,
-        mode: &ClimateControlMode,
-        display: &mut D,
-    ) {
-        match mode {
-            ClimateControlMode::Face => _ = self.cc_face.draw(display),
-            ClimateControlMode::Feet => _ = self.cc_feet.draw(display),
-            ClimateControlMode::FaceFeet => _ = self.cc_face_feet.draw(display),
-            ClimateControlMode::FeetDef => {
-                _ = self.cc_feet.draw(display);
-                _ = self.cc_defsym.draw(display);
-            }
-            ClimateControlMode::Def => {
-                _ = self.cc_def.draw(display);
-                _ = self.cc_defsym.draw(display);
-            }
-        }
+    /// Screen saver frame, the boot logo with its top left corner at `position`
+    pub fn draw_screensaver<D: BinaryTarget>(&self, position: Point, display: &mut D) {
+        _ = self
+            .boot
+            .draw(&mut display.translate(position - self.boot.offset()))
     }
 
-    pub fn draw_ac_toggle<D: BinaryTarget>(&self, toggle_on: bool, display: &mut D) {
-        match toggle_on {
-            true => _ = Text::new("ON", Point::new(107, 19), self.temp_font).draw(display),
-            false => _ = Text::new("OFF", Point::new(105, 19), self.off_font).draw(display),
-        }
+    pub fn draw_background<D: BinaryTarget>(&self, display: &mut D) {
+        _ = self.background.draw(display)
     }
 
-    pub fn draw_recirc_toggle<D: BinaryTarget>(&self, toggle_on: bool, display: &mut D) {
-        match toggle_on {
-            true => _ = Text::new("ON", Point::new(107, 44), self.temp_font).draw(display),
-            false => _ = Text::new("OFF", Point::new(105, 44), self.off_font).draw(display),
-        }
+    /// Plain text in the small font, `position` is the left end of the baseline
+    pub fn draw_label<D: BinaryTarget>(&self, text: &str, position: Point, display: &mut D) {
+        _ = Text::new(text, position, LABEL_STYLE).draw(display);
     }
 
-    pub fn draw_internal_temp<D: BinaryTarget>(&self, temp: i8, display: &mut D) {
-        let point = {
-            if temp < 0 {
-                Point::new(35, 12)
-            } else {
-                Point::new(43, 12)
-            }
-        };
-
-        _ = Text::new(&format!("{temp}"), point, self.temp_font).draw(display);
+    /// Text in the bold font used for temperatures
+    pub fn draw_value<D: BinaryTarget>(&self, text: &str, position: Point, display: &mut D) {
+        _ = Text::new(text, position, VALUE_STYLE).draw(display);
     }
 
-    pub fn draw_ambient_temp<D: BinaryTarget>(&self, temp: i8, display: &mut D) {
-        let point = {
-            if temp < 0 {
-                Point::new(35, 37)
-            } else {
-                Point::new(43, 37)
-            }
+    /// One row of the settings menu, label on the left and value on the right.
+    ///
+    /// The selected row is drawn inverted and a value being edited gets arrows
+    /// either side.
+    pub fn draw_menu_row<D: BinaryTarget>(
+        &self,
+        row: i32,
+        label: &str,
+        value: &str,
+        selected: bool,
+        editing: bool,
+        display: &mut D,
+    ) {
+        let area = Rectangle::new(
+            Point::new(0, row * MENU_ROW_HEIGHT),
+            Size::new(256, MENU_ROW_HEIGHT as u32),
+        );
+        let display = &mut display.viewport(area);
+        let baseline = 11;
+        let font = if selected {
+            _ = display.clear(BinaryColor::On);
+            SELECTED_STYLE
+        } else {
+            LABEL_STYLE
         };
 
-        _ = Text::new(&format!("{temp}"), point, self.temp_font).draw(display);
-    }
-
-    pub fn draw_fan_gauge<D: BinaryTarget>(&self, pos: i32, display: &mut D) {
-        _ = Triangle::new(
-            Point::new(92, pos),
-            Point::new(94, pos - 2),
-            Point::new(94, pos + 2),
-        )
-        .into_styled(self.fill)
-        .draw(display);
-    }
-
-    pub fn draw_temp_guage<D: BinaryTarget>(&self, pos: i32, display: &mut D) {
-        _ = Triangle::new(
-            Point::new(21, pos),
-            Point::new(23, pos - 2),
-            Point::new(23, pos + 2),
-        )
-        .into_styled(self.fill)
-        .draw(display);
+        _ = Text::new(label, Point::new(4, baseline), font).draw(display);
+        let edited;
+        let value = if editing {
+            edited = format!("< {value} >");
+            &edited
+        } else {
+            value
+        };
+        _ = Text::with_alignment(value, Point::new(252, baseline), font, Alignment::Right)
+            .draw(display);
     }
 }
@@ -0,0 +1,392 @@
+//! Reusable pieces of the VFD screens.
+//!
+//! A widget knows where it goes and how it looks, and is drawn from a value:
+//! a gauge from a reading, a toggle from whether it's on. Everything is drawn
+//! in a viewport of the widget's [`area`](Widget::area), so nothing spills
+//! onto what's around it, and the widgets can be `const`s that a page puts
+//! together with a background image.
+//!
+//! The area isn't cleared first, the screens are cleared before each frame.
+
+use alloc::format;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle, Triangle};
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text};
+use embedded_graphics_transform::ViewportExt;
+
+use crate::fonts::{FontStyle, align_text};
+use crate::vfdgraphics::{BinaryTarget, PackedImage};
+
+const FILL: PrimitiveStyle<BinaryColor> = PrimitiveStyle::with_fill(BinaryColor::On);
+const OUTLINE: PrimitiveStyle<BinaryColor> = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+
+/// Something on screen that shows a value
+pub trait Widget {
+    /// What the widget shows
+    type Value: Copy;
+
+    /// Where the widget goes, nothing is drawn outside of it
+    fn area(&self) -> Rectangle;
+
+    /// Draw the widget showing `value`
+    fn draw<D: BinaryTarget>(&self, value: Self::Value, display: &mut D);
+}
+
+/// Which way a gauge or bar runs
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// Bottom to top
+    Vertical,
+    /// Left to right
+    Horizontal,
+}
+
+impl Axis {
+    /// Length of `size` along the axis
+    const fn length(self, size: Size) -> u32 {
+        match self {
+            Axis::Vertical => size.height,
+            Axis::Horizontal => size.width,
+        }
+    }
+}
+
+/// How far along `length` pixels `value` is, from 0 at `min` to
+/// `length` at `max`
+fn scale_value(value: i32, min: i32, max: i32, length: u32) -> u32 {
+    let span = (max - min).max(1);
+    ((value - min).clamp(0, span) as i64 * length as i64 / span as i64) as u32
+}
+
+/// Tick marks along a [`PointerGauge`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GaugeScale {
+    /// Steps between the ticks, there's one at either end as well
+    pub divisions: u32,
+    /// How long each tick is
+    pub length: u32,
+}
+
+/// A pointer moving along a scale.
+///
+/// The scale is the left side of a vertical gauge, with the lowest value at
+/// the bottom, or the top of a horizontal one, with the lowest value on the
+/// left. The pointer is a triangle pointing at it. Values outside of the
+/// range don't show a pointer at all, and neither does an area too short for
+/// the pointer to move.
+///
+/// Without a [`GaugeScale`] the scale is left to whatever is drawn around the
+/// gauge, e.g. the background image.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PointerGauge {
+    area: Rectangle,
+    axis: Axis,
+    min: i32,
+    max: i32,
+    /// How far the pointer sticks out either side of its tip
+    pointer: u32,
+    scale: Option<GaugeScale>,
+}
+
+impl PointerGauge {
+    /// Gauge from `min` to `max` along `axis` of `area`
+    pub const fn new(area: Rectangle, axis: Axis, min: i32, max: i32) -> Self {
+        PointerGauge {
+            area,
+            axis,
+            min,
+            max,
+            pointer: 2,
+            scale: None,
+        }
+    }
+
+    /// Draw the scale as well
+    pub const fn with_scale(mut self, scale: GaugeScale) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Make the pointer `size` pixels either side of its tip
+    pub const fn with_pointer(mut self, size: u32) -> Self {
+        self.pointer = size;
+        self
+    }
+
+    /// Where along the axis the pointer is at `offset`, the ends keep the
+    /// whole pointer inside the area
+    fn along(&self, offset: u32) -> i32 {
+        match self.axis {
+            Axis::Vertical => {
+                let length = self.axis.length(self.area.size);
+                length.saturating_sub(1 + self.pointer + offset) as i32
+            }
+            Axis::Horizontal => (self.pointer + offset) as i32,
+        }
+    }
+
+    /// Tip at `along` and `across`, pointing back across the axis
+    fn pointer(&self, along: i32, across: i32) -> Triangle {
+        let size = self.pointer as i32;
+        let point = |along, across| match self.axis {
+            Axis::Vertical => Point::new(across, along),
+            Axis::Horizontal => Point::new(along, across),
+        };
+        Triangle::new(
+            point(along, across),
+            point(along - size, across + size),
+            point(along + size, across + size),
+        )
+    }
+}
+
+impl Widget for PointerGauge {
+    type Value = i32;
+
+    fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    fn draw<D: BinaryTarget>(&self, value: i32, display: &mut D) {
+        let display = &mut display.viewport(self.area);
+        // nothing to move along when the area is too small for the pointer
+        let travel = self
+            .axis
+            .length(self.area.size)
+            .saturating_sub(1 + 2 * self.pointer);
+
+        let mut across = 0;
+        if let Some(scale) = self.scale {
+            let divisions = scale.divisions.max(1);
+            for tick in 0..=divisions {
+                let along = self.along(tick * travel / divisions);
+                let tick = match self.axis {
+                    Axis::Vertical => {
+                        Rectangle::new(Point::new(0, along), Size::new(scale.length, 1))
+                    }
+                    Axis::Horizontal => {
+                        Rectangle::new(Point::new(along, 0), Size::new(1, scale.length))
+                    }
+                };
+                _ = display.fill_solid(&tick, BinaryColor::On);
+            }
+            // a pixel apart from the ticks
+            across = scale.length as i32 + 1;
+        }
+
+        if travel > 0 && (self.min..=self.max).contains(&value) {
+            let along = self.along(scale_value(value, self.min, self.max, travel));
+            _ = self.pointer(along, across).into_styled(FILL).draw(display);
+        }
+    }
+}
+
+/// A bar filling up from the bottom, or from the left, as the value goes up.
+///
+/// Values outside of the range show an empty or a full bar.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BarGraph {
+    area: Rectangle,
+    axis: Axis,
+    min: i32,
+    max: i32,
+    outline: bool,
+}
+
+impl BarGraph {
+    /// Bar from `min` to `max` along `axis` of `area`
+    pub const fn new(area: Rectangle, axis: Axis, min: i32, max: i32) -> Self {
+        BarGraph {
+            area,
+            axis,
+            min,
+            max,
+            outline: false,
+        }
+    }
+
+    /// Draw a frame around the area, with the bar a pixel inside of it
+    pub const fn outlined(mut self) -> Self {
+        self.outline = true;
+        self
+    }
+}
+
+impl Widget for BarGraph {
+    type Value = i32;
+
+    fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    fn draw<D: BinaryTarget>(&self, value: i32, display: &mut D) {
+        let display = &mut display.viewport(self.area);
+        let mut inside = display.bounding_box();
+        if self.outline {
+            _ = inside.into_styled(OUTLINE).draw(display);
+            inside = inside.offset(-2);
+        }
+
+        let length = scale_value(value, self.min, self.max, self.axis.length(inside.size));
+        let bar = match self.axis {
+            Axis::Vertical => Rectangle::new(
+                inside.top_left + Point::new(0, (inside.size.height - length) as i32),
+                Size::new(inside.size.width, length),
+            ),
+            Axis::Horizontal => {
+                Rectangle::new(inside.top_left, Size::new(length, inside.size.height))
+            }
+        };
+        _ = display.fill_solid(&bar, BinaryColor::On);
+    }
+}
+
+/// A number followed by its units, e.g. `72F`.
+///
+/// The text is aligned across the area and centred down it, in a mono font
+/// or one of the [`fonts`](crate::fonts).
+#[derive(Copy, Clone, Debug)]
+pub struct NumericField<S = FontStyle> {
+    area: Rectangle,
+    style: S,
+    alignment: Alignment,
+    units: &'static str,
+    hanging_sign: bool,
+}
+
+impl<S> NumericField<S> {
+    /// Left aligned number in `style`, without units
+    pub const fn new(area: Rectangle, style: S) -> Self {
+        NumericField {
+            area,
+            style,
+            alignment: Alignment::Left,
+            units: "",
+            hanging_sign: false,
+        }
+    }
+
+    pub const fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Put `units` straight after the number
+    pub const fn with_units(mut self, units: &'static str) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Keep the first character's width free on the left for a minus sign,
+    /// so the digits stay put whatever the sign
+    pub const fn with_hanging_sign(mut self) -> Self {
+        self.hanging_sign = true;
+        self
+    }
+}
+
+impl<S: TextRenderer<Color = BinaryColor> + Copy> Widget for NumericField<S> {
+    type Value = i32;
+
+    fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    fn draw<D: BinaryTarget>(&self, value: i32, display: &mut D) {
+        let display = &mut display.viewport(self.area);
+        let text = format!("{value}{}", self.units);
+        if !self.hanging_sign {
+            let position = align_text(&text, &self.style, self.alignment, self.area.size);
+            _ = Text::with_baseline(&text, position, self.style, Baseline::Top).draw(display);
+            return;
+        }
+
+        // aligned without the sign, in the area right of the sign's room
+        let sign = self
+            .style
+            .measure_string("-", Point::zero(), Baseline::Top)
+            .next_position
+            .x;
+        let size = Size::new(
+            self.area.size.width.saturating_sub(sign as u32),
+            self.area.size.height,
+        );
+        let digits = text.strip_prefix('-').unwrap_or(&text);
+        let mut position =
+            Point::new(sign, 0) + align_text(digits, &self.style, self.alignment, size);
+        if value < 0 {
+            position.x -= sign;
+        }
+        _ = Text::with_baseline(&text, position, self.style, Baseline::Top).draw(display);
+    }
+}
+
+/// Text for either state of a switch, e.g. ON and OFF, centred in its area
+#[derive(Copy, Clone, Debug)]
+pub struct ToggleLabel<S = FontStyle> {
+    area: Rectangle,
+    on: (&'static str, S),
+    off: (&'static str, S),
+}
+
+impl<S> ToggleLabel<S> {
+    /// `on` and `off` are the text and style for either state
+    pub const fn new(area: Rectangle, on: (&'static str, S), off: (&'static str, S)) -> Self {
+        ToggleLabel { area, on, off }
+    }
+}
+
+impl<S: TextRenderer<Color = BinaryColor> + Copy> Widget for ToggleLabel<S> {
+    type Value = bool;
+
+    fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    fn draw<D: BinaryTarget>(&self, on: bool, display: &mut D) {
+        let display = &mut display.viewport(self.area);
+        let (text, style) = if on { self.on } else { self.off };
+        let position = align_text(text, &style, Alignment::Center, self.area.size);
+        _ = Text::with_baseline(text, position, style, Baseline::Top).draw(display);
+    }
+}
+
+/// A picture for each of a number of states, e.g. the climate control modes.
+///
+/// The value is the index of the state's picture. The pictures are drawn with
+/// their top left corner at the icon's position, whatever their own offset.
+/// A state past the last picture draws nothing.
+#[derive(Copy, Clone)]
+pub struct Icon {
+    position: Point,
+    states: &'static [&'static PackedImage],
+}
+
+impl Icon {
+    pub const fn new(position: Point, states: &'static [&'static PackedImage]) -> Self {
+        Icon { position, states }
+    }
+}
+
+impl Widget for Icon {
+    type Value = usize;
+
+    /// Big enough for the biggest picture
+    fn area(&self) -> Rectangle {
+        let size = self
+            .states
+            .iter()
+            .fold(Size::zero(), |size, image| size.component_max(image.size()));
+        Rectangle::new(self.position, size)
+    }
+
+    fn draw<D: BinaryTarget>(&self, state: usize, display: &mut D) {
+        let Some(image) = self.states.get(state) else {
+            return;
+        };
+        let display = &mut display.viewport(self.area());
+        _ = image.draw(&mut display.translate(-image.offset()));
+    }
+}